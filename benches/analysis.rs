@@ -0,0 +1,107 @@
+//! Lexing, indexing, diagnostics and formatting over large sources.
+//!
+//! A generated file with 10k definitions is always measured. Set `FORTH_LSP_CORPUS` to a
+//! directory of real Forth code to also measure every `.forth`, `.fs`, `.fth` and `.4th`
+//! file below it, e.g.
+//!
+//! ```sh
+//! FORTH_LSP_CORPUS=~/src/gforth cargo bench
+//! ```
+
+use std::{env, fs, hint::black_box, path::Path};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use forth_lexer::parser::Lexer;
+use forth_lsp::{
+    config::FormatConfig, definition_index::DefinitionIndex, diagnostics::get_diagnostics,
+    formatter::format_source, words::Words,
+};
+use ropey::Rope;
+
+const DEFINITIONS: usize = 10_000;
+const CORPUS_EXTENSIONS: [&str; 4] = ["forth", "fs", "fth", "4th"];
+
+/// Definitions calling each other, with comments, control structures, strings and
+/// declarations mixed in
+fn generated_source(definitions: usize) -> String {
+    let mut source = String::from("\\ generated benchmark source\n\nvariable counter\n");
+    for ix in 0..definitions {
+        source.push_str(&format!(
+            ": word-{ix} ( n -- n ) \\ step {ix}\n  dup 0< if\n    negate\n  else\n    {} +\n  then\n",
+            ix % 7
+        ));
+        if ix > 0 {
+            source.push_str(&format!("  word-{} 1 counter +!\n", ix - 1));
+        }
+        if ix % 100 == 0 {
+            source.push_str("  .\" checkpoint\" cr\n");
+        }
+        source.push_str(";\n\n");
+    }
+    source
+}
+
+/// Concatenated sources below `FORTH_LSP_CORPUS`, if set
+fn corpus() -> Option<String> {
+    let root = env::var("FORTH_LSP_CORPUS").ok()?;
+    let mut source = String::new();
+    collect_corpus(Path::new(&root), &mut source);
+    (!source.is_empty()).then_some(source)
+}
+
+fn collect_corpus(dir: &Path, source: &mut String) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_corpus(&path, source);
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| CORPUS_EXTENSIONS.contains(&extension))
+        {
+            if let Ok(content) = fs::read(&path) {
+                source.push_str(&String::from_utf8_lossy(&content));
+                source.push('\n');
+            }
+        }
+    }
+}
+
+fn bench_source(c: &mut Criterion, name: &str, source: &str) {
+    let rope = Rope::from_str(source);
+    let data = Words::default();
+    let mut index = DefinitionIndex::default();
+    index.update_file("bench.forth", &rope);
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.sample_size(10);
+    group.bench_function("lex", |b| {
+        b.iter(|| Lexer::new(black_box(source)).parse().len())
+    });
+    group.bench_function("update_file", |b| {
+        let mut index = DefinitionIndex::default();
+        b.iter(|| index.update_file("bench.forth", black_box(&rope)))
+    });
+    group.bench_function("diagnostics", |b| {
+        b.iter(|| get_diagnostics(black_box(&rope), &data, &index).len())
+    });
+    group.bench_function("format", |b| {
+        let config = FormatConfig::default();
+        b.iter(|| format_source(black_box(source), &config, &data).len())
+    });
+    group.finish();
+}
+
+fn analysis(c: &mut Criterion) {
+    bench_source(c, "generated", &generated_source(DEFINITIONS));
+    if let Some(source) = corpus() {
+        bench_source(c, "corpus", &source);
+    }
+}
+
+criterion_group!(benches, analysis);
+criterion_main!(benches);
@@ -0,0 +1,278 @@
+use crate::token::Token;
+
+/// Words that parse a string up to the closing delimiter
+pub const STRING_WORDS: [(&str, char); 6] = [
+    (".\"", '"'),
+    ("S\"", '"'),
+    ("S\\\"", '"'),
+    ("C\"", '"'),
+    ("ABORT\"", '"'),
+    (".(", ')'),
+];
+
+/// Words that parse the next word as a name or a character, instead of it being run
+pub const NAME_PARSERS: [&str; 5] = ["'", "[']", "POSTPONE", "CHAR", "[CHAR]"];
+
+/// A token and the state the source is in where it appears
+#[derive(Debug, PartialEq, Clone)]
+pub struct ContextToken<'a> {
+    pub token: Token<'a>,
+    /// From `:` up to and including `;`
+    pub in_definition: bool,
+    /// A `\ ` or `( ` comment
+    pub in_comment: bool,
+    /// Text of a string literal like `." hello"`, the opening word excluded
+    pub in_string: bool,
+    /// Name of the colon definition the token is part of
+    pub definition: Option<&'a str>,
+    /// Name or character parsed by one of the `NAME_PARSERS` right before it, like the `;` of
+    /// `POSTPONE ;`, which neither ends a definition nor starts a string
+    pub parsed: bool,
+}
+
+impl<'a> ContextToken<'a> {
+    /// Whether this is a word like `."` that starts a string literal
+    pub fn is_string_start(&self) -> bool {
+        match &self.token {
+            Token::Word(data) if !self.in_string && !self.parsed => {
+                let word = data.value.to_uppercase();
+                STRING_WORDS.iter().any(|(start, _)| *start == word)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Annotates tokens with `ContextToken` state
+pub struct ContextTokens<'a, I: Iterator<Item = Token<'a>>> {
+    tokens: std::iter::Peekable<I>,
+    in_definition: bool,
+    definition: Option<&'a str>,
+    closing: Option<char>,
+    /// The string is an `S\"` one, where `\"` does not close it
+    escapes: bool,
+    /// The next token is parsed by the word before it
+    naming: bool,
+}
+
+impl<'a, I: Iterator<Item = Token<'a>>> ContextTokens<'a, I> {
+    pub fn new(tokens: I) -> ContextTokens<'a, I> {
+        ContextTokens {
+            tokens: tokens.peekable(),
+            in_definition: false,
+            definition: None,
+            closing: None,
+            escapes: false,
+            naming: false,
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = Token<'a>>> Iterator for ContextTokens<'a, I> {
+    type Item = ContextToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        let parsed = std::mem::take(&mut self.naming);
+        // a string ends with its line, closed or not
+        if let Token::Newline(_) = token {
+            self.closing = None;
+        }
+        // inside a string every token is text, whatever it would be outside of it
+        let in_string = match self.closing {
+            Some(delimiter) if !matches!(token, Token::Eof(_)) => {
+                let value = token.get_data().value;
+                if value.ends_with(delimiter) && !(self.escapes && escaped_end(value)) {
+                    self.closing = None;
+                }
+                true
+            }
+            _ => false,
+        };
+        match &token {
+            _ if in_string || parsed => {}
+            Token::Colon(_) => {
+                self.in_definition = true;
+                self.definition = match self.tokens.peek() {
+                    Some(Token::Word(name)) => Some(name.value),
+                    _ => None,
+                };
+            }
+            Token::Word(data) => {
+                let word = data.value.to_uppercase();
+                self.closing = STRING_WORDS
+                    .iter()
+                    .find(|(start, _)| *start == word)
+                    .map(|(_, delimiter)| *delimiter);
+                self.escapes = word == "S\\\"";
+                self.naming = NAME_PARSERS.contains(&word.as_str());
+            }
+            _ => {}
+        }
+        let ret = ContextToken {
+            in_definition: self.in_definition,
+            in_comment: !in_string && matches!(token, Token::Comment(_) | Token::StackComment(_)),
+            in_string,
+            definition: self.definition.filter(|_| self.in_definition),
+            parsed,
+            token,
+        };
+        if let Token::Semicolon(_) = ret.token {
+            if !ret.in_string && !parsed {
+                self.in_definition = false;
+                self.definition = None;
+            }
+        }
+        Some(ret)
+    }
+}
+
+/// Whether the last character of `value` follows an odd number of backslashes
+pub(crate) fn escaped_end(value: &str) -> bool {
+    let backslashes = value
+        .chars()
+        .rev()
+        .skip(1)
+        .take_while(|chr| *chr == '\\')
+        .count();
+    backslashes % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Lexer;
+
+    fn context(source: &str) -> Vec<(String, bool, bool, bool, Option<&str>)> {
+        let mut lexer = Lexer::new(source);
+        ContextTokens::new(lexer.parse().into_iter())
+            .filter(|context| !matches!(context.token, Token::Eof(_)))
+            .map(|context| {
+                (
+                    context.token.get_data().value.to_string(),
+                    context.in_definition,
+                    context.in_comment,
+                    context.in_string,
+                    context.definition,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_context_of_definition() {
+        assert_eq!(
+            vec![
+                ("1".to_string(), false, false, false, None),
+                (":".to_string(), true, false, false, Some("hi")),
+                ("hi".to_string(), true, false, false, Some("hi")),
+                ("( -- )".to_string(), true, true, false, Some("hi")),
+                (".\"".to_string(), true, false, false, Some("hi")),
+                ("a".to_string(), true, false, true, Some("hi")),
+                ("b\"".to_string(), true, false, true, Some("hi")),
+                (";".to_string(), true, false, false, Some("hi")),
+                ("hi".to_string(), false, false, false, None),
+            ],
+            context("1 : hi ( -- ) .\" a b\" ; hi")
+        );
+    }
+
+    #[test]
+    fn test_context_of_paren_string() {
+        let strings: Vec<bool> = context(".( x y) z")
+            .iter()
+            .map(|(_, _, _, in_string, _)| *in_string)
+            .collect();
+        assert_eq!(vec![false, true, true, false], strings);
+    }
+
+    #[test]
+    fn test_context_of_escaped_string() {
+        let strings: Vec<bool> = context("s\\\" a \\\" b\\\\\" c")
+            .iter()
+            .map(|(_, _, _, in_string, _)| *in_string)
+            .collect();
+        assert_eq!(vec![false, true, true, true, false], strings);
+    }
+
+    #[test]
+    fn test_context_of_definition_words_in_strings() {
+        assert_eq!(
+            vec![
+                (":".to_string(), true, false, false, Some("greet")),
+                ("greet".to_string(), true, false, false, Some("greet")),
+                (".\"".to_string(), true, false, false, Some("greet")),
+                ("use".to_string(), true, false, true, Some("greet")),
+                (":".to_string(), true, false, true, Some("greet")),
+                ("bogus".to_string(), true, false, true, Some("greet")),
+                ("1".to_string(), true, false, true, Some("greet")),
+                (";".to_string(), true, false, true, Some("greet")),
+                ("here\"".to_string(), true, false, true, Some("greet")),
+                ("s\"".to_string(), true, false, false, Some("greet")),
+                (";".to_string(), true, false, true, Some("greet")),
+                (":".to_string(), true, false, true, Some("greet")),
+                ("\"".to_string(), true, false, true, Some("greet")),
+                (";".to_string(), true, false, false, Some("greet")),
+                ("greet".to_string(), false, false, false, None),
+            ],
+            context(": greet .\" use : bogus 1 ; here\" s\" ; :\" ; greet")
+        );
+    }
+
+    #[test]
+    fn test_context_of_unterminated_strings() {
+        let strings: Vec<(String, bool, bool)> = context(": a .\" x ;\n: b s\" y\" ;")
+            .into_iter()
+            .map(|(value, in_definition, _, in_string, _)| (value, in_definition, in_string))
+            .collect();
+        assert_eq!(
+            vec![
+                (":".to_string(), true, false),
+                ("a".to_string(), true, false),
+                (".\"".to_string(), true, false),
+                ("x".to_string(), true, true),
+                (";".to_string(), true, true),
+                ("\n".to_string(), true, false),
+                (":".to_string(), true, false),
+                ("b".to_string(), true, false),
+                ("s\"".to_string(), true, false),
+                ("y\"".to_string(), true, true),
+                (";".to_string(), true, false),
+            ],
+            strings
+        );
+    }
+
+    #[test]
+    fn test_context_of_parsed_names() {
+        let parsed: Vec<(String, bool, bool)> = ContextTokens::new(
+            Lexer::new(": x postpone ; postpone .\" ; char : emit")
+                .parse()
+                .into_iter(),
+        )
+        .filter(|context| !matches!(context.token, Token::Eof(_)))
+        .map(|context| {
+            (
+                context.token.get_data().value.to_string(),
+                context.in_definition,
+                context.parsed,
+            )
+        })
+        .collect();
+        assert_eq!(
+            vec![
+                (":".to_string(), true, false),
+                ("x".to_string(), true, false),
+                ("postpone".to_string(), true, false),
+                (";".to_string(), true, true),
+                ("postpone".to_string(), true, false),
+                (".\"".to_string(), true, true),
+                (";".to_string(), true, false),
+                ("char".to_string(), false, false),
+                (":".to_string(), false, true),
+                ("emit".to_string(), false, false),
+            ],
+            parsed
+        );
+    }
+}
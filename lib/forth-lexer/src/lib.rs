@@ -1,2 +1,3 @@
+pub mod context;
 pub mod parser;
 pub mod token;
@@ -2,7 +2,10 @@ use std::{iter::Peekable, str::Chars};
 
 use nom::AsChar;
 
-use crate::token::{Data, Token};
+use crate::{
+    context::{escaped_end, STRING_WORDS},
+    token::{Data, Token},
+};
 
 pub enum LexError {}
 #[derive(Debug)]
@@ -11,6 +14,8 @@ pub struct Lexer<'a> {
     read_position: usize,
     ch: char,
     raw: &'a str,
+    /// Byte offset of every char of `raw` and of its end, positions count chars
+    offsets: Vec<usize>,
     input: Peekable<Chars<'a>>,
 }
 
@@ -22,6 +27,11 @@ impl<'a> Lexer<'a> {
             ch: '0',
             input: input.chars().peekable(),
             raw: input,
+            offsets: input
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain([input.len()])
+                .collect(),
         };
         lex.read_char();
 
@@ -45,45 +55,22 @@ impl<'a> Lexer<'a> {
     pub fn next_token(&mut self) -> Result<Token<'a>, LexError> {
         self.skip_whitespace();
 
+        let next = self.peek_char();
         let tok = match self.ch {
             ':' => {
                 let mut dat = self.here();
-                dat.value = &self.raw[self.position..self.read_position];
+                dat.value = self.slice(self.position, self.read_position);
                 Token::Colon(dat)
             }
             ';' => {
                 let mut dat = self.here();
-                dat.value = &self.raw[self.position..self.read_position];
+                dat.value = self.slice(self.position, self.read_position);
                 dat.end = dat.start + 1;
                 Token::Semicolon(dat)
             }
-            '%' => {
-                if self.peek_char().is_digit(2) {
-                    let ident = self.read_number();
-                    Token::Number(ident)
-                } else {
-                    let ident = self.read_ident();
-                    Token::Word(ident)
-                }
-            }
-            '&' => {
-                if self.peek_char() == 'x' || self.peek_char().is_digit(8) {
-                    let ident = self.read_number();
-                    Token::Number(ident)
-                } else {
-                    let ident = self.read_ident();
-                    Token::Word(ident)
-                }
-            }
-            '$' => {
-                if self.peek_char().is_hex_digit() {
-                    let ident = self.read_number();
-                    Token::Number(ident)
-                } else {
-                    let ident = self.read_ident();
-                    Token::Word(ident)
-                }
-            }
+            '%' if next.is_digit(2) => self.read_number_or_word(),
+            '&' if next == 'x' || next.is_digit(8) => self.read_number_or_word(),
+            '$' if next.is_hex_digit() => self.read_number_or_word(),
             '\'' => {
                 let begin = self.position;
                 if !self.peek_char().is_whitespace() {
@@ -93,13 +80,13 @@ impl<'a> Lexer<'a> {
                         let number = Data {
                             start: self.position - 2,
                             end: self.position + 1,
-                            value: &self.raw[begin..(self.position + 1)],
+                            value: self.slice(begin, self.position + 1),
                         };
                         Token::Number(number)
                     } else {
                         let mut ident = self.read_ident();
                         ident.start -= 1;
-                        ident.value = &self.raw[begin..self.position];
+                        ident.value = self.slice(begin, self.position);
                         Token::Word(ident)
                     }
                 } else {
@@ -107,36 +94,15 @@ impl<'a> Lexer<'a> {
                     Token::Word(ident)
                 }
             }
-            '0' => {
-                if self.peek_char() == 'x' || self.peek_char().is_hex_digit() {
-                    let ident = self.read_number();
-                    Token::Number(ident)
-                } else {
-                    let ident = self.read_ident();
-                    Token::Word(ident)
-                }
-            }
-            '0'..='9' => {
-                let ident = self.read_number();
-                Token::Number(ident)
-            }
-            '\\' => {
-                if self.peek_char().is_whitespace() {
-                    let comment = self.read_comment_to('\n');
-                    Token::Comment(comment)
-                } else {
-                    let ident = self.read_ident();
-                    Token::Word(ident)
-                }
+            '0' if next == 'x' || next.is_hex_digit() => self.read_number_or_word(),
+            '1'..='9' => self.read_number_or_word(),
+            '\\' if next.is_whitespace() || next == '\0' => {
+                let comment = self.read_comment_to('\n');
+                Token::Comment(comment)
             }
-            '(' => {
-                if self.peek_char().is_whitespace() {
-                    let comment = self.read_comment_to(')');
-                    Token::Comment(comment)
-                } else {
-                    let ident = self.read_ident();
-                    Token::Word(ident)
-                }
+            '(' if next.is_whitespace() || next == '\0' => {
+                let comment = self.read_comment_to(')');
+                Token::Comment(comment)
             }
             '\0' => {
                 let mut dat = self.here();
@@ -153,6 +119,12 @@ impl<'a> Lexer<'a> {
         Ok(tok)
     }
 
+    /// Text of the chars `start..end`, clamped to the end of the input
+    fn slice(&self, start: usize, end: usize) -> &'a str {
+        let last = self.offsets.len() - 1;
+        &self.raw[self.offsets[start.min(last)]..self.offsets[end.min(last)]]
+    }
+
     fn read_char(&mut self) {
         self.ch = match self.input.peek() {
             Some(ch) => *ch,
@@ -181,19 +153,24 @@ impl<'a> Lexer<'a> {
     fn read_comment_to(&mut self, to: char) -> Data<'a> {
         let start = self.position;
         let mut value = String::new();
-        while self.ch != to {
+        while self.ch != to && self.ch != '\0' {
             value.push(self.ch);
             self.read_char();
         }
-        if to == ')' {
+        if to == ')' && self.ch == to {
             value.push(self.ch);
             self.read_char();
         }
+        let mut end = self.position;
+        // the carriage return of a CRLF line break is not part of the comment
+        if to == '\n' && self.ch == '\n' && value.ends_with('\r') {
+            end -= 1;
+        }
 
         Data {
             start,
-            end: self.position,
-            value: &self.raw[start..self.position],
+            end,
+            value: self.slice(start, end),
         }
     }
 
@@ -207,7 +184,7 @@ impl<'a> Lexer<'a> {
         Data {
             start,
             end: self.position,
-            value: &self.raw[start..self.position],
+            value: self.slice(start, self.position),
         }
     }
 
@@ -228,24 +205,126 @@ impl<'a> Lexer<'a> {
         Data {
             start,
             end: self.position,
-            value: &self.raw[start..self.position],
+            value: self.slice(start, self.position),
         }
     }
 
+    /// A number, or a word that merely starts like one such as `2DROP` or `1+`
+    fn read_number_or_word(&mut self) -> Token<'a> {
+        let number = self.read_number();
+        if self.ch.is_whitespace() || self.ch == '\0' {
+            return Token::Number(number);
+        }
+        let rest = self.read_ident();
+        Token::Word(Data {
+            start: number.start,
+            end: rest.end,
+            value: self.slice(number.start, rest.end),
+        })
+    }
+
     pub fn parse(&mut self) -> Vec<Token<'a>> {
         let mut tokens = vec![];
-        while let Ok(tok) = self.next_token() {
-            match tok {
-                Token::Eof(_) => {
-                    break;
-                }
-                _ => {
-                    tokens.push(tok.clone());
-                }
+        // the line break ending a string left open on the line of its opening word
+        let mut open_until: Option<Data<'a>> = None;
+        loop {
+            let Ok(tok) = self.next_token();
+            if let Token::Eof(_) = tok {
+                break;
+            }
+            if let Some(newline) = open_until.filter(|newline| tok.get_data().start >= newline.end)
+            {
+                tokens.push(Token::Newline(newline));
+                open_until = None;
             }
+            if let (Token::Word(data), None) = (&tok, open_until) {
+                open_until = self.unterminated_string(data);
+            }
+            tokens.push(tok);
         }
         tokens
     }
+
+    /// The line break after `opener` when it starts a string literal that is not closed on
+    /// its line, where Forth ends the string
+    fn unterminated_string(&self, opener: &Data<'a>) -> Option<Data<'a>> {
+        let word = opener.value.to_uppercase();
+        let (_, delimiter) = STRING_WORDS.iter().find(|(start, _)| *start == word)?;
+        let rest = &self.raw[self.offsets[opener.end.min(self.offsets.len() - 1)]..];
+        let (line, _) = rest.split_once('\n')?;
+        let closed = line
+            .split_whitespace()
+            .any(|text| text.ends_with(*delimiter) && !(word == "S\\\"" && escaped_end(text)));
+        if closed {
+            return None;
+        }
+        let ix = opener.end + line.chars().count();
+        let start = if line.ends_with('\r') { ix - 1 } else { ix };
+        Some(Data {
+            start,
+            end: ix + 1,
+            value: self.slice(start, ix + 1),
+        })
+    }
+
+    /// Every token of [`Lexer::parse`] with the whitespace between them, so the source can
+    /// be rebuilt from the tokens alone
+    #[cfg(feature = "trivia")]
+    pub fn parse_with_trivia(&mut self) -> Vec<Token<'a>> {
+        // line breaks ending open strings are added back with the others
+        let tokens: Vec<Token<'a>> = self
+            .parse()
+            .into_iter()
+            .filter(|token| !matches!(token, Token::Newline(_)))
+            .collect();
+        let mut ret = vec![];
+        let mut at = 0;
+        for token in tokens {
+            let data = *token.get_data();
+            self.push_trivia(at, data.start, &mut ret);
+            // `:` is reported empty, the value is what the token covers
+            at = data.start + data.value.chars().count();
+            ret.push(token);
+        }
+        self.push_trivia(at, self.offsets.len() - 1, &mut ret);
+        ret
+    }
+
+    #[cfg(feature = "trivia")]
+    fn push_trivia(&self, start: usize, end: usize, ret: &mut Vec<Token<'a>>) {
+        let mut run = start;
+        for (ix, ch) in self.slice(start, end).chars().enumerate() {
+            let ix = start + ix;
+            if ch != '\n' {
+                continue;
+            }
+            let newline = if ix > run && self.slice(ix - 1, ix) == "\r" {
+                ix - 1
+            } else {
+                ix
+            };
+            if newline > run {
+                ret.push(Token::Whitespace(Data {
+                    start: run,
+                    end: newline,
+                    value: self.slice(run, newline),
+                }));
+            }
+            ret.push(Token::Newline(Data {
+                start: newline,
+                end: ix + 1,
+                value: self.slice(newline, ix + 1),
+            }));
+            run = ix + 1;
+        }
+        if end > run {
+            ret.push(Token::Whitespace(Data {
+                start: run,
+                end,
+                value: self.slice(run, end),
+            }));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -253,6 +332,25 @@ mod tests {
     use super::*;
     use Token::*;
 
+    #[test]
+    fn test_parse_ends_open_strings_with_their_line() {
+        let mut lexer = Lexer::new(".\" a\r\n; s\" b\" .( c )\n;");
+        let tokens = lexer.parse();
+        let expected = vec![
+            Word(Data::new(0, 2, ".\"")),
+            Word(Data::new(3, 4, "a")),
+            Newline(Data::new(4, 6, "\r\n")),
+            Semicolon(Data::new(6, 7, ";")),
+            Word(Data::new(8, 10, "s\"")),
+            Word(Data::new(11, 13, "b\"")),
+            Word(Data::new(14, 16, ".(")),
+            Word(Data::new(17, 18, "c")),
+            Word(Data::new(19, 20, ")")),
+            Semicolon(Data::new(21, 22, ";")),
+        ];
+        assert_eq!(tokens, expected);
+    }
+
     #[test]
     fn test_parse_proper_def() {
         let mut lexer = Lexer::new(": add1 ( n -- n )\n  1 + \\ adds one\n;");
@@ -297,6 +395,65 @@ mod tests {
         assert_eq!(tokens, expected)
     }
 
+    #[test]
+    fn test_parse_unterminated_comments() {
+        let mut lexer = Lexer::new("word \\ last line");
+        let tokens = lexer.parse();
+        assert_eq!(Comment(Data::new(5, 16, "\\ last line")), tokens[1]);
+        let mut lexer = Lexer::new("word ( still typing");
+        let tokens = lexer.parse();
+        assert_eq!(Comment(Data::new(5, 19, "( still typing")), tokens[1]);
+    }
+
+    #[test]
+    fn test_parse_comments_at_end_of_file() {
+        let mut lexer = Lexer::new("word \\");
+        assert_eq!(
+            vec![
+                Word(Data::new(0, 4, "word")),
+                Comment(Data::new(5, 6, "\\"))
+            ],
+            lexer.parse()
+        );
+        let mut lexer = Lexer::new("word (");
+        assert_eq!(Comment(Data::new(5, 6, "(")), lexer.parse()[1]);
+        let mut lexer = Lexer::new("\\ a\n\\ b");
+        assert_eq!(
+            vec![
+                Comment(Data::new(0, 3, "\\ a")),
+                Comment(Data::new(4, 7, "\\ b"))
+            ],
+            lexer.parse()
+        );
+    }
+
+    #[test]
+    fn test_parse_multi_byte_characters() {
+        let mut lexer = Lexer::new("é \\ ünïcode\n: größe ;");
+        assert_eq!(
+            vec![
+                Word(Data::new(0, 1, "é")),
+                Comment(Data::new(2, 11, "\\ ünïcode")),
+                Colon(Data::new(12, 12, ":")),
+                Word(Data::new(14, 19, "größe")),
+                Semicolon(Data::new(20, 21, ";")),
+            ],
+            lexer.parse()
+        );
+    }
+
+    #[test]
+    fn test_parse_comments_before_crlf() {
+        let mut lexer = Lexer::new("\\ a\r\nword");
+        assert_eq!(
+            vec![
+                Comment(Data::new(0, 3, "\\ a")),
+                Word(Data::new(5, 9, "word"))
+            ],
+            lexer.parse()
+        );
+    }
+
     #[test]
     fn test_parse_words_on_lines() {
         let mut lexer = Lexer::new("some\nwords here\0");
@@ -368,6 +525,20 @@ mod tests {
         assert_eq!(tokens, expected)
     }
 
+    #[test]
+    fn test_parse_words_starting_with_digits() {
+        let mut lexer = Lexer::new("2drop 1+ 1. $ffg 7");
+        let tokens = lexer.parse();
+        let expected = vec![
+            Word(Data::new(0, 5, "2drop")),
+            Word(Data::new(6, 8, "1+")),
+            Word(Data::new(9, 11, "1.")),
+            Word(Data::new(12, 16, "$ffg")),
+            Number(Data::new(17, 18, "7")),
+        ];
+        assert_eq!(tokens, expected)
+    }
+
     #[test]
     fn test_parse_number_word() {
         let mut lexer = Lexer::new("word");
@@ -376,6 +547,35 @@ mod tests {
         assert_eq!(tokens, expected)
     }
 
+    #[cfg(feature = "trivia")]
+    #[test]
+    fn test_parse_with_trivia() {
+        let source = ": a ( n )  1 ;\r\n\n\tb \\ c\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.parse_with_trivia();
+        let expected = vec![
+            Colon(Data::new(0, 0, ":")),
+            Whitespace(Data::new(1, 2, " ")),
+            Word(Data::new(2, 3, "a")),
+            Whitespace(Data::new(3, 4, " ")),
+            Comment(Data::new(4, 9, "( n )")),
+            Whitespace(Data::new(9, 11, "  ")),
+            Number(Data::new(11, 12, "1")),
+            Whitespace(Data::new(12, 13, " ")),
+            Semicolon(Data::new(13, 14, ";")),
+            Newline(Data::new(14, 16, "\r\n")),
+            Newline(Data::new(16, 17, "\n")),
+            Whitespace(Data::new(17, 18, "\t")),
+            Word(Data::new(18, 19, "b")),
+            Whitespace(Data::new(19, 20, " ")),
+            Comment(Data::new(20, 23, "\\ c")),
+            Newline(Data::new(23, 24, "\n")),
+        ];
+        assert_eq!(tokens, expected);
+        let rebuilt: String = tokens.iter().map(|token| token.get_data().value).collect();
+        assert_eq!(source, rebuilt);
+    }
+
     #[cfg(feature = "ropey")]
     #[test]
     fn test_to_ropey() {
@@ -8,7 +8,7 @@ pub struct Data<'a> {
 }
 
 impl<'a> Data<'a> {
-    pub fn new(start: usize, end: usize, value: &'a str) -> Data {
+    pub fn new(start: usize, end: usize, value: &'a str) -> Data<'a> {
         Data { start, end, value }
     }
 }
@@ -33,6 +33,11 @@ pub enum Token<'a> {
     Number(Data<'a>),
     Comment(Data<'a>),
     StackComment(Data<'a>),
+    /// Spaces and tabs between tokens, only from `Lexer::parse_with_trivia`
+    Whitespace(Data<'a>),
+    /// A line break, `\n` or `\r\n`, from `Lexer::parse_with_trivia`. `Lexer::parse` only
+    /// gives the one ending a string literal left open on the line of its opening word
+    Newline(Data<'a>),
 }
 
 impl<'a> Token<'a> {
@@ -46,6 +51,7 @@ impl<'a> Token<'a> {
             Token::Number(dat) => dat,
             Token::Comment(dat) => dat,
             Token::StackComment(dat) => dat,
+            Token::Whitespace(dat) | Token::Newline(dat) => dat,
         }
     }
 }
@@ -61,6 +67,7 @@ impl<'a> Display for Token<'a> {
             | Token::Comment(value) => write!(f, "{value:?}"),
             Token::Colon(_) => write!(f, ":"),
             Token::Semicolon(_) => write!(f, ";"),
+            Token::Whitespace(value) | Token::Newline(value) => write!(f, "{value:?}"),
         }
     }
 }
@@ -0,0 +1,551 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use forth_lexer::{
+    context::{ContextTokens, NAME_PARSERS},
+    token::{Data, Token},
+};
+
+/// Words opening a control structure
+pub const OPENERS: [&str; 6] = ["IF", "DO", "?DO", "BEGIN", "CASE", "OF"];
+/// Words starting another branch of the innermost control structure
+pub const MIDDLES: [&str; 2] = ["ELSE", "WHILE"];
+/// Words closing the innermost control structure
+pub const CLOSERS: [&str; 8] = [
+    "THEN", "LOOP", "+LOOP", "UNTIL", "AGAIN", "REPEAT", "ENDOF", "ENDCASE",
+];
+
+/// The name a defining word or `:` takes from `token`. Names that look like numbers, like
+/// `2DEAD`, lex as numbers but are names all the same
+pub fn definition_name<'a>(token: &Token<'a>) -> Option<Data<'a>> {
+    match token {
+        Token::Word(name) | Token::Number(name) => Some(*name),
+        _ => None,
+    }
+}
+
+/// Char offsets of the first and past the last char of a node
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Program<'a> {
+    pub items: Vec<Item<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Item<'a> {
+    Definition(Definition<'a>),
+    /// Code outside of definitions, run when the file is loaded
+    Phrase(Phrase<'a>),
+}
+
+/// `: name ... ;`
+#[derive(Debug, PartialEq)]
+pub struct Definition<'a> {
+    pub colon: Data<'a>,
+    pub name: Option<Data<'a>>,
+    pub body: Vec<Node<'a>>,
+    pub semicolon: Option<Data<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Node<'a> {
+    Phrase(Phrase<'a>),
+    Control(ControlStructure<'a>),
+    /// A token that does not fit, like THEN without IF
+    Error(ErrorNode<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ErrorNode<'a> {
+    pub token: Token<'a>,
+    pub message: String,
+}
+
+/// Something half typed or broken, the tree around it is still usable
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// A run of tokens without control flow
+#[derive(Debug, PartialEq)]
+pub struct Phrase<'a> {
+    pub tokens: Vec<Token<'a>>,
+    pub span: Span,
+}
+
+/// `IF ... ELSE ... THEN`, `BEGIN ... WHILE ... REPEAT` and the like, each branch starts
+/// with its keyword
+#[derive(Debug, PartialEq)]
+pub struct ControlStructure<'a> {
+    pub branches: Vec<Branch<'a>>,
+    pub end: Option<Data<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Branch<'a> {
+    pub keyword: Data<'a>,
+    pub body: Vec<Node<'a>>,
+}
+
+impl<'a> Program<'a> {
+    pub fn parse(tokens: &[Token<'a>]) -> Program<'a> {
+        let in_string = ContextTokens::new(tokens.iter().cloned())
+            .map(|context| context.in_string)
+            .collect();
+        let mut parser = Parser {
+            tokens,
+            in_string,
+            ix: 0,
+        };
+        let mut items = vec![];
+        let mut phrase = vec![];
+        while let Some(token) = parser.peek() {
+            match token {
+                Token::Eof(_) => break,
+                _ if parser.string_text(&mut phrase) => {}
+                _ if parser.parsing_word(&mut phrase) => {}
+                Token::Colon(_) => {
+                    items.extend(take_phrase(&mut phrase).map(Item::Phrase));
+                    items.push(Item::Definition(parser.definition()));
+                }
+                _ => {
+                    phrase.push(token.clone());
+                    parser.ix += 1;
+                }
+            }
+        }
+        items.extend(take_phrase(&mut phrase).map(Item::Phrase));
+        Program { items }
+    }
+
+    /// Missing `;` and closing words, stray control words and nameless definitions
+    pub fn errors(&self) -> Vec<SyntaxError> {
+        let mut ret = vec![];
+        for definition in self.definitions() {
+            let name = definition.name.map(|name| name.value).unwrap_or(":");
+            // the lexer leaves `:` empty, cover it and the name
+            let head = Span {
+                start: definition.colon.start,
+                end: definition
+                    .name
+                    .map(|name| name.end)
+                    .unwrap_or(definition.colon.start + 1),
+            };
+            if definition.name.is_none() {
+                ret.push(SyntaxError {
+                    message: "Missing name after `:`".to_string(),
+                    span: head,
+                });
+            }
+            if definition.semicolon.is_none() {
+                ret.push(SyntaxError {
+                    message: format!("Missing `;` to end `{}`", name),
+                    span: head,
+                });
+            }
+            node_errors(&definition.body, &mut ret);
+        }
+        ret
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &Definition<'a>> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Definition(definition) => Some(definition),
+            Item::Phrase(_) => None,
+        })
+    }
+}
+
+impl<'a> Definition<'a> {
+    /// Whether this is a `:NONAME` definition, the lexer splits `:NONAME` into `:` and a name
+    pub fn is_noname(&self) -> bool {
+        self.name.is_some_and(|name| {
+            name.start == self.colon.start + 1 && name.value.eq_ignore_ascii_case("NONAME")
+        })
+    }
+
+    /// The `( ... -- ... )` comment right after the name
+    pub fn stack_comment(&self) -> Option<Data<'a>> {
+        match self.body.first() {
+            Some(Node::Phrase(phrase)) => match phrase.tokens.first() {
+                Some(Token::Comment(comment)) if comment.value.starts_with('(') => Some(*comment),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Every token of the definition with its nesting level, `:` and `;` are at level 0,
+    /// the body at 1 and control structure keywords at the level of their structure
+    pub fn levels(&self) -> Vec<(Token<'a>, usize)> {
+        let mut ret = vec![(Token::Colon(self.colon), 0)];
+        ret.extend(self.name.map(|name| (Token::Word(name), 0)));
+        node_levels(&self.body, 1, &mut ret);
+        ret.extend(
+            self.semicolon
+                .map(|semicolon| (Token::Semicolon(semicolon), 0)),
+        );
+        ret
+    }
+}
+
+fn node_errors(nodes: &[Node], ret: &mut Vec<SyntaxError>) {
+    for node in nodes {
+        match node {
+            Node::Phrase(_) => {}
+            Node::Control(control) => {
+                let opener = control.branches[0].keyword;
+                if control.end.is_none() {
+                    ret.push(SyntaxError {
+                        message: format!(
+                            "Missing `{}` to close `{}`",
+                            closer_for(opener.value),
+                            opener.value
+                        ),
+                        span: span_of(&opener),
+                    });
+                }
+                for branch in &control.branches {
+                    node_errors(&branch.body, ret);
+                }
+            }
+            Node::Error(error) => ret.push(SyntaxError {
+                message: error.message.clone(),
+                span: span_of(error.token.get_data()),
+            }),
+        }
+    }
+}
+
+/// The usual closing word for `opener`
+fn closer_for(opener: &str) -> &'static str {
+    match opener.to_uppercase().as_str() {
+        "IF" => "THEN",
+        "DO" | "?DO" => "LOOP",
+        "BEGIN" => "UNTIL",
+        "CASE" => "ENDCASE",
+        "OF" => "ENDOF",
+        _ => "THEN",
+    }
+}
+
+fn span_of(data: &Data) -> Span {
+    Span {
+        start: data.start,
+        end: data.end,
+    }
+}
+
+fn node_levels<'a>(nodes: &[Node<'a>], level: usize, ret: &mut Vec<(Token<'a>, usize)>) {
+    for node in nodes {
+        match node {
+            Node::Error(error) => ret.push((error.token.clone(), level)),
+            Node::Phrase(phrase) => {
+                ret.extend(phrase.tokens.iter().map(|token| (token.clone(), level)))
+            }
+            Node::Control(control) => {
+                for branch in &control.branches {
+                    ret.push((Token::Word(branch.keyword), level));
+                    node_levels(&branch.body, level + 1, ret);
+                }
+                ret.extend(control.end.map(|end| (Token::Word(end), level)));
+            }
+        }
+    }
+}
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    /// Whether each token is text of a string literal
+    in_string: Vec<bool>,
+    ix: usize,
+}
+
+impl<'t, 'a> Parser<'t, 'a> {
+    fn peek(&self) -> Option<&'t Token<'a>> {
+        self.tokens.get(self.ix)
+    }
+
+    /// The upper cased word at the cursor, if it is a word
+    fn peek_word(&self) -> Option<(Data<'a>, String)> {
+        match self.peek() {
+            Some(Token::Word(data)) => Some((*data, data.value.to_uppercase())),
+            _ => None,
+        }
+    }
+
+    /// At the text of a string literal, moves it onto `phrase`, as it is not run, whether
+    /// it is `:` or `IF`
+    fn string_text(&mut self, phrase: &mut Vec<Token<'a>>) -> bool {
+        let start = self.ix;
+        while self.in_string.get(self.ix).copied().unwrap_or(false) {
+            phrase.push(self.tokens[self.ix].clone());
+            self.ix += 1;
+        }
+        self.ix > start
+    }
+
+    /// At one of the `NAME_PARSERS`, moves it and the name it parses onto `phrase`, as the
+    /// name is not run, whether it is `;` or `IF`
+    fn parsing_word(&mut self, phrase: &mut Vec<Token<'a>>) -> bool {
+        let Some((_, word)) = self.peek_word() else {
+            return false;
+        };
+        if !NAME_PARSERS.contains(&word.as_str()) {
+            return false;
+        }
+        phrase.push(self.tokens[self.ix].clone());
+        self.ix += 1;
+        if let Some(name) = self.peek().filter(|token| !matches!(token, Token::Eof(_))) {
+            phrase.push(name.clone());
+            self.ix += 1;
+        }
+        true
+    }
+
+    fn definition(&mut self) -> Definition<'a> {
+        let colon = *self.peek().expect("Called at a colon").get_data();
+        self.ix += 1;
+        let name = self.peek().and_then(definition_name);
+        if name.is_some() {
+            self.ix += 1;
+        }
+        let body = self.nodes(false);
+        let semicolon = match self.peek() {
+            Some(Token::Semicolon(semicolon)) => {
+                self.ix += 1;
+                Some(*semicolon)
+            }
+            _ => None,
+        };
+        let end = self.tokens[..self.ix]
+            .last()
+            .map(|token| token.get_data().end)
+            .unwrap_or(colon.end);
+        Definition {
+            colon,
+            name,
+            body,
+            semicolon,
+            span: Span {
+                start: colon.start,
+                end,
+            },
+        }
+    }
+
+    /// Nodes up to `;`, the next `:` or, when `nested`, a keyword of the enclosing structure.
+    /// A `:` implicitly closes whatever is still open, so half typed code does not swallow
+    /// the definitions after it
+    fn nodes(&mut self, nested: bool) -> Vec<Node<'a>> {
+        let mut ret = vec![];
+        let mut phrase = vec![];
+        while let Some(token) = self.peek() {
+            if matches!(token, Token::Eof(_)) {
+                break;
+            }
+            if self.string_text(&mut phrase) || self.parsing_word(&mut phrase) {
+                continue;
+            }
+            if matches!(token, Token::Colon(_) | Token::Semicolon(_)) {
+                break;
+            }
+            if let Some((keyword, word)) = self.peek_word() {
+                if OPENERS.contains(&word.as_str()) {
+                    ret.extend(take_phrase(&mut phrase).map(Node::Phrase));
+                    ret.push(Node::Control(self.control()));
+                    continue;
+                }
+                let continues =
+                    MIDDLES.contains(&word.as_str()) || CLOSERS.contains(&word.as_str());
+                if nested && continues {
+                    break;
+                }
+                if continues {
+                    ret.extend(take_phrase(&mut phrase).map(Node::Phrase));
+                    ret.push(Node::Error(ErrorNode {
+                        token: token.clone(),
+                        message: format!("`{}` without an opening control word", keyword.value),
+                    }));
+                    self.ix += 1;
+                    continue;
+                }
+            }
+            phrase.push(token.clone());
+            self.ix += 1;
+        }
+        ret.extend(take_phrase(&mut phrase).map(Node::Phrase));
+        ret
+    }
+
+    fn control(&mut self) -> ControlStructure<'a> {
+        let (keyword, _) = self.peek_word().expect("Called at an opening word");
+        self.ix += 1;
+        let mut branches = vec![Branch {
+            keyword,
+            body: self.nodes(true),
+        }];
+        let mut end = None;
+        while let Some((keyword, word)) = self.peek_word() {
+            self.ix += 1;
+            if CLOSERS.contains(&word.as_str()) {
+                end = Some(keyword);
+                break;
+            }
+            branches.push(Branch {
+                keyword,
+                body: self.nodes(true),
+            });
+        }
+        let last = self.tokens[..self.ix]
+            .last()
+            .map(|token| token.get_data().end)
+            .unwrap_or(keyword.end);
+        ControlStructure {
+            branches,
+            end,
+            span: Span {
+                start: keyword.start,
+                end: last,
+            },
+        }
+    }
+}
+
+fn take_phrase<'a>(tokens: &mut Vec<Token<'a>>) -> Option<Phrase<'a>> {
+    let first = tokens.first()?.get_data().start;
+    let last = tokens.last()?.get_data().end;
+    Some(Phrase {
+        tokens: std::mem::take(tokens),
+        span: Span {
+            start: first,
+            end: last,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use forth_lexer::parser::Lexer;
+
+    fn levels(source: &str) -> Vec<(String, usize)> {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.parse();
+        let program = Program::parse(&tokens);
+        let definition = program.definitions().next().unwrap();
+        definition
+            .levels()
+            .into_iter()
+            .map(|(token, level)| (token.get_data().value.to_string(), level))
+            .collect()
+    }
+
+    #[test]
+    fn parses_items() {
+        let mut lexer = Lexer::new("variable x\n: a ( -- n ) 1 ;\nimmediate a");
+        let tokens = lexer.parse();
+        let program = Program::parse(&tokens);
+        assert_eq!(3, program.items.len());
+        let definition = program.definitions().next().unwrap();
+        assert_eq!("a", definition.name.unwrap().value);
+        assert_eq!("( -- n )", definition.stack_comment().unwrap().value);
+        assert_eq!(Span { start: 11, end: 27 }, definition.span);
+    }
+
+    #[test]
+    fn nests_control_structures() {
+        let expected: Vec<(String, usize)> = [
+            (":", 0),
+            ("f", 0),
+            ("if", 1),
+            ("begin", 2),
+            ("x", 3),
+            ("while", 2),
+            ("y", 3),
+            ("repeat", 2),
+            ("else", 1),
+            ("z", 2),
+            ("then", 1),
+            (";", 0),
+        ]
+        .iter()
+        .map(|(value, level)| (value.to_string(), *level))
+        .collect();
+        assert_eq!(
+            expected,
+            levels(": f if begin x while y repeat else z then ;")
+        );
+    }
+
+    #[test]
+    fn stray_closers_are_errors() {
+        let mut lexer = Lexer::new(": f then ;");
+        let tokens = lexer.parse();
+        let errors = Program::parse(&tokens).errors();
+        assert_eq!(
+            vec![SyntaxError {
+                message: "`then` without an opening control word".to_string(),
+                span: Span { start: 4, end: 8 }
+            }],
+            errors
+        );
+        assert_eq!(
+            vec![
+                (":".to_string(), 0),
+                ("f".to_string(), 0),
+                ("then".to_string(), 1),
+                (";".to_string(), 0)
+            ],
+            levels(": f then ;")
+        );
+    }
+
+    #[test]
+    fn half_typed_code_closes_implicitly() {
+        let mut lexer = Lexer::new(": foo if\n: bar 1 ;\n: baz");
+        let tokens = lexer.parse();
+        let program = Program::parse(&tokens);
+        let names: Vec<&str> = program
+            .definitions()
+            .filter_map(|definition| definition.name.map(|name| name.value))
+            .collect();
+        assert_eq!(vec!["foo", "bar", "baz"], names);
+        let messages: Vec<String> = program
+            .errors()
+            .into_iter()
+            .map(|error| error.message)
+            .collect();
+        assert_eq!(
+            vec![
+                "Missing `;` to end `foo`",
+                "Missing `THEN` to close `if`",
+                "Missing `;` to end `baz`"
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn parsed_names_are_not_structure() {
+        let mut lexer = Lexer::new(": f postpone if postpone ; ; char : emit");
+        let tokens = lexer.parse();
+        let program = Program::parse(&tokens);
+        assert!(program.errors().is_empty());
+        assert_eq!(2, program.items.len());
+        assert_eq!(
+            Span { start: 0, end: 28 },
+            program.definitions().next().unwrap().span
+        );
+    }
+}
@@ -0,0 +1,200 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::utils::file_path;
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use lsp_types::{Diagnostic, NumberOrString};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+/// Version of the baseline file format written by `forth-lsp lint --write-baseline`
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Diagnostics recorded to be tolerated, so that only new ones are reported. A diagnostic is
+/// known by its file relative to the project root, its rule and the text of its line, which
+/// keeps it recorded while lines above it come and go
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    pub file: String,
+    pub rule: String,
+    /// The line of the diagnostic, without surrounding whitespace
+    pub line: String,
+    /// How many diagnostics of the rule are on such lines in the file
+    pub count: usize,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Baseline {
+            version: BASELINE_VERSION,
+            issues: vec![],
+        }
+    }
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Baseline> {
+        let text = fs::read_to_string(path).map_err(|source| Error::Path {
+            path: path.to_owned(),
+            source,
+        })?;
+        let baseline: Baseline = serde_json::from_str(&text)
+            .map_err(|err| Error::Generic(format!("{}: {}", path.display(), err)))?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(Error::Generic(format!(
+                "{}: unsupported baseline version {}",
+                path.display(),
+                baseline.version
+            )));
+        }
+        Ok(baseline)
+    }
+
+    /// The baseline as written to its file, issues sorted so that it diffs well
+    pub fn to_json(&self) -> String {
+        let mut baseline = self.clone();
+        baseline
+            .issues
+            .sort_by(|a, b| (&a.file, &a.rule, &a.line).cmp(&(&b.file, &b.rule, &b.line)));
+        let mut ret =
+            serde_json::to_string_pretty(&baseline).expect("Must be able to serialize baselines");
+        ret.push('\n');
+        ret
+    }
+
+    /// Record `diagnostics` of `rope`, the file at `relative` to the project root
+    pub fn record(&mut self, relative: &str, rope: &Rope, diagnostics: &[Diagnostic]) {
+        for (rule, line) in diagnostics
+            .iter()
+            .filter_map(|diagnostic| key(rope, diagnostic))
+        {
+            let known = self
+                .issues
+                .iter_mut()
+                .find(|issue| issue.file == relative && issue.rule == rule && issue.line == line);
+            match known {
+                Some(issue) => issue.count += 1,
+                None => self.issues.push(Issue {
+                    file: relative.to_string(),
+                    rule: rule.to_string(),
+                    line,
+                    count: 1,
+                }),
+            }
+        }
+    }
+
+    /// `diagnostics` of `rope`, the file at `relative` to the project root, without those
+    /// recorded. Each issue tolerates as many diagnostics as it counts
+    pub fn filter(
+        &self,
+        relative: &str,
+        rope: &Rope,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Vec<Diagnostic> {
+        let mut left: BTreeMap<(String, String), usize> = self
+            .issues
+            .iter()
+            .filter(|issue| issue.file == relative)
+            .map(|issue| ((issue.rule.clone(), issue.line.clone()), issue.count))
+            .collect();
+        if left.is_empty() {
+            return diagnostics;
+        }
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| {
+                let Some((rule, line)) = key(rope, diagnostic) else {
+                    return true;
+                };
+                match left.get_mut(&(rule.to_string(), line)) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The rule of a diagnostic and the text of the line it starts on
+fn key<'a>(rope: &Rope, diagnostic: &'a Diagnostic) -> Option<(&'a str, String)> {
+    let Some(NumberOrString::String(rule)) = &diagnostic.code else {
+        return None;
+    };
+    let line = rope.get_line(diagnostic.range.start.line as usize)?;
+    Some((rule, line.to_string().trim().to_string()))
+}
+
+/// Where `file`, a key of the files map, is relative to `root`, with `/` between the
+/// directories so that baselines are the same on every system
+pub fn relative_path(file: &str, root: &Path) -> Option<String> {
+    let path = file_path(file)?;
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lsp_types::{Position, Range};
+
+    fn diagnostic(line: u32, rule: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, 0), Position::new(line, 1)),
+            code: Some(NumberOrString::String(rule.to_string())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recorded_diagnostics_are_filtered_where_their_line_moved() {
+        let rope = Rope::from_str("frob\nfrob frob\n");
+        let mut baseline = Baseline::default();
+        baseline.record(
+            "src/a.forth",
+            &rope,
+            &[
+                diagnostic(1, "undefined-word"),
+                diagnostic(1, "undefined-word"),
+            ],
+        );
+        assert_eq!(2, baseline.issues[0].count);
+        let baseline: Baseline = serde_json::from_str(&baseline.to_json()).unwrap();
+
+        let moved = Rope::from_str("\\ new\nfrob frob\nfrob frob\n");
+        let diagnostics = vec![
+            diagnostic(1, "undefined-word"),
+            diagnostic(1, "undefined-word"),
+            diagnostic(2, "undefined-word"),
+            diagnostic(2, "trailing-whitespace"),
+        ];
+        let left = baseline.filter("src/a.forth", &moved, diagnostics.clone());
+        assert_eq!(diagnostics[2..].to_vec(), left);
+        let other = baseline.filter("src/b.forth", &moved, diagnostics.clone());
+        assert_eq!(diagnostics, other);
+    }
+
+    #[test]
+    fn paths_relative_to_the_root() {
+        assert_eq!(
+            Some("src/a.forth".to_string()),
+            relative_path("file:///ws/src/a.forth", Path::new("/ws"))
+        );
+        assert_eq!(None, relative_path("/elsewhere/a.forth", Path::new("/ws")));
+    }
+}
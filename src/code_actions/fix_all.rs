@@ -0,0 +1,127 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::Program,
+    definition_index::DefinitionIndex,
+    diagnostics::{get_diagnostics, unmatched_paren_fix, UNDEFINED_WORD_CODE},
+    utils::{data_to_position::ToPosition, ropey::bounds::RopeBoundsCheck, suggest::edit_distance},
+    words::Words,
+};
+
+use forth_lexer::{parser::Lexer, token::Data, token::Token};
+use lsp_types::{NumberOrString, Range, TextEdit};
+use ropey::Rope;
+
+pub const FIX_ALL_KIND: &str = "source.fixAll.forth-lsp";
+
+/// Typos are only fixed when this share of the suggestion is already typed right
+const MIN_CONFIDENCE: f64 = 0.8;
+
+/// Edits applying every fix that cannot change what correct code means: `;` for definitions
+/// missing one, removing `)` that closes nothing and replacing likely typos of known words
+pub fn fix_all(rope: &Rope, data: &Words, index: &DefinitionIndex) -> Vec<TextEdit> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let mut ret = vec![];
+    for definition in Program::parse(&tokens).definitions() {
+        if definition.semicolon.is_some() {
+            continue;
+        }
+        // a `;` after a trailing `\` comment would be commented out
+        let end = definition
+            .levels()
+            .iter()
+            .rev()
+            .map(|(token, _)| token)
+            .find(|token| !matches!(token, Token::Comment(_)))
+            .map(|token| token.get_data().end)
+            .unwrap_or(definition.colon.end);
+        let at = Data::new(end, end, "").to_position_start(rope);
+        ret.push(TextEdit {
+            range: Range { start: at, end: at },
+            new_text: " ;".to_string(),
+        });
+    }
+    let code = NumberOrString::String(UNDEFINED_WORD_CODE.to_string());
+    for diagnostic in get_diagnostics(rope, data, index) {
+        if let Some(edit) = unmatched_paren_fix(rope, &diagnostic) {
+            ret.push(edit);
+            continue;
+        }
+        if diagnostic.code.as_ref() != Some(&code) {
+            continue;
+        }
+        let Some(suggestion) = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("suggestion"))
+            .and_then(|suggestion| suggestion.as_str())
+        else {
+            continue;
+        };
+        let Some(typo) = rope.slice_range(diagnostic.range).map(String::from) else {
+            continue;
+        };
+        if confidence(&typo, suggestion) >= MIN_CONFIDENCE {
+            ret.push(TextEdit {
+                range: diagnostic.range,
+                new_text: suggestion.to_string(),
+            });
+        }
+    }
+    ret.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+    ret
+}
+
+/// How much of `suggestion` `typo` already gets right, 1 when they only differ in case
+fn confidence(typo: &str, suggestion: &str) -> f64 {
+    let len = typo.chars().count().max(suggestion.chars().count()).max(1);
+    1.0 - edit_distance(typo, suggestion) as f64 / len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(source: &str) -> String {
+        let rope = Rope::from_str(source);
+        let edits = fix_all(&rope, &Words::default(), &DefinitionIndex::default());
+        let mut ret = rope.clone();
+        for edit in edits.iter().rev() {
+            let range = ret.char_range(edit.range).unwrap();
+            ret.remove(range.clone());
+            ret.insert(range.start, &edit.new_text);
+        }
+        ret.to_string()
+    }
+
+    #[test]
+    fn closes_definitions_before_trailing_comments() {
+        assert_eq!(
+            ": sq dup * ; \\ square\n: cube dup sq * ;",
+            fixed(": sq dup * \\ square\n: cube dup sq * ;")
+        );
+    }
+
+    #[test]
+    fn removes_unmatched_parens() {
+        assert_eq!("1 2 + .", fixed("1 2 ) + ."));
+        assert_eq!(": x ( n -- ) ;", fixed(": x ( n -- ) ;"));
+    }
+
+    #[test]
+    fn replaces_confident_typos_only() {
+        assert_eq!(
+            "variable counter\n1 counter ! dpu",
+            fixed("variable counter\n1 countr ! dpu")
+        );
+    }
+
+    #[test]
+    fn confidence_of_typos() {
+        assert_eq!(1.0, confidence("dup", "DUP"));
+        assert!(confidence("countr", "counter") > MIN_CONFIDENCE);
+        assert!(confidence("dpu", "DUP") < MIN_CONFIDENCE);
+    }
+}
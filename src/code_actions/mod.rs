@@ -0,0 +1,4 @@
+pub mod fix_all;
+pub mod normalize_word_case;
+pub mod organize_definitions;
+pub mod variable_value;
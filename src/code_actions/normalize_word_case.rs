@@ -0,0 +1,52 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    config::FormatConfig, formatter::recased_words, utils::position_encoding::data_edit,
+    words::Words,
+};
+
+use forth_lexer::parser::Lexer;
+use lsp_types::TextEdit;
+use ropey::Rope;
+
+pub const NORMALIZE_WORD_CASE_KIND: &str = "source.normalizeWordCase";
+
+/// Edits writing every builtin word of `rope` in the case `config` wants for it, strings
+/// and comments are left alone
+pub fn normalize_word_case(rope: &Rope, config: &FormatConfig, data: &Words) -> Vec<TextEdit> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    recased_words(&tokens, config, data)
+        .into_iter()
+        .map(|(word, recased)| data_edit(&word, rope, recased))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::WordCase;
+
+    use lsp_types::Position;
+
+    fn config(word_case: WordCase) -> FormatConfig {
+        FormatConfig {
+            word_case,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn edits_only_mismatching_builtins() {
+        let rope = Rope::from_str(": sq ( n -- n ) DUP * ;\n.\" dup\" dup");
+        let edits = normalize_word_case(&rope, &config(WordCase::Lower), &Words::default());
+        assert_eq!(1, edits.len());
+        assert_eq!(Position::new(0, 16), edits[0].range.start);
+        assert_eq!("dup", edits[0].new_text);
+        assert!(
+            normalize_word_case(&rope, &config(WordCase::Preserve), &Words::default()).is_empty()
+        );
+    }
+}
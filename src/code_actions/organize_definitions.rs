@@ -0,0 +1,344 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::definition_name, definition_index::is_defining_word, diagnostics::words_outside_strings,
+};
+
+use std::collections::{HashMap, HashSet};
+
+use forth_lexer::{parser::Lexer, token::Token};
+use ropey::Rope;
+
+pub const ORGANIZE_DEFINITIONS_KIND: &str = "source.organizeDefinitions";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChunkKind {
+    /// `VARIABLE x`, `5 CONSTANT five` and friends
+    Declaration,
+    /// `: name ... ;`
+    Definition,
+    /// Anything else at the top level, it runs when the file is loaded
+    Code,
+}
+
+/// Consecutive lines that move as one, including the comment lines right above them
+#[derive(Debug)]
+struct Chunk {
+    kind: ChunkKind,
+    lines: Vec<usize>,
+    defines: Vec<String>,
+    uses: HashSet<String>,
+}
+
+/// What a line outside of colon definitions holds
+enum Line {
+    Blank,
+    Comment,
+    Declaration(Vec<String>),
+    Code,
+    /// First line of a colon definition spanning up to the given line
+    Definition(usize, String),
+}
+
+/// The text of `rope` with top-level definitions ordered so that words are defined
+/// before they are used, declarations first, or `None` if nothing would change or the
+/// file can not be reordered safely
+pub fn organize_definitions(rope: &Rope) -> Option<String> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens: Vec<Token> = lexer
+        .parse()
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Eof(_)))
+        .collect();
+    let line_of = |token: &Token| rope.char_to_line(token.get_data().start);
+
+    let mut by_line: HashMap<usize, Vec<Token>> = HashMap::new();
+    let mut definition_lines: HashMap<usize, (usize, String)> = HashMap::new();
+    let mut covered: HashSet<usize> = HashSet::new();
+    let mut ix = 0;
+    while ix < tokens.len() {
+        let token = &tokens[ix];
+        if let Token::Colon(_) = token {
+            let end = ix
+                + tokens[ix..]
+                    .iter()
+                    .position(|token| matches!(token, Token::Semicolon(_)))?;
+            let (first, last) = (line_of(token), line_of(&tokens[end]));
+            // something in front of `:` on the same line would be torn apart
+            if by_line.get(&first).is_some_and(|tokens| !tokens.is_empty())
+                || covered.contains(&first)
+            {
+                return None;
+            }
+            let name = match tokens.get(ix + 1) {
+                Some(Token::Word(name)) => name.value.to_uppercase(),
+                _ => return None,
+            };
+            definition_lines.insert(first, (last, name));
+            covered.extend(first..=last);
+            by_line
+                .entry(first)
+                .or_default()
+                .extend(tokens[ix..=end].iter().cloned());
+            ix = end + 1;
+            continue;
+        }
+        let line = line_of(token);
+        if covered.contains(&line) && !definition_lines.contains_key(&line) {
+            // trailing words after `;`, like IMMEDIATE, stay with the definition
+            let first = *covered
+                .iter()
+                .filter(|first| definition_lines.contains_key(first) && **first <= line)
+                .max()?;
+            by_line.entry(first).or_default().push(token.clone());
+        } else {
+            by_line.entry(line).or_default().push(token.clone());
+        }
+        ix += 1;
+    }
+
+    let mut lines = vec![];
+    let mut line = 0;
+    while line < rope.len_lines() {
+        if let Some((last, name)) = definition_lines.get(&line) {
+            lines.push((line, Line::Definition(*last, name.clone())));
+            line = last + 1;
+            continue;
+        }
+        let tokens = by_line.get(&line).cloned().unwrap_or_default();
+        let kind = if tokens.is_empty() {
+            Line::Blank
+        } else if tokens
+            .iter()
+            .all(|token| matches!(token, Token::Comment(_) | Token::StackComment(_)))
+        {
+            Line::Comment
+        } else {
+            let declared = declared_names(&tokens);
+            if declared.is_empty() {
+                Line::Code
+            } else {
+                Line::Declaration(declared)
+            }
+        };
+        lines.push((line, kind));
+        line += 1;
+    }
+
+    // leading lines, like INCLUDEs, stay on top, except comments belonging to the first chunk
+    let first_chunk = lines
+        .iter()
+        .position(|(_, kind)| matches!(kind, Line::Declaration(_) | Line::Definition(..)))?;
+    let mut header_end = first_chunk;
+    while header_end > 0 && matches!(lines[header_end - 1].1, Line::Comment) {
+        header_end -= 1;
+    }
+    let header: Vec<usize> = lines[..header_end].iter().map(|(line, _)| *line).collect();
+
+    let mut chunks: Vec<Chunk> = vec![];
+    let mut comments: Vec<usize> = vec![];
+    let mut previous_blank = true;
+    for (line, kind) in &lines[header_end..] {
+        let (kind, line_range, defines) = match kind {
+            Line::Blank => {
+                chunks.extend(comment_chunk(&mut comments));
+                previous_blank = true;
+                continue;
+            }
+            Line::Comment => {
+                comments.push(*line);
+                previous_blank = false;
+                continue;
+            }
+            Line::Code if !previous_blank && comments.is_empty() && !chunks.is_empty() => {
+                // continuation of whatever is right above, like `1 , 2 ,` after CREATE
+                let chunk = chunks.last_mut().expect("Checked above");
+                chunk.lines.push(*line);
+                continue;
+            }
+            Line::Code => (ChunkKind::Code, *line..=*line, vec![]),
+            Line::Declaration(names) => (ChunkKind::Declaration, *line..=*line, names.clone()),
+            Line::Definition(last, name) => {
+                (ChunkKind::Definition, *line..=*last, vec![name.clone()])
+            }
+        };
+        previous_blank = false;
+        let mut chunk_lines = std::mem::take(&mut comments);
+        chunk_lines.extend(line_range);
+        chunks.push(Chunk {
+            kind,
+            lines: chunk_lines,
+            defines,
+            uses: HashSet::new(),
+        });
+    }
+    chunks.extend(comment_chunk(&mut comments));
+
+    for chunk in chunks.iter_mut() {
+        let chunk_tokens: Vec<Token> = chunk
+            .lines
+            .iter()
+            .flat_map(|line| by_line.get(line).cloned().unwrap_or_default())
+            .collect();
+        chunk.uses = words_outside_strings(&chunk_tokens)
+            .iter()
+            .map(|data| data.value.to_uppercase())
+            .filter(|word| !chunk.defines.contains(word))
+            .collect();
+    }
+
+    let mut defined_by: HashMap<&str, usize> = HashMap::new();
+    for (ix, chunk) in chunks.iter().enumerate() {
+        for name in &chunk.defines {
+            // redefinitions depend on their position, leave such files alone
+            if defined_by.insert(name.as_str(), ix).is_some() {
+                return None;
+            }
+        }
+    }
+
+    let movable: Vec<usize> = (0..chunks.len())
+        .filter(|ix| chunks[*ix].kind != ChunkKind::Code)
+        .collect();
+    let mut order: Vec<usize> = vec![];
+    let mut placed: HashSet<usize> = HashSet::new();
+    while order.len() < movable.len() {
+        let ready = movable
+            .iter()
+            .filter(|ix| !placed.contains(ix))
+            .filter(|ix| {
+                chunks[**ix]
+                    .uses
+                    .iter()
+                    .all(|word| match defined_by.get(word.as_str()) {
+                        Some(definer) => placed.contains(definer),
+                        None => true,
+                    })
+            })
+            .min_by_key(|ix| (chunks[**ix].kind != ChunkKind::Declaration, **ix));
+        match ready {
+            Some(ix) => {
+                placed.insert(*ix);
+                order.push(*ix);
+            }
+            None => {
+                // mutual recursion through DEFER and the like, keep the original order
+                let rest: Vec<usize> = movable
+                    .iter()
+                    .filter(|ix| !placed.contains(ix))
+                    .copied()
+                    .collect();
+                placed.extend(&rest);
+                order.extend(rest);
+            }
+        }
+    }
+    order.extend((0..chunks.len()).filter(|ix| chunks[*ix].kind == ChunkKind::Code));
+
+    let text = |lines: &[usize]| {
+        lines
+            .iter()
+            .map(|line| {
+                rope.line(*line)
+                    .to_string()
+                    .trim_end_matches(['\n', '\r'])
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let mut ret = text(&header).trim_end().to_string();
+    let mut previous: Option<ChunkKind> = None;
+    for ix in order {
+        let chunk = &chunks[ix];
+        if !ret.is_empty() {
+            let tight =
+                previous == Some(ChunkKind::Declaration) && chunk.kind == ChunkKind::Declaration;
+            ret.push_str(if tight { "\n" } else { "\n\n" });
+        }
+        ret.push_str(&text(&chunk.lines));
+        previous = Some(chunk.kind);
+    }
+    if progn.ends_with('\n') {
+        ret.push('\n');
+    }
+    if ret == progn {
+        None
+    } else {
+        Some(ret)
+    }
+}
+
+fn comment_chunk(comments: &mut Vec<usize>) -> Option<Chunk> {
+    if comments.is_empty() {
+        return None;
+    }
+    Some(Chunk {
+        kind: ChunkKind::Code,
+        lines: std::mem::take(comments),
+        defines: vec![],
+        uses: HashSet::new(),
+    })
+}
+
+/// Upper cased names following a defining word on a line
+fn declared_names(tokens: &[Token]) -> Vec<String> {
+    let mut ret = vec![];
+    for pair in tokens.windows(2) {
+        if let [Token::Word(defining), name] = pair {
+            if is_defining_word(defining.value) {
+                ret.extend(definition_name(name).map(|name| name.value.to_uppercase()));
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn organize(source: &str) -> Option<String> {
+        organize_definitions(&Rope::from_str(source))
+    }
+
+    #[test]
+    fn defines_before_use_with_declarations_first() {
+        assert_eq!(
+            Some(": a 1 ;\n\n: b a ;\n".to_string()),
+            organize(": b a ;\n: a 1 ;\n")
+        );
+        assert_eq!(
+            Some("variable x\n5 constant five\n\n: inc 1 x +! ;\n\ninc\n".to_string()),
+            organize(": inc 1 x +! ;\nvariable x\n\ninc\n\n5 constant five\n")
+        );
+    }
+
+    #[test]
+    fn comments_and_continuations_move_along() {
+        assert_eq!(
+            Some(
+                "include lib.fs\n\ncreate table\n1 , 2 ,\n\n: a 1 ;\n\n\\ use a\n: b ( -- n ) a ;"
+                    .to_string()
+            ),
+            organize(
+                "include lib.fs\n\\ use a\n: b ( -- n ) a ;\n: a 1 ;\n\ncreate table\n1 , 2 ,"
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_organized_and_ambiguous_files_alone() {
+        assert_eq!(None, organize("variable x\n\n: a x @ ;\n"));
+        // redefinition
+        assert_eq!(None, organize(": a 2 ;\n: a 1 ;\n"));
+        // unterminated definition
+        assert_eq!(None, organize(": b a ;\n: a 1"));
+    }
+
+    #[test]
+    fn cycles_keep_original_order() {
+        assert_eq!(None, organize(": a b ;\n\n: b a ;\n"));
+    }
+}
@@ -0,0 +1,262 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::{DefKind, DefinitionIndex},
+    diagnostics::words_outside_strings,
+    utils::{file_uri, position_encoding::data_range},
+};
+
+use std::collections::{HashMap, HashSet};
+
+use forth_lexer::{
+    parser::Lexer,
+    token::{Data, Token},
+};
+use lsp_types::{Range, TextEdit, Url, WorkspaceEdit};
+use ropey::Rope;
+
+#[derive(Debug, PartialEq)]
+enum Conversion {
+    /// `VARIABLE x` becomes `0 VALUE x`
+    ToValue,
+    /// `n VALUE x` becomes `VARIABLE x n x !`
+    ToVariable { init: String },
+}
+
+/// A declaration under the cursor, with its range in chars
+#[derive(Debug)]
+struct Declaration {
+    name: String,
+    start: usize,
+    end: usize,
+    lowercase: bool,
+    conversion: Conversion,
+}
+
+/// Title and edit converting the VARIABLE or VALUE declared at `ix` of `file`, with every
+/// read and write of it across `files`, or `None` if some use can not be converted
+pub fn convert_variable_value(
+    file: &str,
+    rope: &Rope,
+    ix: usize,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+) -> Option<(String, WorkspaceEdit)> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let declaration = declaration_at(&tokens, ix)?;
+    // a colon definition with the same name makes uses ambiguous
+    let ambiguous = index
+        .definitions(&declaration.name)
+        .iter()
+        .any(|(_, definition)| !matches!(definition.kind, DefKind::Variable | DefKind::Value));
+    if ambiguous {
+        return None;
+    }
+    let keyword = |word: &str| {
+        if declaration.lowercase {
+            word.to_lowercase()
+        } else {
+            word.to_string()
+        }
+    };
+    let name = &declaration.name;
+    let new_declaration = match &declaration.conversion {
+        Conversion::ToValue => format!("0 {} {}", keyword("VALUE"), name),
+        Conversion::ToVariable { init } => {
+            format!("{} {} {} {} !", keyword("VARIABLE"), name, init, name)
+        }
+    };
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    changes.entry(file_uri(file)?).or_default().push(TextEdit {
+        range: range(rope, declaration.start, declaration.end),
+        new_text: new_declaration,
+    });
+
+    let in_files: HashSet<&String> = index
+        .references(name)
+        .into_iter()
+        .map(|(file, _, _)| file)
+        .collect();
+    for other in in_files {
+        let rope = files.get(other)?;
+        let progn = rope.to_string();
+        let mut lexer = Lexer::new(progn.as_str());
+        let tokens = lexer.parse();
+        let words: HashSet<usize> = words_outside_strings(&tokens)
+            .iter()
+            .map(|data| data.start)
+            .collect();
+        for (ix, token) in tokens.iter().enumerate() {
+            let data = match token {
+                Token::Word(data)
+                    if data.value.eq_ignore_ascii_case(name) && words.contains(&data.start) =>
+                {
+                    data
+                }
+                _ => continue,
+            };
+            if other == file && data.start >= declaration.start && data.end <= declaration.end {
+                continue;
+            }
+            let previous = ix.checked_sub(1).and_then(|ix| word(&tokens, ix));
+            let next = word(&tokens, ix + 1);
+            let (start, end, new_text) = match declaration.conversion {
+                Conversion::ToValue => match next.map(|next| next.value) {
+                    Some("@") => (data.start, next?.end, data.value.to_string()),
+                    Some("!") => (
+                        data.start,
+                        next?.end,
+                        format!("{} {}", keyword("TO"), data.value),
+                    ),
+                    Some("+!") => (
+                        data.start,
+                        next?.end,
+                        format!("{} + {} {}", data.value, keyword("TO"), data.value),
+                    ),
+                    // the address itself is used, a VALUE has none
+                    _ => return None,
+                },
+                Conversion::ToVariable { .. } => {
+                    match previous
+                        .map(|previous| previous.value.to_uppercase())
+                        .as_deref()
+                    {
+                        Some("TO") => (previous?.start, data.end, format!("{} !", data.value)),
+                        Some("+TO") => (previous?.start, data.end, format!("{} +!", data.value)),
+                        Some("'") | Some("[']") => return None,
+                        _ => (data.start, data.end, format!("{} @", data.value)),
+                    }
+                }
+            };
+            changes.entry(file_uri(other)?).or_default().push(TextEdit {
+                range: range(rope, start, end),
+                new_text,
+            });
+        }
+    }
+    let title = match declaration.conversion {
+        Conversion::ToValue => format!("Convert `{}` to VALUE", name),
+        Conversion::ToVariable { .. } => format!("Convert `{}` to VARIABLE", name),
+    };
+    Some((
+        title,
+        WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        },
+    ))
+}
+
+fn declaration_at(tokens: &[Token], ix: usize) -> Option<Declaration> {
+    for (at, token) in tokens.iter().enumerate() {
+        let defining = match token {
+            Token::Word(defining) => defining,
+            _ => continue,
+        };
+        let name = match word(tokens, at + 1) {
+            Some(name) => name,
+            None => continue,
+        };
+        let lowercase = defining.value.chars().all(|chr| !chr.is_uppercase());
+        match defining.value.to_uppercase().as_str() {
+            "VARIABLE" if defining.start <= ix && ix <= name.end => {
+                return Some(Declaration {
+                    name: name.value.to_string(),
+                    start: defining.start,
+                    end: name.end,
+                    lowercase,
+                    conversion: Conversion::ToValue,
+                })
+            }
+            "VALUE" => {
+                let init = match at.checked_sub(1).map(|at| &tokens[at]) {
+                    Some(Token::Number(init)) | Some(Token::Word(init)) => init,
+                    _ => continue,
+                };
+                if init.start <= ix && ix <= name.end {
+                    return Some(Declaration {
+                        name: name.value.to_string(),
+                        start: init.start,
+                        end: name.end,
+                        lowercase,
+                        conversion: Conversion::ToVariable {
+                            init: init.value.to_string(),
+                        },
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn word<'a>(tokens: &[Token<'a>], ix: usize) -> Option<Data<'a>> {
+    match tokens.get(ix) {
+        Some(Token::Word(data)) => Some(*data),
+        _ => None,
+    }
+}
+
+fn range(rope: &Rope, start: usize, end: usize) -> Range {
+    let data = Data::new(start, end, "");
+    data_range(&data, rope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ropey::bounds::RopeBoundsCheck;
+
+    fn convert(source: &str, ix: usize) -> Option<(String, String)> {
+        let file = "/ws/a.forth".to_string();
+        let rope = Rope::from_str(source);
+        let mut index = DefinitionIndex::default();
+        index.update_file(&file, &rope);
+        let files = HashMap::from([(file.clone(), rope.clone())]);
+        let (title, edit) = convert_variable_value(&file, &rope, ix, &files, &index)?;
+        let mut edits = edit.changes?.into_values().next()?;
+        edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+        let mut text = rope.clone();
+        for edit in edits.iter().rev() {
+            let range = text.char_range(edit.range)?;
+            text.remove(range.clone());
+            text.insert(range.start, &edit.new_text);
+        }
+        Some((title, text.to_string()))
+    }
+
+    #[test]
+    fn variable_to_value() {
+        assert_eq!(
+            Some((
+                "Convert `x` to VALUE".to_string(),
+                "0 value x\n: inc x 1 + to x ;\n: add x + to x ;\n: show .\" x\" x . ;".to_string()
+            )),
+            convert(
+                "variable x\n: inc x @ 1 + x ! ;\n: add x +! ;\n: show .\" x\" x @ . ;",
+                3
+            )
+        );
+    }
+
+    #[test]
+    fn value_to_variable() {
+        assert_eq!(
+            Some((
+                "Convert `n` to VARIABLE".to_string(),
+                "VARIABLE n 5 n !\n: inc n @ 1+ n ! ;".to_string()
+            )),
+            convert("5 VALUE n\n: inc n 1+ TO n ;", 0)
+        );
+    }
+
+    #[test]
+    fn refuses_address_uses() {
+        assert_eq!(None, convert("variable x\n: show x ? ;", 0));
+        assert_eq!(None, convert("5 value n\n' n", 4));
+        assert_eq!(None, convert("variable x\nx @", 12));
+    }
+}
@@ -0,0 +1,680 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    utils::ropey::bounds::RopeBoundsCheck,
+    words::{Word, Words},
+};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{DiagnosticSeverity, Range};
+use ropey::Rope;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const CONFIG_FILE_NAME: &str = ".forth-lsp.toml";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub source: SourceConfig,
+    pub color: ColorConfig,
+    pub references: ReferencesConfig,
+    pub builtin: BuiltinConfig,
+    pub format: FormatConfig,
+    pub completion: CompletionConfig,
+    pub definition: DefinitionConfig,
+    pub diagnostics: DiagnosticsConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct SourceConfig {
+    /// File extensions, without the dot, that are loaded as Forth source
+    pub extensions: Vec<String>,
+    /// Library directories or files outside the workspace, globs and `$VAR` are expanded
+    pub include_paths: Vec<String>,
+    /// Globs relative to the project root that are never indexed, `build` also excludes
+    /// everything below `build/`
+    pub exclude: Vec<String>,
+    /// Skip files ignored by `.gitignore` and `.ignore`
+    pub gitignore: bool,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        SourceConfig {
+            extensions: vec!["forth".to_string()],
+            include_paths: vec![],
+            exclude: vec![],
+            gitignore: true,
+        }
+    }
+}
+
+impl SourceConfig {
+    /// Existing paths matched by `include_paths`, relative entries resolve against `root`
+    pub fn resolve_include_paths(&self, root: &Path) -> Vec<PathBuf> {
+        let mut ret = vec![];
+        for include_path in &self.include_paths {
+            let expanded = match expand_env(include_path, |name| env::var(name).ok()) {
+                Ok(expanded) => expanded,
+                Err(name) => {
+                    eprintln!(
+                        "Skipping include path {}, ${} is not set",
+                        include_path, name
+                    );
+                    continue;
+                }
+            };
+            let pattern = root.join(expanded);
+            match glob::glob(&pattern.to_string_lossy()) {
+                Ok(paths) => ret.extend(paths.flatten()),
+                Err(err) => eprintln!("Invalid include path {}: {}", include_path, err),
+            }
+        }
+        ret
+    }
+
+    /// Include paths naming a variable that is not set, which are skipped
+    pub fn include_path_problems(&self) -> Vec<ConfigProblem> {
+        self.include_paths
+            .iter()
+            .filter_map(|include_path| {
+                let name = expand_env(include_path, |name| env::var(name).ok()).err()?;
+                Some(ConfigProblem {
+                    message: format!(
+                        "Skipping include path `{}`, `${}` is not set",
+                        include_path, name
+                    ),
+                    range: None,
+                })
+            })
+            .collect()
+    }
+}
+
+impl SourceConfig {
+    /// Whether `relative`, a path relative to the project root, matches an exclude glob
+    pub fn is_excluded(&self, relative: &Path) -> bool {
+        let patterns: Vec<glob::Pattern> = self
+            .exclude
+            .iter()
+            .filter_map(|exclude| glob::Pattern::new(exclude).ok())
+            .collect();
+        relative.ancestors().any(|ancestor| {
+            !ancestor.as_os_str().is_empty()
+                && patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_path(ancestor))
+        })
+    }
+}
+
+/// Replace `$VAR` and `${VAR}` with what `lookup` finds for them, the environment outside of
+/// tests. Fails with the name of the first variable `lookup` does not find
+fn expand_env(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let mut ret = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(chr) = chars.next() {
+        if chr != '$' {
+            ret.push(chr);
+            continue;
+        }
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next.is_ascii_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if braced && chars.peek() == Some(&'}') {
+            chars.next();
+        }
+        if name.is_empty() {
+            ret.push('$');
+        } else {
+            match lookup(&name) {
+                Some(var) => ret.push_str(&var),
+                None => return Err(name),
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// Color swatches for `$RRGGBB` literals, off by default as most code bases use
+/// hex literals for anything but colors
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReferencesConfig {
+    pub scope: ReferenceScope,
+}
+
+/// Which files find-references and rename look at
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReferenceScope {
+    #[default]
+    Workspace,
+    /// Only files connected to the current one through INCLUDE/REQUIRE
+    IncludeGraph,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatConfig {
+    /// Spaces per nesting level inside definitions
+    pub indent: usize,
+    pub word_case: WordCase,
+    /// Case of control flow words like `IF` and `LOOP`, `word_case` when unset
+    pub control_case: Option<WordCase>,
+    /// Case of defining words like `CREATE` and `VARIABLE`, `word_case` when unset
+    pub defining_case: Option<WordCase>,
+    /// Wrap longer lines inside definitions, no limit when unset
+    pub max_line_width: Option<usize>,
+    /// Ask the client to apply the formatted text whenever a file is saved
+    pub on_save: bool,
+    /// Edits the client applies right before saving, when it waits for them
+    pub before_save: BeforeSave,
+    /// Line breaks of formatted files
+    pub line_ending: LineEnding,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig {
+            indent: 2,
+            word_case: WordCase::Preserve,
+            control_case: None,
+            defining_case: None,
+            max_line_width: None,
+            on_save: false,
+            before_save: BeforeSave::Off,
+            line_ending: LineEnding::Auto,
+        }
+    }
+}
+
+/// What `textDocument/willSaveWaitUntil` changes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum BeforeSave {
+    #[default]
+    Off,
+    /// Remove whitespace at the end of lines
+    Trim,
+    /// Format the whole file
+    Format,
+}
+
+/// Line breaks written by formatting
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineEnding {
+    /// Those most lines of the file end with
+    #[default]
+    Auto,
+    Lf,
+    Crlf,
+}
+
+/// How builtin words are written when formatting or normalizing case
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum WordCase {
+    Upper,
+    Lower,
+    #[default]
+    Preserve,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompletionConfig {
+    /// Offer `IF $0 THEN` style variants for control words inside definitions
+    pub snippets: bool,
+}
+
+impl Default for CompletionConfig {
+    fn default() -> Self {
+        CompletionConfig { snippets: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefinitionConfig {
+    /// Resolve a word to the last definition compiled before it, following includes, like
+    /// the dictionary lookup of the running program, instead of to every definition
+    pub nearest_preceding: bool,
+}
+
+impl Default for DefinitionConfig {
+    fn default() -> Self {
+        DefinitionConfig {
+            nearest_preceding: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiagnosticsConfig {
+    pub definition_order: DefinitionOrder,
+    /// Severity of the diagnostics of a rule, keyed by their code like `undefined-word`
+    pub severity: BTreeMap<String, Severity>,
+    /// File written by `forth-lsp lint --write-baseline`, relative to the project root. The
+    /// diagnostics it records are not reported
+    pub baseline: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    pub fn to_lsp(self) -> DiagnosticSeverity {
+        match self {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Information => DiagnosticSeverity::INFORMATION,
+            Severity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// Whether a word used above its definition in the same file is reported
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DefinitionOrder {
+    /// Any definition in the file will do, wherever it is
+    #[default]
+    Lenient,
+    /// Forth compiles top-down, a use before the definition is an information
+    Strict,
+}
+
+/// Words provided by the target Forth system, on top of the standard ones
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuiltinConfig {
+    pub words: Vec<CustomWord>,
+    /// Know widespread non-standard words such as `-ROT`, `BOUNDS` and `CELL`
+    pub common_extensions: bool,
+}
+
+impl Default for BuiltinConfig {
+    fn default() -> Self {
+        BuiltinConfig {
+            words: vec![],
+            common_extensions: true,
+        }
+    }
+}
+
+/// A `[[builtin.words]]` entry
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CustomWord {
+    pub name: String,
+    #[serde(default)]
+    pub stack: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl CustomWord {
+    /// The word to document, a word of `standard` keeps its stack comment unless given another
+    pub fn to_word<'a>(&'a self, standard: &Words<'a>) -> Word<'a> {
+        let standard = standard
+            .words
+            .iter()
+            .find(|word| word.token.eq_ignore_ascii_case(&self.name));
+        Word {
+            doc: standard.map(|word| word.doc).unwrap_or_default(),
+            token: &self.name,
+            stack: match standard {
+                Some(word) if self.stack.is_empty() => word.stack,
+                _ => &self.stack,
+            },
+            help: &self.description,
+        }
+    }
+}
+
+/// A key or value of a config file that does not fit the config, which keeps its default
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblem {
+    pub message: String,
+    /// Where in the file, when known
+    pub range: Option<Range>,
+}
+
+/// Most attempts at parsing a config before giving up on it
+const MAX_CONFIG_PROBLEMS: usize = 32;
+
+impl Config {
+    pub fn from_toml(content: &str) -> Result<Config> {
+        toml::from_str(content).map_err(|err| Error::Generic(err.to_string()))
+    }
+
+    /// Parse `content`, ignoring the lines that do not fit the config instead of all of it.
+    /// Every ignored part is a problem, when no part can be blamed the config is the default
+    pub fn from_toml_checked(content: &str) -> (Config, Vec<ConfigProblem>) {
+        let mut lines: Vec<String> = content.split_inclusive('\n').map(String::from).collect();
+        let mut problems = vec![];
+        while problems.len() < MAX_CONFIG_PROBLEMS {
+            // ignored lines are blank, so positions in what is left match those in `content`
+            let remaining = lines.concat();
+            let err = match toml::from_str::<Config>(&remaining) {
+                Ok(config) => return (config, problems),
+                Err(err) => err,
+            };
+            let rope = Rope::from_str(&remaining);
+            let range = err.span().and_then(|span| {
+                let start = rope.try_byte_to_char(span.start).ok()?;
+                let end = rope.try_byte_to_char(span.end).ok()?;
+                Some(Range::new(rope.position_of(start)?, rope.position_of(end)?))
+            });
+            let ignored = range.map_or(0, |range| ignore_lines(&mut lines, range));
+            problems.push(ConfigProblem {
+                message: err.message().trim().to_string(),
+                range,
+            });
+            if ignored == 0 {
+                break;
+            }
+        }
+        (Config::default(), problems)
+    }
+
+    /// JSON Schema of `.forth-lsp.toml`, for editors to validate and complete it
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = serde_json::to_value(schemars::schema_for!(Config))
+            .expect("Must be able to serialize the config schema");
+        schema["title"] = CONFIG_FILE_NAME.into();
+        schema
+    }
+
+    /// Load `.forth-lsp.toml` from `root`, with defaults for the parts that are missing or do
+    /// not fit, which are returned as problems
+    pub fn load(root: &Path) -> (Config, Vec<ConfigProblem>) {
+        let path = root.join(CONFIG_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let (config, mut problems) = Config::from_toml_checked(&content);
+                problems.extend(config.source.include_path_problems());
+                for problem in &problems {
+                    eprintln!("Ignoring part of {}: {}", path.display(), problem.message);
+                }
+                (config, problems)
+            }
+            Err(_) => (Config::default(), vec![]),
+        }
+    }
+}
+
+/// Blank out the lines of `range`, all of a table when they start one, as its keys would
+/// otherwise land in the table before it. Returns how many lines were not blank yet
+fn ignore_lines(lines: &mut [String], range: Range) -> usize {
+    let is_header = |line: &str| line.trim_start().starts_with('[');
+    let start = range.start.line as usize;
+    let mut end = (range.end.line as usize).max(start);
+    if lines.get(start).is_some_and(|line| is_header(line)) {
+        while end + 1 < lines.len() && !is_header(&lines[end + 1]) {
+            end += 1;
+        }
+    }
+    let mut ignored = 0;
+    for line in lines.iter_mut().take(end + 1).skip(start) {
+        if !line.trim().is_empty() {
+            ignored += 1;
+        }
+        // keep the line breaks, so later errors point at the right line
+        *line = line
+            .chars()
+            .filter(|chr| *chr == '\n' || *chr == '\r')
+            .collect();
+    }
+    ignored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn empty_is_default() {
+        let config = Config::from_toml("").unwrap();
+        assert!(!config.color.enabled);
+    }
+
+    #[test]
+    fn color_enabled() {
+        let config = Config::from_toml("[color]\nenabled = true").unwrap();
+        assert!(config.color.enabled);
+    }
+
+    #[test]
+    fn source_extensions() {
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(vec!["forth"], config.source.extensions);
+        let config = Config::from_toml("[source]\nextensions = [\"fs\", \"fth\"]").unwrap();
+        assert_eq!(vec!["fs", "fth"], config.source.extensions);
+    }
+
+    #[test]
+    fn expand_env_variables() {
+        let vars = HashMap::from([("LIB", "/opt/forth")]);
+        let expand = |value| expand_env(value, |name| vars.get(name).map(|var| var.to_string()));
+        assert_eq!(Ok("/opt/forth/lib".to_string()), expand("$LIB/lib"));
+        assert_eq!(Ok("/opt/forth-x".to_string()), expand("${LIB}-x"));
+        assert_eq!(Err("MISSING".to_string()), expand("$LIB/$MISSING/lib"));
+        assert_eq!(Ok("a$".to_string()), expand("a$"));
+    }
+
+    #[test]
+    fn resolve_include_paths_with_glob() {
+        let root = crate::utils::test_dir("include-paths");
+        fs::create_dir_all(root.join("lib/a")).unwrap();
+        fs::create_dir_all(root.join("lib/b")).unwrap();
+        let config =
+            Config::from_toml("[source]\ninclude_paths = [\"lib/*\", \"missing\"]").unwrap();
+        let mut paths = config.source.resolve_include_paths(&root);
+        paths.sort();
+        assert_eq!(vec![root.join("lib/a"), root.join("lib/b")], paths);
+    }
+
+    #[test]
+    fn unset_variables_skip_include_paths() {
+        let root = crate::utils::test_dir("unset-include-paths");
+        fs::create_dir_all(root.join("lib")).unwrap();
+        let config = Config::from_toml(
+            "[source]\ninclude_paths = [\"$FORTH_LSP_UNSET_VARIABLE/lib\", \"lib\"]",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![root.join("lib")],
+            config.source.resolve_include_paths(&root)
+        );
+        let problems = config.source.include_path_problems();
+        assert_eq!(1, problems.len());
+        assert!(problems[0].message.contains("$FORTH_LSP_UNSET_VARIABLE"));
+    }
+
+    #[test]
+    fn format_options() {
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(2, config.format.indent);
+        assert_eq!(WordCase::Preserve, config.format.word_case);
+        assert_eq!(None, config.format.max_line_width);
+        assert!(!config.format.on_save);
+        assert_eq!(BeforeSave::Off, config.format.before_save);
+        let config =
+            Config::from_toml("[format]\nindent = 4\nword_case = \"upper\"\nmax_line_width = 80")
+                .unwrap();
+        assert_eq!(4, config.format.indent);
+        assert_eq!(WordCase::Upper, config.format.word_case);
+        assert_eq!(Some(80), config.format.max_line_width);
+        let config = Config::from_toml("[format]\ncontrol_case = \"upper\"").unwrap();
+        assert_eq!(Some(WordCase::Upper), config.format.control_case);
+        assert_eq!(None, config.format.defining_case);
+        let config = Config::from_toml("[format]\nbefore_save = \"trim\"").unwrap();
+        assert_eq!(BeforeSave::Trim, config.format.before_save);
+    }
+
+    #[test]
+    fn completion_snippets() {
+        assert!(Config::from_toml("").unwrap().completion.snippets);
+        let config = Config::from_toml("[completion]\nsnippets = false").unwrap();
+        assert!(!config.completion.snippets);
+    }
+
+    #[test]
+    fn definition_options() {
+        assert!(Config::from_toml("").unwrap().definition.nearest_preceding);
+        let config = Config::from_toml("[definition]\nnearest_preceding = false").unwrap();
+        assert!(!config.definition.nearest_preceding);
+    }
+
+    #[test]
+    fn diagnostics_options() {
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(
+            DefinitionOrder::Lenient,
+            config.diagnostics.definition_order
+        );
+        let config = Config::from_toml("[diagnostics]\ndefinition_order = \"strict\"").unwrap();
+        assert_eq!(DefinitionOrder::Strict, config.diagnostics.definition_order);
+        let config =
+            Config::from_toml("[diagnostics.severity]\nundefined-word = \"error\"").unwrap();
+        assert_eq!(
+            Some(&Severity::Error),
+            config.diagnostics.severity.get("undefined-word")
+        );
+    }
+
+    #[test]
+    fn builtin_words() {
+        let config = Config::from_toml(
+            "[[builtin.words]]\nname = \"PIN!\"\nstack = \"( x pin -- )\"\ndescription = \"Drive a pin\"\n\n[[builtin.words]]\nname = \"LED\"",
+        )
+        .unwrap();
+        assert_eq!(2, config.builtin.words.len());
+        let standard = Words::default();
+        let word = config.builtin.words[0].to_word(&standard);
+        assert_eq!(
+            "# `PIN!`   `( x pin -- )`\n\nDrive a pin",
+            word.documentation()
+        );
+        assert_eq!(
+            "# `LED`\n\n",
+            config.builtin.words[1].to_word(&standard).documentation()
+        );
+    }
+
+    #[test]
+    fn builtin_words_override_standard_ones() {
+        let config = Config::from_toml(
+            "[[builtin.words]]\nname = \"dup\"\ndescription = \"Prefer OVER for clarity\"",
+        )
+        .unwrap();
+        let standard = Words::default();
+        assert_eq!(
+            "# `dup`   `( x -- x x )`\n\nPrefer OVER for clarity",
+            config.builtin.words[0].to_word(&standard).documentation()
+        );
+    }
+
+    #[test]
+    fn exclude_globs() {
+        let config =
+            Config::from_toml("[source]\nexclude = [\"build\", \"**/*.gen.forth\"]").unwrap();
+        assert!(config.source.is_excluded(Path::new("build/out.forth")));
+        assert!(config.source.is_excluded(Path::new("src/a.gen.forth")));
+        assert!(!config.source.is_excluded(Path::new("src/build.forth")));
+        assert!(!config.source.is_excluded(Path::new("src/a.forth")));
+    }
+
+    #[test]
+    fn reference_scope() {
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(ReferenceScope::Workspace, config.references.scope);
+        let config = Config::from_toml("[references]\nscope = \"include-graph\"").unwrap();
+        assert_eq!(ReferenceScope::IncludeGraph, config.references.scope);
+    }
+
+    #[test]
+    fn problems_keep_the_rest_of_the_config() {
+        let (config, problems) = Config::from_toml_checked(
+            "[color]\nenabled = true\ncolour = 1\n\n[formt]\nindent = 4\n\n[format]\nindent = \"four\"\non_save = true\n",
+        );
+        assert!(config.color.enabled);
+        assert!(config.format.on_save);
+        assert_eq!(2, config.format.indent);
+        let lines: Vec<u32> = problems
+            .iter()
+            .map(|problem| problem.range.unwrap().start.line)
+            .collect();
+        assert_eq!(vec![2, 4, 8], lines);
+        assert!(problems[0].message.contains("unknown field `colour`"));
+        let (_, problems) = Config::from_toml_checked("[color\n");
+        assert_eq!(1, problems.len());
+        assert!(Config::from_toml_checked("").1.is_empty());
+    }
+
+    #[test]
+    fn schema_follows_the_config() {
+        let schema = Config::json_schema();
+        let properties = schema["properties"].as_object().unwrap();
+        let sections: Vec<&str> = properties.keys().map(String::as_str).collect();
+        assert_eq!(
+            vec![
+                "builtin",
+                "color",
+                "completion",
+                "definition",
+                "diagnostics",
+                "format",
+                "references",
+                "source"
+            ],
+            sections
+        );
+        let before_save = &schema["definitions"]["BeforeSave"];
+        assert!(before_save.to_string().contains("\"trim\""));
+        assert_eq!(
+            false,
+            schema["definitions"]["FormatConfig"]["additionalProperties"]
+        );
+        assert_eq!(
+            2,
+            schema["definitions"]["FormatConfig"]["properties"]["indent"]["default"]
+        );
+    }
+}
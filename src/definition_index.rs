@@ -0,0 +1,977 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::{self, definition_name, Program},
+    exceptions::literal_constants,
+    structures::structures,
+    utils::{
+        data_to_position::ToPosition,
+        include_graph::{included_names, IncludeGraph, LoadSegment},
+        token_at::{word_spans, WordSpan},
+    },
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use forth_lexer::{
+    context::ContextTokens,
+    parser::Lexer,
+    token::{Data, Token},
+};
+use lsp_types::{Position, Range};
+use ropey::Rope;
+
+/// Where a word is used, with what was compiled before it
+#[derive(Debug, Clone, Copy)]
+pub struct UseSite<'a> {
+    pub file: &'a str,
+    pub position: Position,
+    pub order: &'a [LoadSegment],
+}
+
+/// What defined a word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefKind {
+    Colon,
+    /// A `:NONAME` definition bound with `IS`, `TO` or `VALUE`
+    Noname,
+    Synonym,
+    Variable,
+    Constant,
+    Value,
+    Defer,
+    Create,
+    Buffer,
+    Marker,
+    Structure,
+    Field,
+}
+
+/// Defining words other than `:` and the kind of word they define
+pub const DEFINING_KINDS: [(&str, DefKind); 13] = [
+    ("VARIABLE", DefKind::Variable),
+    ("2VARIABLE", DefKind::Variable),
+    ("FVARIABLE", DefKind::Variable),
+    ("CONSTANT", DefKind::Constant),
+    ("2CONSTANT", DefKind::Constant),
+    ("FCONSTANT", DefKind::Constant),
+    ("VALUE", DefKind::Value),
+    ("2VALUE", DefKind::Value),
+    ("FVALUE", DefKind::Value),
+    ("CREATE", DefKind::Create),
+    ("DEFER", DefKind::Defer),
+    ("BUFFER:", DefKind::Buffer),
+    ("MARKER", DefKind::Marker),
+];
+
+/// Whether `word` is one of the `DEFINING_KINDS`, in any case
+pub fn is_defining_word(word: &str) -> bool {
+    DEFINING_KINDS
+        .iter()
+        .any(|(defining, _)| defining.eq_ignore_ascii_case(word))
+}
+
+/// A word defined in a workspace file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefKind,
+    /// From `:` up to and including `;`
+    pub range: Range,
+    /// Just the name following `:`
+    pub selection_range: Range,
+    /// The word a `SYNONYM` or `ALIAS` stands for
+    pub alias_of: Option<String>,
+    /// Set for fields of a `BEGIN-STRUCTURE`
+    pub field_of: Option<FieldOf>,
+    /// The literal of `n CONSTANT name`
+    pub constant: Option<String>,
+    /// The stack comment following the name of a colon definition
+    pub stack: Option<String>,
+    /// The `\` comment lines right above the definition
+    pub doc: Option<String>,
+    /// `IS`, `TO` or `VALUE` for a `:NONAME` definition, named after the word it is bound to
+    pub bound_by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldOf {
+    pub structure: String,
+    /// Sizes of the fields before this one, like `1 CELLS + 1 CHARS`
+    pub offset: String,
+}
+
+/// `SYNONYM new old` or `' old ALIAS new`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Synonym<'a> {
+    pub start: Data<'a>,
+    pub end: Data<'a>,
+    pub name: Data<'a>,
+    pub target: Data<'a>,
+}
+
+/// Words storing the execution token of a `:NONAME` definition under the name after them
+pub const NONAME_BINDERS: [&str; 3] = ["IS", "TO", "VALUE"];
+
+/// The binding word and the name right after the `;` of a `:NONAME` definition, like
+/// `IS name` in `:NONAME ... ; IS name`
+pub fn noname_binding<'a>(
+    tokens: &[Token<'a>],
+    definition: &ast::Definition<'a>,
+) -> Option<(Data<'a>, Data<'a>)> {
+    let semicolon = definition.semicolon?;
+    let ix = tokens
+        .iter()
+        .position(|token| token.get_data().start == semicolon.start)?;
+    let mut words = tokens[ix + 1..]
+        .iter()
+        .filter(|token| !matches!(token, Token::Comment(_) | Token::StackComment(_)));
+    match (words.next(), words.next()) {
+        (Some(Token::Word(binder)), Some(Token::Word(name)))
+            if NONAME_BINDERS
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case(binder.value)) =>
+        {
+            Some((*binder, *name))
+        }
+        _ => None,
+    }
+}
+
+/// Every synonym created in `tokens`
+pub fn synonyms<'a>(tokens: &[Token<'a>]) -> Vec<Synonym<'a>> {
+    let mut ret = vec![];
+    for (ix, token) in tokens.iter().enumerate() {
+        let Token::Word(word) = token else {
+            continue;
+        };
+        match (word.value.to_uppercase().as_str(), ix.checked_sub(2)) {
+            ("SYNONYM", _) => {
+                if let (Some(name), Some(Token::Word(target))) = (
+                    tokens.get(ix + 1).and_then(definition_name),
+                    tokens.get(ix + 2),
+                ) {
+                    ret.push(Synonym {
+                        start: *word,
+                        end: *target,
+                        name,
+                        target: *target,
+                    });
+                }
+            }
+            ("ALIAS", Some(tick)) => {
+                if let (Token::Word(start), Token::Word(target), Some(name)) = (
+                    &tokens[tick],
+                    &tokens[tick + 1],
+                    tokens.get(ix + 1).and_then(definition_name),
+                ) {
+                    if start.value == "'" || start.value == "[']" {
+                        ret.push(Synonym {
+                            start: *start,
+                            end: name,
+                            name,
+                            target: *target,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// The `\` comment lines right above a definition starting a line at `start`, without a
+/// blank line between them. When some of them start with `\ doc:` only those are the
+/// documentation, so other comments can sit next to it
+pub fn doc_comment(rope: &Rope, start: Position) -> Option<String> {
+    let line = start.line as usize;
+    let mut before = rope.line(line).chars().take(start.character as usize);
+    if !before.all(char::is_whitespace) {
+        return None;
+    }
+    let mut lines = vec![];
+    for line in (0..line).rev() {
+        let text = rope.line(line).to_string();
+        let text = text.trim();
+        let Some(comment) = text.strip_prefix('\\') else {
+            break;
+        };
+        if !comment.is_empty() && !comment.starts_with(char::is_whitespace) {
+            break;
+        }
+        lines.push(
+            comment
+                .strip_prefix(' ')
+                .unwrap_or(comment)
+                .trim_end()
+                .to_string(),
+        );
+    }
+    lines.reverse();
+    let marked: Vec<&str> = lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("doc:"))
+        .map(str::trim)
+        .collect();
+    let doc = if marked.is_empty() {
+        lines.join("\n")
+    } else {
+        marked.join("\n")
+    };
+    Some(doc).filter(|doc| !doc.trim().is_empty())
+}
+
+/// Words whose next word is taken as an execution token instead of executed
+pub const TICKING_WORDS: [&str; 3] = ["'", "[']", "POSTPONE"];
+
+/// Any use of a word, definitions included
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub name: String,
+    pub range: Range,
+    /// Named after `'`, `[']` or `POSTPONE`, e.g. to be executed later through a `DEFER`
+    pub xt: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FileIndex {
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+    /// Every word in order, to find the one at a position without lexing again
+    pub words: Vec<WordSpan>,
+    /// Names of the included files as written, with the end of each
+    pub includes: Vec<(Position, String)>,
+}
+
+impl FileIndex {
+    /// The innermost definition using `reference`, not counting the name of a definition
+    pub fn user_of(&self, reference: &Reference) -> Option<&Definition> {
+        let within =
+            |outer: Range, inner: Range| outer.start <= inner.start && inner.end <= outer.end;
+        self.definitions
+            .iter()
+            .filter(|definition| {
+                definition.selection_range != reference.range
+                    && within(definition.range, reference.range)
+            })
+            .min_by_key(|definition| {
+                (
+                    definition.range.end.line - definition.range.start.line,
+                    definition.range.end.character,
+                )
+            })
+    }
+
+    pub fn from_rope(rope: &Rope) -> FileIndex {
+        let progn = rope.to_string();
+        let mut lexer = Lexer::new(progn.as_str());
+        let tokens = lexer.parse();
+        let range = |begin: &Data, end: &Data| Range {
+            start: begin.to_position_start(rope),
+            end: end.to_position_end(rope),
+        };
+        let mut definitions: Vec<Definition> = Program::parse(&tokens)
+            .definitions()
+            .filter_map(|definition| {
+                let mut name = definition.name?;
+                let mut kind = DefKind::Colon;
+                let mut bound_by = None;
+                if definition.is_noname() {
+                    // only a bound `:NONAME` can be found by a name
+                    let (binder, target) = noname_binding(&tokens, definition)?;
+                    name = target;
+                    kind = DefKind::Noname;
+                    bound_by = Some(binder.value.to_uppercase());
+                }
+                // half typed definitions end at their last token
+                let end = Data::new(definition.span.end, definition.span.end, "");
+                Some(Definition {
+                    name: name.value.to_owned(),
+                    kind,
+                    range: range(&definition.colon, &definition.semicolon.unwrap_or(end)),
+                    selection_range: range(&name, &name),
+                    alias_of: None,
+                    field_of: None,
+                    constant: None,
+                    stack: definition
+                        .stack_comment()
+                        .map(|stack| stack.value.to_owned()),
+                    doc: None,
+                    bound_by,
+                })
+            })
+            .collect();
+        definitions.extend(synonyms(&tokens).iter().map(|synonym| Definition {
+            name: synonym.name.value.to_owned(),
+            kind: DefKind::Synonym,
+            range: range(&synonym.start, &synonym.end),
+            selection_range: range(&synonym.name, &synonym.name),
+            alias_of: Some(synonym.target.value.to_owned()),
+            field_of: None,
+            constant: None,
+            stack: None,
+            doc: None,
+            bound_by: None,
+        }));
+        definitions.extend(
+            literal_constants(&tokens)
+                .iter()
+                .map(|(name, literal)| Definition {
+                    name: name.value.to_owned(),
+                    kind: DefKind::Constant,
+                    range: range(literal, name),
+                    selection_range: range(name, name),
+                    alias_of: None,
+                    field_of: None,
+                    constant: Some(literal.value.to_owned()),
+                    stack: None,
+                    doc: None,
+                    bound_by: None,
+                }),
+        );
+        for structure in structures(&tokens) {
+            definitions.push(Definition {
+                name: structure.name.value.to_owned(),
+                kind: DefKind::Structure,
+                range: range(&structure.begin, &structure.last()),
+                selection_range: range(&structure.name, &structure.name),
+                alias_of: None,
+                field_of: None,
+                constant: None,
+                stack: None,
+                doc: None,
+                bound_by: None,
+            });
+            definitions.extend(structure.fields.iter().map(|field| Definition {
+                name: field.name.value.to_owned(),
+                kind: DefKind::Field,
+                range: range(&field.start, &field.name),
+                selection_range: range(&field.name, &field.name),
+                alias_of: None,
+                field_of: Some(FieldOf {
+                    structure: structure.name.value.to_owned(),
+                    offset: field.offset.clone(),
+                }),
+                constant: None,
+                stack: None,
+                doc: None,
+                bound_by: None,
+            }));
+        }
+        // names after a defining word, unless indexed already like literal constants and
+        // `:NONAME` definitions bound with `VALUE`
+        let code: Vec<Token> = ContextTokens::new(tokens.iter().cloned())
+            .filter(|context| !context.in_string && !context.in_comment && !context.parsed)
+            .map(|context| context.token)
+            .collect();
+        for pair in code.windows(2) {
+            let [Token::Word(defining), name] = pair else {
+                continue;
+            };
+            let (Some((_, kind)), Some(name)) = (
+                DEFINING_KINDS
+                    .iter()
+                    .find(|(word, _)| word.eq_ignore_ascii_case(defining.value)),
+                definition_name(name),
+            ) else {
+                continue;
+            };
+            let selection_range = range(&name, &name);
+            if definitions
+                .iter()
+                .any(|definition| definition.selection_range == selection_range)
+            {
+                continue;
+            }
+            definitions.push(Definition {
+                name: name.value.to_owned(),
+                kind: *kind,
+                range: range(defining, &name),
+                selection_range,
+                alias_of: None,
+                field_of: None,
+                constant: None,
+                stack: None,
+                doc: None,
+                bound_by: None,
+            });
+        }
+        definitions.sort_by_key(|definition| definition.range.start);
+        for definition in definitions.iter_mut() {
+            definition.doc = doc_comment(rope, definition.range.start);
+        }
+        // the text of strings and the character after `CHAR` use no word
+        let mut ticked = false;
+        let references = ContextTokens::new(tokens.iter().cloned())
+            .filter(|context| !context.in_string)
+            .filter_map(|context| match context.token {
+                Token::Word(_) if context.parsed && !ticked => {
+                    ticked = false;
+                    None
+                }
+                Token::Word(data) => {
+                    let xt = ticked;
+                    ticked = TICKING_WORDS
+                        .iter()
+                        .any(|word| word.eq_ignore_ascii_case(data.value));
+                    Some(Reference {
+                        name: data.value.to_owned(),
+                        range: range(&data, &data),
+                        xt,
+                    })
+                }
+                Token::Comment(_) => None,
+                _ => {
+                    ticked = false;
+                    None
+                }
+            })
+            .collect();
+        FileIndex {
+            definitions,
+            references,
+            words: word_spans(&tokens),
+            includes: included_names(&tokens, rope),
+        }
+    }
+}
+
+/// Definitions and references of every known file, keyed like the files map
+#[derive(Debug, Default)]
+pub struct DefinitionIndex {
+    files: HashMap<String, FileIndex>,
+    includes: IncludeGraph,
+    /// How long the latest indexing took, of one file or of many at once
+    last_index: Duration,
+}
+
+/// Size of the index, for diagnosing slow workspaces
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IndexStats {
+    pub files: usize,
+    pub definitions: usize,
+    pub references: usize,
+    /// Estimated bytes held by the index, strings included
+    pub bytes: usize,
+    /// How long the latest indexing took
+    pub last_index: Duration,
+}
+
+impl DefinitionIndex {
+    pub fn insert(&mut self, file: String, index: FileIndex) {
+        self.includes.insert(&file, &index.includes);
+        self.files.insert(file, index);
+    }
+
+    /// Re-index `file`, returns the upper cased names it defined before or defines now, but
+    /// not both
+    pub fn update_file(&mut self, file: &str, rope: &Rope) -> HashSet<String> {
+        let started = Instant::now();
+        let names = |index: Option<&FileIndex>| -> HashSet<String> {
+            index
+                .iter()
+                .flat_map(|index| index.definitions.iter())
+                .map(|definition| definition.name.to_uppercase())
+                .collect()
+        };
+        let before = names(self.files.get(file));
+        let updated = FileIndex::from_rope(rope);
+        let after = names(Some(&updated));
+        self.insert(file.to_owned(), updated);
+        self.record_index(started.elapsed());
+        before.symmetric_difference(&after).cloned().collect()
+    }
+
+    /// Remember that the latest indexing, like loading a whole workspace, took `took`
+    pub fn record_index(&mut self, took: Duration) {
+        self.last_index = took;
+    }
+
+    /// Move the index of `from` to `to`, like when an opened document shadows its scanned copy
+    pub fn rename_file(&mut self, from: &str, to: &str) {
+        if let Some(index) = self.files.remove(from) {
+            self.files.insert(to.to_owned(), index);
+        }
+        self.includes.rename(from, to);
+    }
+
+    /// Forget `file`, returns the upper cased names it defined
+    pub fn remove_file(&mut self, file: &str) -> HashSet<String> {
+        self.includes.remove(file);
+        self.files
+            .remove(file)
+            .iter()
+            .flat_map(|index| index.definitions.iter())
+            .map(|definition| definition.name.to_uppercase())
+            .collect()
+    }
+
+    /// Every indexed file with its definitions and references
+    pub fn files(&self) -> impl Iterator<Item = (&String, &FileIndex)> {
+        self.files.iter()
+    }
+
+    /// Which indexed files include which
+    pub fn include_graph(&self) -> &IncludeGraph {
+        &self.includes
+    }
+
+    /// The words of `file` in order, none when it is not indexed
+    pub fn words(&self, file: &str) -> &[WordSpan] {
+        self.files
+            .get(file)
+            .map(|index| index.words.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Name, name range and kind of every definition in `file`, in source order
+    pub fn definitions_in_file(&self, file: &str) -> impl Iterator<Item = (&str, Range, DefKind)> {
+        self.symbols(file).map(|definition| {
+            (
+                definition.name.as_str(),
+                definition.selection_range,
+                definition.kind,
+            )
+        })
+    }
+
+    /// Every definition in `file`, in source order
+    pub fn symbols(&self, file: &str) -> impl Iterator<Item = &Definition> {
+        self.files
+            .get(file)
+            .into_iter()
+            .flat_map(|index| index.definitions.iter())
+    }
+
+    /// Every definition of `word`, ignoring case, together with its file
+    pub fn definitions(&self, word: &str) -> Vec<(&String, &Definition)> {
+        let mut ret = vec![];
+        for (file, index) in self.files.iter() {
+            for definition in &index.definitions {
+                if definition.name.eq_ignore_ascii_case(word) {
+                    ret.push((file, definition));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Every use of `word`, ignoring case, and whether that use is the name of a definition
+    pub fn references(&self, word: &str) -> Vec<(&String, &Reference, bool)> {
+        let mut ret = vec![];
+        for (file, index) in self.files.iter() {
+            for reference in &index.references {
+                if reference.name.eq_ignore_ascii_case(word) {
+                    let is_declaration = index
+                        .definitions
+                        .iter()
+                        .any(|definition| definition.selection_range == reference.range);
+                    ret.push((file, reference, is_declaration));
+                }
+            }
+        }
+        ret
+    }
+
+    /// The word `word` is a synonym of, if it is one
+    pub fn alias_target(&self, word: &str) -> Option<&str> {
+        self.definitions(word)
+            .into_iter()
+            .find_map(|(_, definition)| definition.alias_of.as_deref())
+    }
+
+    /// The definition of `word` a use at `site` binds to: the last one compiled before it
+    /// in load order, or the one `site` names
+    pub fn in_effect(&self, word: &str, site: UseSite) -> Option<(&String, &Definition)> {
+        let before = |a: Position, b: Position| (a.line, a.character) <= (b.line, b.character);
+        let matching = |file: &str| {
+            self.files
+                .get_key_value(file)
+                .into_iter()
+                .flat_map(|(file, index)| {
+                    index
+                        .definitions
+                        .iter()
+                        .filter(|definition| definition.name.eq_ignore_ascii_case(word))
+                        .map(move |definition| (file, definition))
+                })
+        };
+        let named = matching(site.file).find(|(_, definition)| {
+            before(definition.selection_range.start, site.position)
+                && before(site.position, definition.selection_range.end)
+        });
+        named.or_else(|| {
+            site.order.iter().rev().find_map(|segment| {
+                matching(&segment.file)
+                    .filter(|(_, definition)| {
+                        before(segment.start, definition.range.start)
+                            && before(definition.range.end, segment.end)
+                    })
+                    .max_by_key(|(_, definition)| {
+                        (definition.range.end.line, definition.range.end.character)
+                    })
+            })
+        })
+    }
+
+    /// What `word` at `site` refers to: the definition in effect there when one was
+    /// compiled before it, otherwise every definition
+    pub fn resolve(&self, word: &str, site: UseSite) -> Vec<(&String, &Definition)> {
+        match self.in_effect(word, site) {
+            Some(definition) => vec![definition],
+            None => self.definitions(word),
+        }
+    }
+
+    pub fn stats(&self) -> IndexStats {
+        let mut ret = IndexStats {
+            files: self.files.len(),
+            last_index: self.last_index,
+            ..Default::default()
+        };
+        for (file, index) in self.files.iter() {
+            ret.definitions += index.definitions.len();
+            ret.references += index.references.len();
+            ret.bytes += file.capacity()
+                + size_of::<(String, FileIndex)>()
+                + index.definitions.capacity() * size_of::<Definition>()
+                + index.references.capacity() * size_of::<Reference>()
+                + index.words.capacity() * size_of::<WordSpan>();
+            for definition in index.definitions.iter() {
+                ret.bytes += definition.name.capacity()
+                    + definition.alias_of.as_ref().map_or(0, String::capacity)
+                    + definition.constant.as_ref().map_or(0, String::capacity)
+                    + definition.doc.as_ref().map_or(0, String::capacity)
+                    + definition.field_of.as_ref().map_or(0, |field_of| {
+                        field_of.structure.capacity() + field_of.offset.capacity()
+                    });
+            }
+            for reference in index.references.iter() {
+                ret.bytes += reference.name.capacity();
+            }
+        }
+        ret
+    }
+
+    /// Names of every definition, duplicates included
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.files.values().flat_map(|index| {
+            index
+                .definitions
+                .iter()
+                .map(|definition| definition.name.as_str())
+        })
+    }
+
+    pub fn is_defined(&self, word: &str) -> bool {
+        self.files.values().any(|index| {
+            index
+                .definitions
+                .iter()
+                .any(|definition| definition.name.eq_ignore_ascii_case(word))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utils::token_at::WordTokenAt;
+    use lsp_types::Position;
+
+    #[test]
+    fn index_definitions_and_references() {
+        let rope = Rope::from_str(": add1 ( n -- n )\n  1 + ;\n2 add1");
+        let index = FileIndex::from_rope(&rope);
+        assert_eq!(1, index.definitions.len());
+        let definition = &index.definitions[0];
+        assert_eq!("add1", definition.name);
+        assert_eq!(Position::new(0, 0), definition.range.start);
+        assert_eq!(Position::new(1, 7), definition.range.end);
+        assert_eq!(Position::new(0, 2), definition.selection_range.start);
+        assert_eq!(Position::new(0, 6), definition.selection_range.end);
+        let names: Vec<&str> = index.references.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(vec!["add1", "+", "add1"], names);
+    }
+
+    #[test]
+    fn update_replaces_file() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("a.forth", &Rope::from_str(": one 1 ;"));
+        assert!(index.is_defined("ONE"));
+        let changed = index.update_file("a.forth", &Rope::from_str(": two 2 ;"));
+        assert_eq!(
+            HashSet::from(["ONE".to_string(), "TWO".to_string()]),
+            changed
+        );
+        assert!(!index.is_defined("one"));
+        assert_eq!(1, index.definitions("two").len());
+    }
+
+    #[test]
+    fn include_graph_follows_updates() {
+        let mut index = DefinitionIndex::default();
+        let includes = |index: &DefinitionIndex, key: &str| -> Vec<String> {
+            index.include_graph().includes(key).cloned().collect()
+        };
+        index.update_file("/p/main.forth", &Rope::from_str("include lib.forth"));
+        assert!(includes(&index, "/p/main.forth").is_empty());
+        index.update_file("/p/lib.forth", &Rope::from_str(": sq dup * ;"));
+        assert_eq!(vec!["/p/lib.forth"], includes(&index, "/p/main.forth"));
+        index.rename_file("/p/lib.forth", "file:///p/lib.forth");
+        assert_eq!(
+            vec!["file:///p/lib.forth"],
+            includes(&index, "/p/main.forth")
+        );
+        index.remove_file("file:///p/lib.forth");
+        assert!(includes(&index, "/p/main.forth").is_empty());
+        index.update_file("/p/main.forth", &Rope::from_str(": main ;"));
+        index.update_file("/p/lib.forth", &Rope::new());
+        assert!(includes(&index, "/p/main.forth").is_empty());
+    }
+
+    #[test]
+    fn synonyms_are_definitions() {
+        let mut index = DefinitionIndex::default();
+        index.update_file(
+            "a.forth",
+            &Rope::from_str(": one 1 ;\nsynonym uno one\n' one ALIAS eins\n['] dup alias twin"),
+        );
+        assert_eq!(Some("one"), index.alias_target("UNO"));
+        assert_eq!(Some("one"), index.alias_target("eins"));
+        assert_eq!(Some("dup"), index.alias_target("twin"));
+        assert_eq!(None, index.alias_target("one"));
+        let (_, definition) = index.definitions("eins")[0];
+        assert_eq!(Position::new(2, 0), definition.range.start);
+        assert_eq!(Position::new(2, 16), definition.range.end);
+        assert_eq!(Position::new(2, 12), definition.selection_range.start);
+    }
+
+    #[test]
+    fn structures_and_fields_are_definitions() {
+        let mut index = DefinitionIndex::default();
+        index.update_file(
+            "a.forth",
+            &Rope::from_str("begin-structure point\n  field: p.x\n  field: p.y\nend-structure"),
+        );
+        let (_, point) = index.definitions("point")[0];
+        assert_eq!(Position::new(3, 13), point.range.end);
+        let (_, y) = index.definitions("p.y")[0];
+        assert_eq!(Position::new(2, 2), y.range.start);
+        let field_of = y.field_of.as_ref().unwrap();
+        assert_eq!("point", field_of.structure);
+        assert_eq!("1 CELLS", field_of.offset);
+    }
+
+    #[test]
+    fn literal_constants_are_definitions() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("a.forth", &Rope::from_str("-100 constant err-full"));
+        let (_, definition) = index.definitions("err-full")[0];
+        assert_eq!(Some("-100".to_string()), definition.constant);
+        assert_eq!(Position::new(0, 0), definition.range.start);
+    }
+
+    #[test]
+    fn names_that_lex_as_numbers_are_definitions() {
+        let index = FileIndex::from_rope(&Rope::from_str(
+            ": 2dead ;\n5 constant 1add\nsynonym 3bad dup",
+        ));
+        let names: Vec<&str> = index
+            .definitions
+            .iter()
+            .map(|definition| definition.name.as_str())
+            .collect();
+        assert_eq!(vec!["2dead", "1add", "3bad"], names);
+    }
+
+    #[test]
+    fn doc_comments_above_definitions() {
+        let index = FileIndex::from_rope(&Rope::from_str(
+            "\\ unrelated\n\n\\ Square a number.\n\\   Wraps around.\n: sq dup * ;\n\\ TODO faster\n\\ doc: Cube it.\n: cube dup sq * ; : x ;\n5 constant five",
+        ));
+        let docs: Vec<Option<&str>> = index
+            .definitions
+            .iter()
+            .map(|definition| definition.doc.as_deref())
+            .collect();
+        assert_eq!(
+            vec![
+                Some("Square a number.\n  Wraps around."),
+                Some("Cube it."),
+                None,
+                None
+            ],
+            docs
+        );
+    }
+
+    #[test]
+    fn stats_count_every_file() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("a.forth", &Rope::from_str(": one 1 ;\none"));
+        index.update_file("b.forth", &Rope::from_str(": two one one ;"));
+        let stats = index.stats();
+        assert_eq!(2, stats.files);
+        assert_eq!(2, stats.definitions);
+        assert_eq!(5, stats.references);
+        assert!(stats.bytes > 5 * size_of::<Reference>());
+    }
+
+    #[test]
+    fn stats_report_the_latest_indexing() {
+        let mut index = DefinitionIndex::default();
+        index.record_index(Duration::from_secs(60));
+        assert_eq!(Duration::from_secs(60), index.stats().last_index);
+        index.update_file("a.forth", &Rope::from_str(": one 1 ;"));
+        assert!(index.stats().last_index < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn in_effect_follows_redefinitions() {
+        let mut index = DefinitionIndex::default();
+        index.update_file(
+            "a.forth",
+            &Rope::from_str(": one 1 ;\none\n: one one 1+ ;\none"),
+        );
+        let site = |position: Position| {
+            let order = vec![LoadSegment {
+                file: "a.forth".to_string(),
+                start: Position::default(),
+                end: position,
+            }];
+            index
+                .in_effect(
+                    "one",
+                    UseSite {
+                        file: "a.forth",
+                        position,
+                        order: &order,
+                    },
+                )
+                .map(|(_, definition)| definition.range.start.line)
+        };
+        assert_eq!(Some(0), site(Position::new(1, 1)));
+        // inside its own redefinition a word still means the previous definition
+        assert_eq!(Some(0), site(Position::new(2, 8)));
+        assert_eq!(Some(2), site(Position::new(2, 3)));
+        assert_eq!(Some(2), site(Position::new(3, 1)));
+        assert_eq!(None, site(Position::new(0, 0)));
+    }
+
+    #[test]
+    fn in_effect_follows_load_order() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("a.forth", &Rope::from_str(": one 1 ;"));
+        index.update_file("b.forth", &Rope::from_str(": one 2 ;"));
+        let segment = |file: &str| LoadSegment {
+            file: file.to_string(),
+            start: Position::default(),
+            end: Position::new(u32::MAX, u32::MAX),
+        };
+        let order = [segment("b.forth"), segment("a.forth"), segment("c.forth")];
+        let site = UseSite {
+            file: "c.forth",
+            position: Position::default(),
+            order: &order,
+        };
+        let resolved = index.resolve("one", site);
+        assert_eq!(
+            vec!["a.forth"],
+            resolved
+                .iter()
+                .map(|(file, _)| file.as_str())
+                .collect::<Vec<_>>()
+        );
+        let unrelated = UseSite { order: &[], ..site };
+        assert_eq!(2, index.resolve("one", unrelated).len());
+    }
+
+    #[test]
+    fn references_flag_declarations() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("a.forth", &Rope::from_str(": one 1 ;\none ONE"));
+        let declarations: Vec<bool> = index
+            .references("one")
+            .iter()
+            .map(|(_, _, is_declaration)| *is_declaration)
+            .collect();
+        assert_eq!(vec![true, false, false], declarations);
+    }
+
+    #[test]
+    fn words_follow_updates() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("a.forth", &Rope::from_str(": one 1 ;"));
+        assert_eq!(2, index.words("a.forth").word_token_at(3).unwrap().start);
+        index.update_file("a.forth", &Rope::from_str("one"));
+        assert_eq!(0, index.words("a.forth").word_token_at(3).unwrap().start);
+        assert!(index.words("b.forth").is_empty());
+    }
+
+    #[test]
+    fn ticked_words_are_xt_references() {
+        let rope = Rope::from_str(
+            "defer op\n: run ['] sq is op ;\n' ( the xt ) sq is op\n: compile-sq postpone sq ;",
+        );
+        let index = FileIndex::from_rope(&rope);
+        let sq: Vec<bool> = index
+            .references
+            .iter()
+            .filter(|reference| reference.name == "sq")
+            .map(|reference| reference.xt)
+            .collect();
+        assert_eq!(vec![true, true, true], sq);
+        assert!(index
+            .references
+            .iter()
+            .filter(|r| r.xt)
+            .all(|r| r.name == "sq"));
+    }
+
+    #[test]
+    fn strings_and_characters_hold_no_references() {
+        let rope = Rope::from_str(": one 1 ;\n: greet .\" one\" s\" one two\" char one one ;");
+        let index = FileIndex::from_rope(&rope);
+        let names: Vec<(&str, Position)> = index
+            .references
+            .iter()
+            .map(|reference| (reference.name.as_str(), reference.range.start))
+            .collect();
+        assert_eq!(
+            vec![
+                ("one", Position::new(0, 2)),
+                ("greet", Position::new(1, 2)),
+                (".\"", Position::new(1, 8)),
+                ("s\"", Position::new(1, 16)),
+                ("char", Position::new(1, 28)),
+                ("one", Position::new(1, 37)),
+            ],
+            names
+        );
+    }
+
+    #[test]
+    fn bound_nonames_are_named_after_their_target() {
+        let index = FileIndex::from_rope(&Rope::from_str(
+            "defer greet\n:noname .\" hi\" ; is greet\n:NONAME 1 ; drop\n: noname 2 ;",
+        ));
+        let names: Vec<(&str, Option<&str>)> = index
+            .definitions
+            .iter()
+            .map(|definition| (definition.name.as_str(), definition.bound_by.as_deref()))
+            .collect();
+        assert_eq!(
+            vec![("greet", None), ("greet", Some("IS")), ("noname", None)],
+            names
+        );
+        let greet = &index.definitions[1];
+        assert_eq!(Position::new(1, 0), greet.range.start);
+        assert_eq!(Position::new(1, 20), greet.selection_range.start);
+    }
+}
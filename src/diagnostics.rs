@@ -0,0 +1,1403 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::{definition_name, Program},
+    baseline::relative_path,
+    config::{DefinitionOrder, DiagnosticsConfig},
+    definition_index::{is_defining_word, synonyms, DefinitionIndex},
+    loops::loop_index_uses,
+    markers::forgotten_uses,
+    project::{ProjectWords, Projects},
+    structures::structures,
+    suppressions::{directives, suppress, unused_suppressions, UNUSED_SUPPRESSION_CODE},
+    utils::{
+        data_to_position::ToPosition,
+        display_path, file_path, file_uri,
+        include_graph::{include_targets, normalize},
+        position_encoding::{data_range, PositionEncoding},
+        ropey::bounds::RopeBoundsCheck,
+        suggest::closest,
+    },
+    words::Words,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use forth_lexer::{
+    context::{ContextTokens, STRING_WORDS},
+    parser::Lexer,
+    token::{Data, Token},
+};
+use lsp_server::{Connection, Message, Notification};
+use lsp_types::{
+    notification::{Notification as _, PublishDiagnostics},
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+    NumberOrString, Position, PublishDiagnosticsParams, Range, TextEdit, Url,
+};
+use rayon::prelude::*;
+use ropey::Rope;
+
+/// Longest string a counted string can hold in a standard system
+const MAX_COUNTED_STRING: usize = 255;
+
+/// `Diagnostic::code` of undefined words, their `data` holds the `suggestion` if there is one
+pub const UNDEFINED_WORD_CODE: &str = "undefined-word";
+
+/// `Diagnostic::code` of spaces and tabs ending a line
+pub const TRAILING_WHITESPACE_CODE: &str = "trailing-whitespace";
+
+/// `Diagnostic::code` of a document not ending in a line break, which formatting adds
+pub const MISSING_FINAL_NEWLINE_CODE: &str = "missing-final-newline";
+
+/// `Diagnostic::code` of a word used above its definition, with `diagnostics.definition_order`
+/// set to `strict`
+pub const USE_BEFORE_DEFINITION_CODE: &str = "use-before-definition";
+
+/// The documentation of the rules, with a section named after each code
+pub const RULES_DOCS: &str = "https://github.com/AlexanderBrevig/forth-lsp/blob/main/docs/rules.md";
+
+/// `Diagnostic::code` of problems in a `.forth-lsp.toml`, which no directive suppresses
+pub const CONFIG_CODE: &str = "config";
+
+/// `Diagnostic::code` of unbalanced definitions and control structures
+pub const SYNTAX_CODE: &str = "syntax";
+
+/// `Diagnostic::code` of a `)` that closes no comment
+pub const UNMATCHED_PAREN_CODE: &str = "unmatched-paren";
+
+/// `Diagnostic::code` of words used in the wrong state, compiling or interpreting
+pub const STATE_CODE: &str = "state";
+
+/// `Diagnostic::code` of unterminated and overlong string literals
+pub const STRING_LITERAL_CODE: &str = "string-literal";
+
+/// `Diagnostic::code` of an include closing a cycle of includes
+pub const INCLUDE_CYCLE_CODE: &str = "include-cycle";
+
+/// `Diagnostic::code` of an include of a file found nowhere it is searched for, its `data`
+/// holds the `path` the file would be created at
+pub const MISSING_INCLUDE_CODE: &str = "missing-include";
+
+/// `Diagnostic::code` of a word used after running a `MARKER` that forgot it
+pub const USE_AFTER_FORGET_CODE: &str = "use-after-forget";
+
+/// `Diagnostic::code` of `I` or `J` used where not enough `DO` loops are active
+pub const LOOP_INDEX_CODE: &str = "loop-index";
+
+/// Codes of the diagnostics that directives and `diagnostics.severity` refer to
+pub const RULES: [&str; 13] = [
+    UNDEFINED_WORD_CODE,
+    TRAILING_WHITESPACE_CODE,
+    MISSING_FINAL_NEWLINE_CODE,
+    USE_BEFORE_DEFINITION_CODE,
+    SYNTAX_CODE,
+    UNMATCHED_PAREN_CODE,
+    STATE_CODE,
+    STRING_LITERAL_CODE,
+    INCLUDE_CYCLE_CODE,
+    MISSING_INCLUDE_CODE,
+    USE_AFTER_FORGET_CODE,
+    LOOP_INDEX_CODE,
+    UNUSED_SUPPRESSION_CODE,
+];
+
+/// Words whose interpretation semantics the standard leaves undefined. `."` is left out
+/// as practically every system interprets it
+pub const COMPILE_ONLY_WORDS: [&str; 32] = [
+    "IF",
+    "ELSE",
+    "THEN",
+    "BEGIN",
+    "UNTIL",
+    "WHILE",
+    "REPEAT",
+    "AGAIN",
+    "AHEAD",
+    "DO",
+    "?DO",
+    "LOOP",
+    "+LOOP",
+    "LEAVE",
+    "UNLOOP",
+    "I",
+    "J",
+    "EXIT",
+    "RECURSE",
+    "DOES>",
+    "LITERAL",
+    "2LITERAL",
+    "FLITERAL",
+    "SLITERAL",
+    "POSTPONE",
+    "[']",
+    "[CHAR]",
+    "[COMPILE]",
+    "ABORT\"",
+    "C\"",
+    "CASE",
+    "ENDCASE",
+];
+
+/// Interpreting words that parse the next word when a definition runs, which is rarely
+/// what is meant inside a definition
+pub const INTERPRETING_PARSERS: [(&str, &str); 2] = [("'", "[']"), ("CHAR", "[CHAR]")];
+
+pub fn get_diagnostics(rope: &Rope, data: &Words, index: &DefinitionIndex) -> Vec<Diagnostic> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let builtins: HashSet<String> = data
+        .words
+        .iter()
+        .map(|word| word.token.to_uppercase())
+        .collect();
+    let local_definitions = local_definitions(&tokens);
+    // suggest the user's own words before builtins
+    let known: Vec<&str> = local_definitions
+        .values()
+        .map(|name| name.value)
+        .chain(index.names())
+        .chain(data.words.iter().map(|word| word.token))
+        .collect();
+    let characters = character_literals(&tokens);
+    let mut ret = vec![];
+    for error in Program::parse(&tokens).errors() {
+        let data = Data::new(error.span.start, error.span.end, "");
+        ret.push(Diagnostic {
+            range: data_range(&data, rope),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(SYNTAX_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message: error.message,
+            ..Default::default()
+        });
+    }
+    for (data, severity, message) in state_misuse(&tokens) {
+        ret.push(Diagnostic {
+            range: data_range(&data, rope),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(STATE_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message,
+            ..Default::default()
+        });
+    }
+    for (data, severity, message) in string_literals(&tokens, rope) {
+        ret.push(Diagnostic {
+            range: data_range(&data, rope),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(STRING_LITERAL_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message,
+            ..Default::default()
+        });
+    }
+    for forgotten in forgotten_uses(&tokens) {
+        let run = forgotten.forgotten_by;
+        ret.push(Diagnostic {
+            range: data_range(&forgotten.word, rope),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(USE_AFTER_FORGET_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message: format!(
+                "`{}` was forgotten by running `{}` on line {}",
+                forgotten.word.value,
+                run.value,
+                run.to_position_start(rope).line + 1
+            ),
+            ..Default::default()
+        });
+    }
+    for found in loop_index_uses(&tokens) {
+        if found.opener.is_some() {
+            continue;
+        }
+        let message = match found.active {
+            0 => format!("`{}` is used outside of a `DO` loop", found.word.value),
+            _ => format!(
+                "`{}` reads the index of an outer loop, but only one `DO` loop is active here",
+                found.word.value
+            ),
+        };
+        ret.push(Diagnostic {
+            range: data_range(&found.word, rope),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(LOOP_INDEX_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message,
+            ..Default::default()
+        });
+    }
+    for data in words_outside_strings(&tokens) {
+        let word = data.value.to_uppercase();
+        if builtins.contains(&word)
+            || local_definitions.contains_key(&word)
+            || index.is_defined(data.value)
+            || is_number(data.value)
+            || characters.contains(&data.start)
+        {
+            continue;
+        }
+        if data.value == ")" {
+            ret.push(Diagnostic {
+                range: data_range(&data, rope),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(UNMATCHED_PAREN_CODE.to_string())),
+                source: Some("forth-lsp".to_string()),
+                message: "Unmatched closing parenthesis".to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let suggestion = closest(data.value, known.iter().copied());
+        let message = match suggestion {
+            Some(suggestion) => format!(
+                "Undefined word `{}`, did you mean `{}`?",
+                data.value, suggestion
+            ),
+            None => format!("Undefined word `{}`", data.value),
+        };
+        ret.push(Diagnostic {
+            range: data_range(&data, rope),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(UNDEFINED_WORD_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message,
+            data: suggestion.map(|suggestion| serde_json::json!({ "suggestion": suggestion })),
+            ..Default::default()
+        });
+    }
+    ret
+}
+
+/// Every diagnostic of `file`, one of `files`, at the severities `config` sets for their rule,
+/// without those suppressed by a directive and with the directives that suppress nothing.
+/// Included files are searched for next to `file`, then in `include_dirs`
+pub fn document_diagnostics(
+    file: &str,
+    files: &HashMap<String, Rope>,
+    data: &Words,
+    index: &DefinitionIndex,
+    config: &DiagnosticsConfig,
+    include_dirs: &[PathBuf],
+) -> Vec<Diagnostic> {
+    let Some(rope) = files.get(file) else {
+        return vec![];
+    };
+    let mut ret = get_diagnostics(rope, data, index);
+    ret.extend(include_cycles(file, files, index));
+    ret.extend(missing_includes(file, files, include_dirs));
+    ret.extend(whitespace_lints(rope));
+    ret.extend(use_before_definition(rope, data, config.definition_order));
+    relate_definitions(file, rope, index, &mut ret);
+    let mut directives = directives(rope);
+    suppress(&mut ret, &mut directives);
+    let mut unused = unused_suppressions(&directives, &RULES);
+    // `ignore-file[unused-suppression]` silences the directives left over
+    suppress(&mut unused, &mut directives);
+    ret.extend(unused);
+    for diagnostic in ret.iter_mut() {
+        if let Some(severity) = rule(diagnostic).and_then(|rule| config.severity.get(rule)) {
+            diagnostic.severity = Some(severity.to_lsp());
+        }
+        diagnostic.code_description = rule(diagnostic).and_then(code_description);
+    }
+    ret
+}
+
+/// The diagnostics of `file` as its project configures them, without those its baseline
+/// records, their ranges in `encoding`
+pub fn project_diagnostics(
+    file: &str,
+    files: &HashMap<String, Rope>,
+    words: &ProjectWords,
+    index: &DefinitionIndex,
+    projects: &Projects,
+    encoding: PositionEncoding,
+) -> Vec<Diagnostic> {
+    let Some(rope) = files.get(file) else {
+        return vec![];
+    };
+    let config = &projects.config_for(file).diagnostics;
+    let include_dirs = projects.include_dirs(file);
+    let data = words.for_file(file);
+    let mut ret = document_diagnostics(file, files, data, index, config, &include_dirs);
+    let baselined = file_path(file).and_then(|path| {
+        let project = projects.project_for_path(&path)?;
+        Some((
+            project.baseline.as_ref()?,
+            relative_path(file, &project.root)?,
+        ))
+    });
+    if let Some((baseline, relative)) = baselined {
+        ret = baseline.filter(&relative, rope, ret);
+    }
+    encoding.encode_diagnostics(rope, file_uri(file).as_ref(), &mut ret);
+    ret
+}
+
+/// Point diagnostics about where a word is used, before its definition or after a marker
+/// forgot it, to its definitions in `file`
+fn relate_definitions(
+    file: &str,
+    rope: &Rope,
+    index: &DefinitionIndex,
+    diagnostics: &mut [Diagnostic],
+) {
+    let Some(uri) = file_uri(file) else {
+        return;
+    };
+    for diagnostic in diagnostics {
+        let about_definition = matches!(
+            rule(diagnostic),
+            Some(USE_BEFORE_DEFINITION_CODE | USE_AFTER_FORGET_CODE)
+        );
+        let Some(word) = rope
+            .slice_range(diagnostic.range)
+            .map(String::from)
+            .filter(|_| about_definition)
+        else {
+            continue;
+        };
+        let related: Vec<DiagnosticRelatedInformation> = index
+            .definitions(&word)
+            .into_iter()
+            .filter(|(defined_in, _)| *defined_in == file)
+            .map(|(_, definition)| DiagnosticRelatedInformation {
+                location: Location::new(uri.clone(), definition.selection_range),
+                message: format!("`{}` is defined here", definition.name),
+            })
+            .collect();
+        if !related.is_empty() {
+            diagnostic.related_information = Some(related);
+        }
+    }
+}
+
+/// Link to the section of [RULES_DOCS] on the rule `code`
+pub fn code_description(code: &str) -> Option<CodeDescription> {
+    let href = Url::parse(&format!("{}#{}", RULES_DOCS, code)).ok()?;
+    Some(CodeDescription { href })
+}
+
+/// The rule of one of our diagnostics, its code
+fn rule(diagnostic: &Diagnostic) -> Option<&str> {
+    match diagnostic.code.as_ref()? {
+        NumberOrString::String(code) => Some(code),
+        NumberOrString::Number(_) => None,
+    }
+}
+
+/// Includes in `key` closing a cycle of includes, with the files of the cycle
+pub fn include_cycles(
+    key: &str,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+) -> Vec<Diagnostic> {
+    let Some(rope) = files.get(key) else {
+        return vec![];
+    };
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let targets = include_targets(&tokens);
+    // only a file including others can close a cycle, spare searching the graph otherwise
+    if targets.is_empty() {
+        return vec![];
+    }
+    let dir = file_path(key).and_then(|path| path.parent().map(|dir| dir.to_path_buf()));
+    let mut ret = vec![];
+    for cycle in index.include_graph().cycles() {
+        if cycle.file != key {
+            continue;
+        }
+        let Some(target) = targets
+            .iter()
+            .find(|target| target.to_position_end(rope) == cycle.at)
+        else {
+            continue;
+        };
+        let chain: Vec<String> = cycle
+            .chain
+            .iter()
+            .map(|file| display_path(file, dir.as_deref()))
+            .collect();
+        ret.push(Diagnostic {
+            range: Range {
+                start: target.to_position_start(rope),
+                end: cycle.at,
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(INCLUDE_CYCLE_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message: format!("Include cycle: {}", chain.join(" → ")),
+            ..Default::default()
+        });
+    }
+    ret
+}
+
+/// Includes in `key` of files that are neither loaded nor on disk, next to `key` or in one
+/// of `include_dirs`
+pub fn missing_includes(
+    key: &str,
+    files: &HashMap<String, Rope>,
+    include_dirs: &[PathBuf],
+) -> Vec<Diagnostic> {
+    let (Some(rope), Some(dir)) = (
+        files.get(key),
+        file_path(key).and_then(|path| path.parent().map(Path::to_path_buf)),
+    ) else {
+        return vec![];
+    };
+    let loaded: HashSet<PathBuf> = files.keys().filter_map(|file| file_path(file)).collect();
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let mut ret = vec![];
+    for target in include_targets(&tokens) {
+        let mut searched: Vec<PathBuf> = vec![];
+        for candidate in std::iter::once(&dir)
+            .chain(include_dirs)
+            .map(|dir| normalize(&dir.join(target.value)))
+        {
+            if !searched.contains(&candidate) {
+                searched.push(candidate);
+            }
+        }
+        if searched
+            .iter()
+            .any(|path| loaded.contains(path) || path.is_file())
+        {
+            continue;
+        }
+        let locations: Vec<String> = searched
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        ret.push(Diagnostic {
+            range: data_range(&target, rope),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(MISSING_INCLUDE_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message: format!(
+                "Cannot find `{}`, searched {}",
+                target.value,
+                locations.join(", ")
+            ),
+            data: Some(serde_json::json!({ "path": searched[0] })),
+            ..Default::default()
+        });
+    }
+    ret
+}
+
+/// Trailing whitespace and a missing final line break, both of which formatting changes
+pub fn whitespace_lints(rope: &Rope) -> Vec<Diagnostic> {
+    let mut ret: Vec<Diagnostic> = trailing_whitespace(rope)
+        .into_iter()
+        .map(|range| Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String(TRAILING_WHITESPACE_CODE.to_string())),
+            source: Some("forth-lsp".to_string()),
+            message: "Trailing whitespace".to_string(),
+            ..Default::default()
+        })
+        .collect();
+    let len = rope.len_chars();
+    if len > 0 && rope.line_len(rope.len_lines() - 1) != Some(0) {
+        let end = rope.whole_range().end;
+        ret.push(Diagnostic {
+            range: Range {
+                start: rope.position_of(len - 1).unwrap_or(end),
+                end,
+            },
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String(
+                MISSING_FINAL_NEWLINE_CODE.to_string(),
+            )),
+            source: Some("forth-lsp".to_string()),
+            message: "No line break at the end of the file".to_string(),
+            ..Default::default()
+        });
+    }
+    ret
+}
+
+/// Ranges of the spaces and tabs at the end of every line
+pub fn trailing_whitespace(rope: &Rope) -> Vec<Range> {
+    let mut ret = vec![];
+    for (ix, line) in rope.lines().enumerate() {
+        let len = rope.line_len(ix).unwrap_or_default();
+        let trimmed = line
+            .chars()
+            .take(len)
+            .collect::<String>()
+            .trim_end_matches([' ', '\t'])
+            .chars()
+            .count();
+        if trimmed < len {
+            ret.push(Range::new(
+                Position::new(ix as u32, trimmed as u32),
+                Position::new(ix as u32, len as u32),
+            ));
+        }
+    }
+    ret
+}
+
+/// The edit fixing a `whitespace_lints` diagnostic
+pub fn whitespace_fix(diagnostic: &Diagnostic) -> Option<TextEdit> {
+    let NumberOrString::String(code) = diagnostic.code.as_ref()? else {
+        return None;
+    };
+    match code.as_str() {
+        TRAILING_WHITESPACE_CODE => Some(TextEdit {
+            range: diagnostic.range,
+            new_text: String::new(),
+        }),
+        MISSING_FINAL_NEWLINE_CODE => Some(TextEdit {
+            range: Range::new(diagnostic.range.end, diagnostic.range.end),
+            new_text: "\n".to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// The edit removing the `)` of an unmatched parenthesis diagnostic, with char positions,
+/// along with the space before it
+pub fn unmatched_paren_fix(rope: &Rope, diagnostic: &Diagnostic) -> Option<TextEdit> {
+    let code = NumberOrString::String(UNMATCHED_PAREN_CODE.to_string());
+    if diagnostic.code.as_ref() != Some(&code) {
+        return None;
+    }
+    let paren = rope.char_range(diagnostic.range)?;
+    let start = if paren.start > 0 && rope.get_char(paren.start - 1) == Some(' ') {
+        paren.start - 1
+    } else {
+        paren.start
+    };
+    Some(TextEdit {
+        range: Range::new(rope.position_of(start)?, diagnostic.range.end),
+        new_text: String::new(),
+    })
+}
+
+/// Compile-only words used while interpreting, and interpreting parsers inside definitions.
+/// Code after `:NONAME` or `]` is compiled until `;` or `[`, `[ ... ]` inside a definition
+/// is interpreted
+fn state_misuse<'a>(tokens: &[Token<'a>]) -> Vec<(Data<'a>, DiagnosticSeverity, String)> {
+    let mut ret = vec![];
+    let mut compiling = false;
+    let mut bracketed = false;
+    for context in ContextTokens::new(tokens.iter().cloned()) {
+        if context.in_string || context.in_comment || context.parsed {
+            continue;
+        }
+        let (data, word) = match &context.token {
+            Token::Word(data) => (*data, data.value.to_uppercase()),
+            Token::Semicolon(data) => (*data, ";".to_string()),
+            _ => continue,
+        };
+        if context.in_definition || compiling {
+            match word.as_str() {
+                ";" => {
+                    compiling = false;
+                    bracketed = false;
+                }
+                "[" if context.in_definition => bracketed = true,
+                "[" => compiling = false,
+                "]" => bracketed = false,
+                _ => {}
+            }
+            if bracketed {
+                continue;
+            }
+            if let Some((_, compiled)) = INTERPRETING_PARSERS.iter().find(|(w, _)| *w == word) {
+                ret.push((
+                    data,
+                    DiagnosticSeverity::WARNING,
+                    format!(
+                        "`{}` parses the next word when the definition runs, use `{}` to compile it",
+                        data.value, compiled
+                    ),
+                ));
+            }
+            continue;
+        }
+        match word.as_str() {
+            ":NONAME" | "]" => compiling = true,
+            ";" => ret.push((
+                data,
+                DiagnosticSeverity::ERROR,
+                "Interpretation semantics for `;` are undefined".to_string(),
+            )),
+            _ if COMPILE_ONLY_WORDS.contains(&word.as_str()) => ret.push((
+                data,
+                DiagnosticSeverity::ERROR,
+                format!(
+                    "Interpretation semantics for `{}` are undefined",
+                    data.value
+                ),
+            )),
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// String literals without their closing delimiter on the same line, and compiled or
+/// counted strings longer than a counted string can portably hold. The range covers the
+/// opening word and the text
+fn string_literals<'a>(
+    tokens: &[Token<'a>],
+    rope: &Rope,
+) -> Vec<(Data<'a>, DiagnosticSeverity, String)> {
+    let mut ret = vec![];
+    for context in ContextTokens::new(tokens.iter().cloned()) {
+        if !context.is_string_start() {
+            continue;
+        }
+        let opener = *context.token.get_data();
+        let word = opener.value.to_uppercase();
+        let Some((_, delimiter)) = STRING_WORDS.iter().find(|(start, _)| *start == word) else {
+            continue;
+        };
+        // the text starts after the single space delimiting the opening word, a line break
+        // right after it leaves the string empty and unterminated on this line
+        let start = if rope.get_char(opener.end) == Some(' ') {
+            opener.end + 1
+        } else {
+            opener.end
+        };
+        let mut end = start;
+        let mut closed = false;
+        let mut escaped = false;
+        while let Some(chr) = rope.get_char(end) {
+            if chr == '\n' || chr == '\r' {
+                break;
+            }
+            end += 1;
+            if escaped {
+                escaped = false;
+            } else if chr == '\\' && word == "S\\\"" {
+                escaped = true;
+            } else if chr == *delimiter {
+                closed = true;
+                break;
+            }
+        }
+        let length = if closed { end - start - 1 } else { end - start };
+        let data = Data::new(opener.start, end, "");
+        if !closed {
+            ret.push((
+                data,
+                DiagnosticSeverity::ERROR,
+                format!("Missing closing `{}` for `{}`", delimiter, opener.value),
+            ));
+        } else if length > MAX_COUNTED_STRING && (context.in_definition || word == "C\"") {
+            ret.push((
+                data,
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "String of {} characters, counted strings only portably hold {}",
+                    length, MAX_COUNTED_STRING
+                ),
+            ));
+        }
+    }
+    ret
+}
+
+/// Starts of the words after `CHAR` and `[CHAR]`, which are characters and not words
+fn character_literals(tokens: &[Token]) -> HashSet<usize> {
+    tokens
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [Token::Word(char), next]
+                if char.value.eq_ignore_ascii_case("CHAR")
+                    || char.value.eq_ignore_ascii_case("[CHAR]") =>
+            {
+                Some(next.get_data().start)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Names defined in `tokens` by their upper cased spelling, with their first definition
+fn local_definitions<'a>(tokens: &[Token<'a>]) -> HashMap<String, Data<'a>> {
+    let mut ret: HashMap<String, Data<'a>> = HashMap::new();
+    for name in defined_names(tokens) {
+        ret.entry(name.value.to_uppercase()).or_insert(name);
+    }
+    ret
+}
+
+/// Names defined in `tokens` by `:`, one of the `DEFINING_KINDS`, a synonym or a structure,
+/// in source order
+pub fn defined_names<'a>(tokens: &[Token<'a>]) -> Vec<Data<'a>> {
+    let mut names: Vec<Data> = synonyms(tokens)
+        .iter()
+        .map(|synonym| synonym.name)
+        .collect();
+    for structure in structures(tokens) {
+        names.push(structure.name);
+        names.extend(structure.fields.iter().map(|field| field.name));
+    }
+    let code: Vec<Token> = ContextTokens::new(tokens.iter().cloned())
+        .filter(|context| !context.in_string && !context.in_comment && !context.parsed)
+        .map(|context| context.token)
+        .collect();
+    for pair in code.windows(2) {
+        let defines = match &pair[0] {
+            Token::Colon(_) => true,
+            Token::Word(defining) => is_defining_word(defining.value),
+            _ => false,
+        };
+        if defines {
+            names.extend(definition_name(&pair[1]));
+        }
+    }
+    names.sort_by_key(|name| name.start);
+    names
+}
+
+/// Words used above their first definition in the same file, unless they are builtins the
+/// definition replaces. Empty for the lenient `order`
+pub fn use_before_definition(rope: &Rope, data: &Words, order: DefinitionOrder) -> Vec<Diagnostic> {
+    if order == DefinitionOrder::Lenient {
+        return vec![];
+    }
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let definitions = local_definitions(&tokens);
+    let mut ret = vec![];
+    for word in words_outside_strings(&tokens) {
+        let name = word.value.to_uppercase();
+        let Some(start) = definitions.get(&name).map(|definition| definition.start) else {
+            continue;
+        };
+        if word.start >= start
+            || data
+                .words
+                .iter()
+                .any(|builtin| builtin.token.eq_ignore_ascii_case(&name))
+        {
+            continue;
+        }
+        let line = Data::new(start, start, "").to_position_start(rope).line + 1;
+        ret.push(Diagnostic {
+            range: data_range(&word, rope),
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            code: Some(NumberOrString::String(
+                USE_BEFORE_DEFINITION_CODE.to_string(),
+            )),
+            source: Some("forth-lsp".to_string()),
+            message: format!(
+                "`{}` is used before its definition on line {}",
+                word.value, line
+            ),
+            ..Default::default()
+        });
+    }
+    ret
+}
+
+/// `Word` tokens that are not part of a string literal like `." hello"`
+pub fn words_outside_strings<'a>(tokens: &[Token<'a>]) -> Vec<Data<'a>> {
+    ContextTokens::new(tokens.iter().cloned())
+        .filter(|context| !context.in_string && !context.is_string_start())
+        .filter_map(|context| match context.token {
+            Token::Word(data) => Some(data),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Number literals the lexer hands out as words, like `-1`, `#10` or `1.`
+pub fn is_number(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let (digits, radix) = match value.chars().next() {
+        Some('#') => (&value[1..], 10),
+        Some('$') => (&value[1..], 16),
+        Some('%') => (&value[1..], 2),
+        Some('&') => (&value[1..], 8),
+        _ => (value, 10),
+    };
+    let digits = digits.strip_suffix('.').unwrap_or(digits);
+    (!digits.is_empty() && digits.chars().all(|chr| chr.is_digit(radix))) || is_float(value)
+}
+
+/// Floating point literals like `1e`, `1.5E-3` or `+2.e0`, which always have an exponent
+fn is_float(value: &str) -> bool {
+    let value = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let Some((significand, exponent)) = value.split_once(['e', 'E']) else {
+        return false;
+    };
+    let (integer, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+    let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+    !integer.is_empty()
+        && integer.chars().all(|chr| chr.is_ascii_digit())
+        && fraction.chars().all(|chr| chr.is_ascii_digit())
+        && exponent.chars().all(|chr| chr.is_ascii_digit())
+}
+
+/// Send diagnostics for `uri` computed from its text at `version`. Messages are handled one
+/// at a time, so that is still the latest version, clients drop them once it no longer is
+pub fn publish_diagnostics(
+    connection: &Connection,
+    uri: Url,
+    diagnostics: Vec<Diagnostic>,
+    version: Option<i32>,
+) -> Result<()> {
+    let params = PublishDiagnosticsParams {
+        uri,
+        diagnostics,
+        version,
+    };
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            params,
+        )))
+        .map_err(|err| Error::SendError(err.to_string()))
+}
+
+/// Recompute and send diagnostics for every open document, e.g. after the config changed
+pub fn publish_all_diagnostics(
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    versions: &HashMap<String, i32>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    let open: Vec<&String> = versions.keys().collect();
+    publish_batch(
+        connection, projects, words, files, index, versions, &open, encoding,
+    )
+}
+
+/// Recompute and send diagnostics for the open documents other than `file` that use one of
+/// the `changed` words, whose definitions `file` just added or removed
+#[allow(clippy::too_many_arguments)]
+pub fn publish_dependent_diagnostics(
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    versions: &HashMap<String, i32>,
+    file: &str,
+    changed: &HashSet<String>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let dependents: Vec<&String> = index
+        .files()
+        .filter(|(dependent, file_index)| {
+            *dependent != file
+                && versions.contains_key(*dependent)
+                && file_index
+                    .references
+                    .iter()
+                    .any(|reference| changed.contains(&reference.name.to_uppercase()))
+        })
+        .map(|(dependent, _)| dependent)
+        .collect();
+    publish_batch(
+        connection,
+        projects,
+        words,
+        files,
+        index,
+        versions,
+        &dependents,
+        encoding,
+    )
+}
+
+/// Diagnostics of the open documents `open`, computed in parallel and sent once all are done
+#[allow(clippy::too_many_arguments)]
+fn publish_batch(
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    versions: &HashMap<String, i32>,
+    open: &[&String],
+    encoding: PositionEncoding,
+) -> Result<()> {
+    let batch: Vec<(Url, Vec<Diagnostic>, i32)> = open
+        .par_iter()
+        .filter_map(|file| {
+            let (true, Some(uri), Some(version)) = (
+                files.contains_key(*file),
+                file_uri(file),
+                versions.get(*file),
+            ) else {
+                return None;
+            };
+            let diagnostics = project_diagnostics(file, files, words, index, projects, encoding);
+            Some((uri, diagnostics, *version))
+        })
+        .collect();
+    for (uri, diagnostics, version) in batch {
+        publish_diagnostics(connection, uri, diagnostics, Some(version))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Severity;
+
+    fn messages(source: &str) -> Vec<String> {
+        let rope = Rope::from_str(source);
+        get_diagnostics(&rope, &Words::default(), &DefinitionIndex::default())
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn flags_undefined_words() {
+        assert_eq!(
+            vec!["Undefined word `frobnitz`"],
+            messages("1 dup frobnitz")
+        );
+    }
+
+    #[test]
+    fn unmatched_parens_are_removed_with_the_space_before() {
+        let rope = Rope::from_str("1 2 ) + .\n( a ) )");
+        let diagnostics = get_diagnostics(&rope, &Words::default(), &DefinitionIndex::default());
+        assert_eq!(
+            vec![
+                "Unmatched closing parenthesis",
+                "Unmatched closing parenthesis"
+            ],
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.as_str())
+                .collect::<Vec<_>>()
+        );
+        let edits: Vec<Range> = diagnostics
+            .iter()
+            .filter_map(|diagnostic| unmatched_paren_fix(&rope, diagnostic))
+            .map(|edit| edit.range)
+            .collect();
+        assert_eq!(
+            vec![
+                Range::new(Position::new(0, 3), Position::new(0, 5)),
+                Range::new(Position::new(1, 5), Position::new(1, 7)),
+            ],
+            edits
+        );
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &Rope::from_str(": ) ;"));
+        assert!(get_diagnostics(&Rope::from_str(")"), &Words::default(), &index).is_empty());
+    }
+
+    #[test]
+    fn common_extensions_are_defined_unless_turned_off() {
+        let rope = Rope::from_str("1 2 3 -rot cell bounds");
+        let index = DefinitionIndex::default();
+        assert!(get_diagnostics(&rope, &Words::with_custom(&[], true), &index).is_empty());
+        assert_eq!(
+            3,
+            get_diagnostics(&rope, &Words::with_custom(&[], false), &index).len()
+        );
+    }
+
+    #[test]
+    fn suggests_close_words() {
+        assert_eq!(
+            vec![
+                "Undefined word `dpu`, did you mean `DUP`?",
+                "Undefined word `countr`, did you mean `counter`?"
+            ],
+            messages("variable counter\n1 dpu countr")
+        );
+        let rope = Rope::from_str("dpu");
+        let diagnostic = &get_diagnostics(&rope, &Words::default(), &DefinitionIndex::default())[0];
+        assert_eq!(
+            Some(NumberOrString::String(UNDEFINED_WORD_CODE.to_string())),
+            diagnostic.code
+        );
+        assert_eq!(
+            Some(serde_json::json!({ "suggestion": "DUP" })),
+            diagnostic.data
+        );
+    }
+
+    #[test]
+    fn local_and_defining_words_are_defined() {
+        assert!(
+            messages(": add1 1 + ;\nvariable counter\n5 constant five\nadd1 counter five")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn extension_word_sets_are_defined() {
+        assert_eq!(
+            Vec::<String>::new(),
+            messages(
+                "fvariable x 1e x f! x f@ f. 1. 2. d+ d. s\" a\" s\" b\" compare drop\n\
+             s\" f.txt\" r/o open-file 2drop [defined] .s [if] .s [then]"
+            )
+        );
+    }
+
+    #[test]
+    fn float_literals() {
+        assert!(is_number("1e"));
+        assert!(is_number("-1.5E-3"));
+        assert!(is_number("2.e+10"));
+        assert!(!is_number("e5"));
+        assert!(!is_number("1ex"));
+    }
+
+    #[test]
+    fn compile_only_words_outside_definitions() {
+        assert_eq!(
+            vec![
+                "Interpretation semantics for `if` are undefined",
+                "Interpretation semantics for `then` are undefined",
+                "Interpretation semantics for `;` are undefined",
+            ],
+            messages("1 if 2 then ;\n: ok if then ;\n:noname if then ; ] if [ .\" if\"")
+        );
+    }
+
+    #[test]
+    fn words_used_after_their_marker_ran() {
+        assert_eq!(
+            vec!["`sq` was forgotten by running `-app` on line 3"],
+            messages("marker -app\n: sq dup * ;\n-app\n2 sq")
+        );
+    }
+
+    #[test]
+    fn loop_indices_without_their_loops() {
+        assert_eq!(
+            vec![
+                "`j` reads the index of an outer loop, but only one `DO` loop is active here",
+                "`I` is used outside of a `DO` loop",
+            ],
+            messages(": row 3 0 do i j + . loop ;\n: stray I ;")
+        );
+    }
+
+    #[test]
+    fn interpreting_parsers_inside_definitions() {
+        assert_eq!(
+            vec!["`'` parses the next word when the definition runs, use `[']` to compile it"],
+            messages(": x ' dup ['] dup [char] a [ ' dup ] literal ;\n' dup char a")
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert_eq!(
+            vec!["Missing `;` to end `foo`", "Missing `THEN` to close `if`"],
+            messages(": foo dup if")
+        );
+    }
+
+    #[test]
+    fn definition_words_inside_strings() {
+        assert!(messages(": greet .\" use : bogus here\" s\" ; 1\" type ;").is_empty());
+    }
+
+    #[test]
+    fn unterminated_strings() {
+        // the `;` is text of the string, which ends with its line
+        assert_eq!(
+            vec!["Missing `;` to end `hi`", "Missing closing `\"` for `.\"`"],
+            messages(": hi .\" hello ;\n: ok s\\\" a \\\" b\" type ;")
+        );
+        let rope = Rope::from_str("s\" abc\n");
+        let diagnostics = get_diagnostics(&rope, &Words::default(), &DefinitionIndex::default());
+        assert_eq!(Position::new(0, 0), diagnostics[0].range.start);
+        assert_eq!(Position::new(0, 6), diagnostics[0].range.end);
+        let rope = Rope::from_str(": hi s\"\n\" ;");
+        let diagnostics = get_diagnostics(&rope, &Words::default(), &DefinitionIndex::default());
+        assert_eq!(Position::new(0, 5), diagnostics[0].range.start);
+        assert_eq!(Position::new(0, 7), diagnostics[0].range.end);
+    }
+
+    #[test]
+    fn long_compiled_strings() {
+        let long = "x".repeat(256);
+        assert_eq!(
+            vec!["String of 256 characters, counted strings only portably hold 255"],
+            messages(&format!(": hi .\" {}\" ;\ns\" {}\" type", long, long))
+        );
+        assert!(messages(&format!(": hi .\" {}\" ;", &long[1..])).is_empty());
+    }
+
+    #[test]
+    fn index_definitions_are_defined() {
+        let mut index = DefinitionIndex::default();
+        index.update_file("lib.forth", &Rope::from_str(": frob ;"));
+        let rope = Rope::from_str("frob");
+        assert!(get_diagnostics(&rope, &Words::default(), &index).is_empty());
+    }
+
+    #[test]
+    fn skips_strings_comments_and_numbers() {
+        assert!(messages(
+            ".\" hello world\" s\" a b\" .( paren text) \\ comment words\n( more words ) -1 #10 1."
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn include_cycles_list_the_chain() {
+        let files = HashMap::from([
+            (
+                "/p/main.forth".to_string(),
+                Rope::from_str("include lib/a.forth"),
+            ),
+            (
+                "/p/lib/a.forth".to_string(),
+                Rope::from_str("1 drop include ../main.forth"),
+            ),
+        ]);
+        let mut index = DefinitionIndex::default();
+        for (file, rope) in &files {
+            index.update_file(file, rope);
+        }
+        // nobody includes a file first, so loading starts at the first key
+        assert!(include_cycles("/p/lib/a.forth", &files, &index).is_empty());
+        let diagnostics = include_cycles("/p/main.forth", &files, &index);
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            "Include cycle: lib/a.forth → main.forth → lib/a.forth",
+            diagnostics[0].message
+        );
+        assert_eq!(Position::new(0, 8), diagnostics[0].range.start);
+        assert_eq!(Position::new(0, 19), diagnostics[0].range.end);
+    }
+
+    #[test]
+    fn missing_includes_list_where_they_were_searched() {
+        let root = crate::utils::test_dir("missing-includes");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("vendor")).unwrap();
+        std::fs::write(root.join("vendor/found.fs"), "").unwrap();
+        let main = root.join("main.fs").display().to_string();
+        let files = HashMap::from([
+            (
+                main.clone(),
+                Rope::from_str("include loaded.fs\nrequire found.fs\ninclude lost.fs"),
+            ),
+            (root.join("loaded.fs").display().to_string(), Rope::new()),
+        ]);
+        let diagnostics = missing_includes(&main, &files, &[root.join("vendor")]);
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(Some(DiagnosticSeverity::ERROR), diagnostics[0].severity);
+        assert_eq!(
+            format!(
+                "Cannot find `lost.fs`, searched {}, {}",
+                root.join("lost.fs").display(),
+                root.join("vendor/lost.fs").display()
+            ),
+            diagnostics[0].message
+        );
+        assert_eq!(Position::new(2, 8), diagnostics[0].range.start);
+        assert_eq!(
+            Some(serde_json::json!({ "path": root.join("lost.fs") })),
+            diagnostics[0].data
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn whitespace_lints_and_their_fixes() {
+        let diagnostics = whitespace_lints(&Rope::from_str(": sq  \r\n  dup * ;\t\n\n  "));
+        let ranges: Vec<(u32, u32, u32)> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.range.start.line,
+                    diagnostic.range.start.character,
+                    diagnostic.range.end.character,
+                )
+            })
+            .collect();
+        assert_eq!(vec![(0, 4, 6), (1, 9, 10), (3, 0, 2), (3, 1, 2)], ranges);
+        let fix = whitespace_fix(&diagnostics[3]).unwrap();
+        assert_eq!(Position::new(3, 2), fix.range.start);
+        assert_eq!("\n", fix.new_text);
+        assert!(whitespace_fix(&diagnostics[0]).unwrap().new_text.is_empty());
+        assert!(whitespace_lints(&Rope::from_str(": sq dup * ;\n")).is_empty());
+        assert!(whitespace_lints(&Rope::new()).is_empty());
+    }
+
+    #[test]
+    fn parsed_names_are_not_run() {
+        assert!(messages("' if drop char ; emit : x postpone ; ; immediate").is_empty());
+        assert!(messages(": y postpone if ; immediate char a emit").is_empty());
+        assert_eq!(
+            vec!["Undefined word `frobnitz`"],
+            messages(": z ['] frobnitz drop ;")
+        );
+    }
+
+    #[test]
+    fn uses_before_definition_in_strict_order() {
+        let rope = Rope::from_str(": main greet ;\n: greet .\" hi\" ;\n: dup dup ;\ngreet\n");
+        let data = Words::default();
+        assert!(use_before_definition(&rope, &data, DefinitionOrder::Lenient).is_empty());
+        let diagnostics = use_before_definition(&rope, &data, DefinitionOrder::Strict);
+        let found: Vec<(u32, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            vec![(0, "`greet` is used before its definition on line 2")],
+            found
+        );
+        assert_eq!(
+            Some(DiagnosticSeverity::INFORMATION),
+            diagnostics[0].severity
+        );
+    }
+
+    #[test]
+    fn names_that_lex_as_numbers_are_defined() {
+        let tokens = Lexer::new("variable 2dead : 1add ; create 2max").parse();
+        let mut names: Vec<String> = local_definitions(&tokens).into_keys().collect();
+        names.sort();
+        assert_eq!(vec!["1ADD", "2DEAD", "2MAX"], names);
+    }
+
+    #[test]
+    fn severities_and_allow_directives() {
+        let files = HashMap::from([(
+            "/p/a.forth".to_string(),
+            Rope::from_str("frob\n\\ forth-lsp: allow undefined-word\nfrob frob\nfrob\n"),
+        )]);
+        let config = DiagnosticsConfig {
+            severity: [("undefined-word".to_string(), Severity::Error)].into(),
+            ..Default::default()
+        };
+        let diagnostics = document_diagnostics(
+            "/p/a.forth",
+            &files,
+            &Words::default(),
+            &DefinitionIndex::default(),
+            &config,
+            &[],
+        );
+        let found: Vec<(u32, Option<DiagnosticSeverity>)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.severity))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, Some(DiagnosticSeverity::ERROR)),
+                (3, Some(DiagnosticSeverity::ERROR))
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn ignore_directives_and_unused_suppressions() {
+        let files = HashMap::from([(
+            "/p/a.forth".to_string(),
+            Rope::from_str(
+                "\\ forth-lsp: ignore-file[trailing-whitespace]\nfrob \\ forth-lsp: ignore[undefined-word]\n\\ forth-lsp: ignore[undefined-word, state]\nfrob \nfrob\n",
+            ),
+        )]);
+        let diagnostics = document_diagnostics(
+            "/p/a.forth",
+            &files,
+            &Words::default(),
+            &DefinitionIndex::default(),
+            &DiagnosticsConfig::default(),
+            &[],
+        );
+        let found: Vec<(u32, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, rule(diagnostic).unwrap()))
+            .collect();
+        assert_eq!(
+            vec![(4, UNDEFINED_WORD_CODE), (2, UNUSED_SUPPRESSION_CODE)],
+            found
+        );
+        assert_eq!(
+            Some(format!("{}#unused-suppression", RULES_DOCS)),
+            diagnostics[1]
+                .code_description
+                .as_ref()
+                .map(|description| description.href.to_string())
+        );
+    }
+
+    #[test]
+    fn uses_are_related_to_their_definitions() {
+        let rope = Rope::from_str("2 sq\nmarker -app\n: sq dup * ;\n-app\n3 sq\n");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/p/a.forth", &rope);
+        index.update_file("/p/b.forth", &Rope::from_str(": sq ;"));
+        let files = HashMap::from([("/p/a.forth".to_string(), rope)]);
+        let config = DiagnosticsConfig {
+            definition_order: DefinitionOrder::Strict,
+            ..Default::default()
+        };
+        let diagnostics = document_diagnostics(
+            "/p/a.forth",
+            &files,
+            &Words::default(),
+            &index,
+            &config,
+            &[],
+        );
+        let uri = Url::from_file_path("/p/a.forth").unwrap();
+        let defined_here = vec![DiagnosticRelatedInformation {
+            location: Location::new(uri, Range::new(Position::new(2, 2), Position::new(2, 4))),
+            message: "`sq` is defined here".to_string(),
+        }];
+        let related: Vec<(&str, Option<&Vec<DiagnosticRelatedInformation>>)> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    rule(diagnostic).unwrap(),
+                    diagnostic.related_information.as_ref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (USE_AFTER_FORGET_CODE, Some(&defined_here)),
+                (USE_BEFORE_DEFINITION_CODE, Some(&defined_here)),
+            ],
+            related
+        );
+    }
+
+    #[test]
+    fn every_rule_is_documented() {
+        let docs = include_str!("../docs/rules.md");
+        for rule in RULES.iter().chain([&CONFIG_CODE]) {
+            assert!(docs.contains(&format!("\n## {}\n", rule)), "{}", rule);
+        }
+    }
+}
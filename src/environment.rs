@@ -0,0 +1,106 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::words::ENVIRONMENT_QUERIES;
+
+use forth_lexer::{
+    context::{ContextToken, ContextTokens},
+    token::Token,
+};
+
+/// The text of an `S" ..." ENVIRONMENT?` string, from the char after `S" ` up to the
+/// closing `"`
+#[derive(Debug, PartialEq)]
+pub struct EnvironmentQuery {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The query string passed to `ENVIRONMENT?` that `ix` is in, the closing `"` included
+pub fn environment_query_at(tokens: &[Token], ix: usize) -> Option<EnvironmentQuery> {
+    let contexts: Vec<ContextToken> = ContextTokens::new(tokens.iter().cloned()).collect();
+    for (open, context) in contexts.iter().enumerate() {
+        let opener = context.token.get_data();
+        if !context.is_string_start() || !opener.value.eq_ignore_ascii_case("S\"") {
+            continue;
+        }
+        let text: Vec<&ContextToken> = contexts[open + 1..]
+            .iter()
+            .take_while(|context| context.in_string)
+            .collect();
+        let closing = text.last()?.token.get_data();
+        let start = opener.end + 1;
+        let end = closing.end - 1;
+        if ix < start || ix > end {
+            continue;
+        }
+        let queried = contexts
+            .get(open + 1 + text.len())
+            .is_some_and(|next| match &next.token {
+                Token::Word(data) => data.value.eq_ignore_ascii_case("ENVIRONMENT?"),
+                _ => false,
+            });
+        if !closing.value.ends_with('"') || !queried {
+            return None;
+        }
+        let words: Vec<&str> = text
+            .iter()
+            .map(|context| context.token.get_data().value)
+            .collect();
+        let text = words.join(" ");
+        return Some(EnvironmentQuery {
+            text: text[..text.len() - 1].to_string(),
+            start,
+            end,
+        });
+    }
+    None
+}
+
+/// Hover documentation of the standard `ENVIRONMENT?` query `name`
+pub fn environment_query_documentation(name: &str) -> Option<String> {
+    let (name, answer, meaning) = ENVIRONMENT_QUERIES
+        .iter()
+        .find(|(query, _, _)| query.eq_ignore_ascii_case(name))?;
+    Some(format!(
+        "# `{}`   `( -- {} true )`\n\n{}\n\nAn `ENVIRONMENT?` query, the flag is false when the \
+         system does not know it",
+        name, answer, meaning
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use forth_lexer::parser::Lexer;
+
+    fn query_at(source: &str, ix: usize) -> Option<EnvironmentQuery> {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.parse();
+        environment_query_at(&tokens, ix)
+    }
+
+    #[test]
+    fn strings_passed_to_environment_query() {
+        let source = "s\" /HOLD\" environment? s\" hi\" type";
+        assert_eq!(
+            Some(EnvironmentQuery {
+                text: "/HOLD".to_string(),
+                start: 3,
+                end: 8,
+            }),
+            query_at(source, 5)
+        );
+        assert_eq!(None, query_at(source, 1));
+        assert_eq!(None, query_at(source, 26));
+        assert_eq!("", query_at("s\" \" environment?", 3).unwrap().text);
+    }
+
+    #[test]
+    fn documentation_of_standard_queries() {
+        let doc = environment_query_documentation("max-n").unwrap();
+        assert!(doc.starts_with("# `MAX-N`   `( -- n true )`"));
+        assert_eq!(None, environment_query_documentation("/FOO"));
+    }
+}
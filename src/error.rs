@@ -1,14 +1,35 @@
+use lsp_server::{ErrorCode, RequestId, ResponseError};
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[allow(dead_code)]
-    #[error("Generic {0}")]
+    #[error("{0}")]
     Generic(String),
-    #[error("SendError {0}")]
+    #[error("Could not send to the client: {0}")]
     SendError(String),
-    #[error("OutOfBounds at ix {0}")]
-    OutOfBounds(usize),
-    #[error("NoSuchFile {0}")]
+    #[error("Position {}:{} is out of bounds", .0.line, .0.character)]
+    OutOfBounds(lsp_types::Position),
+    #[error("No such file {0}")]
     NoSuchFile(String),
+    #[error("{}", .path.display())]
+    Path {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid params for {method}")]
+    InvalidParams {
+        method: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// What went wrong handling a request, with the request it went wrong for
+    #[error("{method} ({id}) failed")]
+    Request {
+        method: String,
+        id: RequestId,
+        #[source]
+        source: Box<Error>,
+    },
 
     #[error(transparent)]
     IO(#[from] std::io::Error),
@@ -26,3 +47,68 @@ pub enum Error {
     #[error(transparent)]
     SerdeJsonError(#[from] serde_json::Error),
 }
+
+impl Error {
+    /// The JSON-RPC error code a client gets for this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Request { source, .. } => source.code(),
+            Error::InvalidParams { .. } | Error::OutOfBounds(_) => ErrorCode::InvalidParams,
+            Error::ExtractRequestError(_) | Error::ExtractNotificationError(_) => {
+                ErrorCode::MethodNotFound
+            }
+            Error::NoSuchFile(_) | Error::Path { .. } | Error::IO(_) | Error::Generic(_) => {
+                ErrorCode::RequestFailed
+            }
+            Error::SendError(_) | Error::ProtocolError(_) | Error::SerdeJsonError(_) => {
+                ErrorCode::InternalError
+            }
+        }
+    }
+
+    /// The message with the errors that caused it, like `a: b: c`
+    pub fn report(&self) -> String {
+        let mut ret = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            ret.push_str(&format!(": {}", err));
+            source = err.source();
+        }
+        ret
+    }
+}
+
+impl From<Error> for ResponseError {
+    fn from(err: Error) -> ResponseError {
+        ResponseError {
+            code: err.code() as i32,
+            message: err.report(),
+            data: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_report_their_cause() {
+        let source = serde_json::from_str::<u32>("x").unwrap_err();
+        let err = Error::Request {
+            method: "textDocument/hover".to_string(),
+            id: RequestId::from(3),
+            source: Box::new(Error::InvalidParams {
+                method: "textDocument/hover".to_string(),
+                source,
+            }),
+        };
+        let response = ResponseError::from(err);
+        assert_eq!(ErrorCode::InvalidParams as i32, response.code);
+        assert_eq!(
+            "textDocument/hover (3) failed: Invalid params for textDocument/hover: \
+             expected value at line 1 column 1",
+            response.message
+        );
+    }
+}
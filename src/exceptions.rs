@@ -0,0 +1,172 @@
+use crate::ast::definition_name;
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use forth_lexer::token::{Data, Token};
+
+/// Meanings of the THROW codes reserved by Forth 2012, table 9.1
+pub const THROW_CODES: [&str; 58] = [
+    "ABORT",
+    "ABORT\"",
+    "stack overflow",
+    "stack underflow",
+    "return stack overflow",
+    "return stack underflow",
+    "do-loops nested too deeply during execution",
+    "dictionary overflow",
+    "invalid memory address",
+    "division by zero",
+    "result out of range",
+    "argument type mismatch",
+    "undefined word",
+    "interpreting a compile-only word",
+    "invalid FORGET",
+    "attempt to use zero-length string as a name",
+    "pictured numeric output string overflow",
+    "parsed string overflow",
+    "definition name too long",
+    "write to a read-only location",
+    "unsupported operation",
+    "control structure mismatch",
+    "address alignment exception",
+    "invalid numeric argument",
+    "return stack imbalance",
+    "loop parameters unavailable",
+    "invalid recursion",
+    "user interrupt",
+    "compiler nesting",
+    "obsolescent feature",
+    ">BODY used on non-CREATEd definition",
+    "invalid name argument (e.g., TO name)",
+    "block read exception",
+    "block write exception",
+    "invalid block number",
+    "invalid file position",
+    "file I/O exception",
+    "non-existent file",
+    "unexpected end of file",
+    "invalid BASE for floating point conversion",
+    "loss of precision",
+    "floating-point divide by zero",
+    "floating-point result out of range",
+    "floating-point stack overflow",
+    "floating-point stack underflow",
+    "floating-point invalid argument",
+    "compilation word list deleted",
+    "invalid POSTPONE",
+    "search-order overflow",
+    "search-order underflow",
+    "compilation word list changed",
+    "control-flow stack overflow",
+    "exception stack overflow",
+    "floating-point underflow",
+    "floating-point unidentified fault",
+    "QUIT",
+    "exception in sending or receiving a character",
+    "[IF], [ELSE], or [THEN] exception",
+];
+
+/// What the standard says THROW `code` means, codes -1 through -58 are reserved
+pub fn throw_code_meaning(code: i64) -> Option<&'static str> {
+    let ix = usize::try_from(-code).ok()?.checked_sub(1)?;
+    THROW_CODES.get(ix).copied()
+}
+
+/// The value of an integer literal like `-9`, `#10`, `$FF`, `%101` or `&17`
+pub fn parse_number(value: &str) -> Option<i64> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let (digits, radix) = match value.chars().next()? {
+        '#' => (&value[1..], 10),
+        '$' => (&value[1..], 16),
+        '%' => (&value[1..], 2),
+        '&' => (&value[1..], 8),
+        _ => (value, 10),
+    };
+    // `from_str_radix` takes a sign of its own, the only one allowed is before the prefix
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let number = i64::from_str_radix(digits, radix).ok()?;
+    if negative {
+        number.checked_neg()
+    } else {
+        Some(number)
+    }
+}
+
+/// `n CONSTANT name` with an integer literal `n`, as name and literal
+pub fn literal_constants<'a>(tokens: &[Token<'a>]) -> Vec<(Data<'a>, Data<'a>)> {
+    tokens
+        .windows(3)
+        .filter_map(|window| match window {
+            [Token::Number(literal) | Token::Word(literal), Token::Word(constant), name]
+                if constant.value.eq_ignore_ascii_case("CONSTANT")
+                    && parse_number(literal.value).is_some() =>
+            {
+                Some((definition_name(name)?, *literal))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The token at `ix` when it is directly followed by `THROW`
+pub fn thrown_at<'a>(tokens: &[Token<'a>], ix: usize) -> Option<Data<'a>> {
+    tokens.windows(2).find_map(|pair| match pair {
+        [Token::Number(data) | Token::Word(data), Token::Word(throw)]
+            if data.start <= ix && ix <= data.end && throw.value.eq_ignore_ascii_case("THROW") =>
+        {
+            Some(*data)
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use forth_lexer::parser::Lexer;
+
+    #[test]
+    fn standard_meanings() {
+        assert_eq!(Some("ABORT"), throw_code_meaning(-1));
+        assert_eq!(Some("division by zero"), throw_code_meaning(-10));
+        assert_eq!(
+            Some("[IF], [ELSE], or [THEN] exception"),
+            throw_code_meaning(-58)
+        );
+        assert_eq!(None, throw_code_meaning(-59));
+        assert_eq!(None, throw_code_meaning(0));
+        assert_eq!(None, throw_code_meaning(3));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Some(-9), parse_number("-9"));
+        assert_eq!(Some(255), parse_number("$FF"));
+        assert_eq!(Some(-5), parse_number("-%101"));
+        assert_eq!(None, parse_number("dup"));
+        assert_eq!(None, parse_number("-"));
+        assert_eq!(None, parse_number("--9223372036854775808"));
+        assert_eq!(None, parse_number("$-FF"));
+        assert_eq!(None, parse_number("#+9"));
+        assert_eq!(Some(-i64::MAX), parse_number("-9223372036854775807"));
+    }
+
+    #[test]
+    fn constants_and_throws() {
+        let mut lexer = Lexer::new("-100 constant err-full\n: check err-full throw -9 throw ;");
+        let tokens = lexer.parse();
+        let constants = literal_constants(&tokens);
+        assert_eq!(1, constants.len());
+        assert_eq!("err-full", constants[0].0.value);
+        assert_eq!("-100", constants[0].1.value);
+        assert_eq!("err-full", thrown_at(&tokens, 32).unwrap().value);
+        assert_eq!("-9", thrown_at(&tokens, 47).unwrap().value);
+        assert!(thrown_at(&tokens, 25).is_none());
+    }
+}
@@ -0,0 +1,370 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::{Program, CLOSERS, MIDDLES, OPENERS},
+    config::{FormatConfig, LineEnding, WordCase},
+    definition_index::is_defining_word,
+    diagnostics::words_outside_strings,
+    words::Words,
+};
+
+use std::collections::{HashMap, HashSet};
+
+use forth_lexer::{
+    context::ContextTokens,
+    parser::Lexer,
+    token::{Data, Token},
+};
+
+/// Format a whole file: re-case builtin words, indent definition bodies by nesting,
+/// strip trailing whitespace and blank line runs, and end with a single newline. Lines end
+/// as `config.line_ending` says
+pub fn format_source(source: &str, config: &FormatConfig, data: &Words) -> String {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.parse_with_trivia();
+    let code: Vec<Token> = tokens
+        .iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_) | Token::Newline(_)))
+        .cloned()
+        .collect();
+    let recased: HashMap<usize, String> = recased_words(&code, config, data)
+        .into_iter()
+        .map(|(word, recased)| (word.start, recased))
+        .collect();
+    let program = Program::parse(&code);
+
+    // a multi line comment keeps the lines it continues on the line it starts
+    let lines: Vec<&[Token]> = tokens
+        .split(|token| matches!(token, Token::Newline(_)))
+        .collect();
+    let line_of: HashMap<usize, usize> = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(line, tokens)| {
+            tokens
+                .iter()
+                .map(move |token| (token.get_data().start, line))
+        })
+        .collect();
+    let mut levels: HashMap<usize, usize> = HashMap::new();
+    for definition in program.definitions() {
+        let first = line_of[&definition.colon.start];
+        for (token, level) in definition.levels() {
+            let line = line_of[&token.get_data().start];
+            if line != first {
+                levels.entry(line).or_insert(level);
+            }
+        }
+    }
+
+    let in_string: HashSet<usize> = ContextTokens::new(code.iter().cloned())
+        .filter(|context| context.in_string)
+        .map(|context| context.token.get_data().start)
+        .collect();
+    let in_definition: HashSet<usize> = program
+        .definitions()
+        .flat_map(|definition| definition.levels())
+        .map(|(token, _)| line_of[&token.get_data().start])
+        .collect();
+
+    let mut ret = String::new();
+    let mut previous_blank = true;
+    for (ix, tokens) in lines.iter().enumerate() {
+        let level = levels.get(&ix);
+        let indent = " ".repeat(level.unwrap_or(&0) * config.indent);
+        let mut pieces: Vec<Piece> = vec![];
+        let mut space = String::new();
+        for token in tokens.iter() {
+            let text = match token {
+                Token::Whitespace(whitespace) => {
+                    space.push_str(whitespace.value);
+                    continue;
+                }
+                Token::Comment(comment) | Token::StackComment(comment) => {
+                    let lines: Vec<&str> = comment.value.split('\n').map(str::trim_end).collect();
+                    lines.join("\n")
+                }
+                Token::Word(word) => recased
+                    .get(&word.start)
+                    .map_or(word.value, String::as_str)
+                    .to_string(),
+                token => token.get_data().value.to_string(),
+            };
+            let breakable = !in_string.contains(&token.get_data().start)
+                && !CONDITIONED.contains(&text.to_uppercase().as_str());
+            pieces.push(Piece {
+                space: std::mem::take(&mut space),
+                text,
+                breakable,
+            });
+        }
+        if let (Some(first), Some(_)) = (pieces.first_mut(), level) {
+            first.space.clear();
+        }
+        let line = match config.max_line_width {
+            Some(width) if in_definition.contains(&ix) => {
+                let continuation = " ".repeat((level.unwrap_or(&0) + 1) * config.indent);
+                wrap(&pieces, &indent, &continuation, width)
+            }
+            _ => unwrapped(&pieces, &indent),
+        };
+        let line = line.trim_end();
+        let blank = line.is_empty();
+        if blank && previous_blank {
+            continue;
+        }
+        ret.push_str(line);
+        ret.push('\n');
+        previous_blank = blank;
+    }
+    while ret.ends_with("\n\n") {
+        ret.pop();
+    }
+    if line_break(source, config.line_ending) == "\r\n" {
+        ret = ret.replace('\n', "\r\n");
+    }
+    ret
+}
+
+/// The line break for `ending`, the one most lines of `source` end with for `auto`
+fn line_break(source: &str, ending: LineEnding) -> &'static str {
+    match ending {
+        LineEnding::Lf => "\n",
+        LineEnding::Crlf => "\r\n",
+        LineEnding::Auto => {
+            let crlf = source.matches("\r\n").count();
+            let lf = source.matches('\n').count() - crlf;
+            if crlf > lf {
+                "\r\n"
+            } else {
+                "\n"
+            }
+        }
+    }
+}
+
+/// Control words taking their flag or bounds from the words right before them, which
+/// are never wrapped onto a line of their own
+const CONDITIONED: [&str; 6] = ["IF", "WHILE", "UNTIL", "DO", "?DO", "OF"];
+
+/// A token as it is written on its line
+struct Piece {
+    /// Whitespace between the previous token and this one
+    space: String,
+    text: String,
+    /// Whether a line may be wrapped before this token
+    breakable: bool,
+}
+
+fn unwrapped(pieces: &[Piece], indent: &str) -> String {
+    let mut ret = indent.to_string();
+    for piece in pieces {
+        ret.push_str(&piece.space);
+        ret.push_str(&piece.text);
+    }
+    ret
+}
+
+/// `pieces` with line breaks before the tokens that would go past `width`, keeping runs
+/// that may not be broken together, continuation lines start with `continuation`
+fn wrap(pieces: &[Piece], indent: &str, continuation: &str, width: usize) -> String {
+    let line = unwrapped(pieces, indent);
+    if line.trim_end().chars().count() <= width || line.contains('\n') {
+        return line;
+    }
+    let mut runs: Vec<&[Piece]> = vec![];
+    let mut start = 0;
+    for ix in 1..=pieces.len() {
+        if ix == pieces.len() || pieces[ix].breakable {
+            runs.push(&pieces[start..ix]);
+            start = ix;
+        }
+    }
+    let mut ret = indent.to_string();
+    let mut length = indent.chars().count();
+    for (ix, run) in runs.into_iter().enumerate() {
+        let text = unwrapped(run, "");
+        let first = text.trim_start();
+        let fits = length + text.chars().count() <= width;
+        if ix == 0 || fits || length <= continuation.chars().count() {
+            ret.push_str(&text);
+            length += text.chars().count();
+        } else {
+            ret.push('\n');
+            ret.push_str(continuation);
+            ret.push_str(first);
+            length = continuation.chars().count() + first.chars().count();
+        }
+    }
+    ret
+}
+
+/// The case `config` wants for the builtin `word`
+pub fn case_of(word: &str, config: &FormatConfig) -> WordCase {
+    let word = word.to_uppercase();
+    let word = word.as_str();
+    let specific = if OPENERS.contains(&word) || MIDDLES.contains(&word) || CLOSERS.contains(&word)
+    {
+        config.control_case
+    } else if is_defining_word(word) || word == ":NONAME" {
+        config.defining_case
+    } else {
+        None
+    };
+    specific.unwrap_or(config.word_case)
+}
+
+/// Builtin words outside strings and comments whose case differs from the one `config`
+/// wants, with the text they should have
+pub fn recased_words<'a>(
+    tokens: &[Token<'a>],
+    config: &FormatConfig,
+    data: &Words,
+) -> Vec<(Data<'a>, String)> {
+    let builtins: HashSet<String> = data
+        .words
+        .iter()
+        .map(|word| word.token.to_uppercase())
+        .collect();
+    words_outside_strings(tokens)
+        .into_iter()
+        .filter(|word| builtins.contains(&word.value.to_uppercase()))
+        .filter_map(|word| {
+            let recased = match case_of(word.value, config) {
+                WordCase::Upper => word.value.to_uppercase(),
+                WordCase::Lower => word.value.to_lowercase(),
+                WordCase::Preserve => return None,
+            };
+            (recased != word.value).then_some((word, recased))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str, word_case: WordCase) -> String {
+        let config = FormatConfig {
+            word_case,
+            ..Default::default()
+        };
+        format_source(source, &config, &Words::default())
+    }
+
+    #[test]
+    fn indents_control_structures() {
+        assert_eq!(
+            ": abs ( n -- u )\n  dup 0< if\n    negate\n  then\n;\n",
+            format(
+                ": abs ( n -- u )\ndup 0< if\n        negate\n    then\n  ;",
+                WordCase::Preserve
+            )
+        );
+        assert_eq!(
+            ": count-down ( n -- )\n  begin\n    dup .\n    1- dup 0=\n  until drop ; immediate\n",
+            format(
+                ": count-down ( n -- )\nbegin\ndup .\n1- dup 0=\nuntil drop ; immediate",
+                WordCase::Preserve
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_top_level_and_comments() {
+        assert_eq!(
+            "\\ header\n\nvariable x\n\n: a ( multi\n      line ) 1 ;\n",
+            format(
+                "\\ header   \n\n\n\nvariable x   \n\n: a ( multi\n      line ) 1 ;\n\n\n",
+                WordCase::Preserve
+            )
+        );
+    }
+
+    #[test]
+    fn normalizes_line_breaks() {
+        let config = FormatConfig {
+            line_ending: LineEnding::Lf,
+            ..Default::default()
+        };
+        assert_eq!(
+            ": a ( n\n   -- n )\n  1 +\n;\n",
+            format_source(
+                ": a ( n   \r\n   -- n )\r\n\t1 +  \r\n;",
+                &config,
+                &Words::default()
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_the_dominant_line_breaks() {
+        assert_eq!(
+            ": a ( n\r\n   -- n )\r\n  1 +\r\n;\r\n",
+            format(": a ( n   \r\n   -- n )\n\t1 +  \r\n;", WordCase::Preserve)
+        );
+        assert_eq!(
+            ": a\n  1 +\n;\n",
+            format(": a\r\n\t1 +\n;\n", WordCase::Preserve)
+        );
+        let config = FormatConfig {
+            line_ending: LineEnding::Crlf,
+            ..Default::default()
+        };
+        assert_eq!("dup\r\n", format_source("dup", &config, &Words::default()));
+    }
+
+    #[test]
+    fn wraps_long_lines_inside_definitions() {
+        let config = FormatConfig {
+            max_line_width: Some(24),
+            ..Default::default()
+        };
+        let format = |source| format_source(source, &config, &Words::default());
+        assert_eq!(
+            ": show ( n -- ) dup\n  0< if\n  .\" negative number\"\n  then drop ;\n",
+            format(": show ( n -- ) dup 0< if .\" negative number\" then drop ;")
+        );
+        assert_eq!(
+            ": a\n  1 2 3 4 5 6 7 8 9 10\n    11 12 13 \\ a comment\n;\n",
+            format(": a\n1 2 3 4 5 6 7 8 9 10 11 12 13 \\ a comment\n;")
+        );
+        assert_eq!(
+            "variable a-long-variable-name-that-does-not-fit\n",
+            format("variable a-long-variable-name-that-does-not-fit")
+        );
+    }
+
+    #[test]
+    fn control_and_defining_words_have_their_own_case() {
+        let config = FormatConfig {
+            word_case: WordCase::Lower,
+            control_case: Some(WordCase::Upper),
+            defining_case: Some(WordCase::Preserve),
+            ..Default::default()
+        };
+        assert_eq!(
+            "Variable x\n: a ( n -- ) dup 0< IF drop THEN ;\n",
+            format_source(
+                "Variable x\n: a ( n -- ) DUP 0< if Drop then ;",
+                &config,
+                &Words::default()
+            )
+        );
+    }
+
+    #[test]
+    fn word_case_skips_strings_and_comments() {
+        assert_eq!(
+            ": greet ( -- ) .\" dup\" DUP SWAP ; \\ dup swap\n",
+            format(
+                ": greet ( -- ) .\" dup\" dup Swap ; \\ dup swap",
+                WordCase::Upper
+            )
+        );
+        assert_eq!(
+            "dup frob swap\n",
+            format("DUP frob Swap\n", WordCase::Lower)
+        );
+    }
+}
@@ -0,0 +1,274 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::Program,
+    definition_index::DefinitionIndex,
+    utils::{data_to_position::ToPosition, display_path},
+};
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
+
+use forth_lexer::parser::Lexer;
+use ropey::Rope;
+
+/// A user word with every place it is defined and the words using it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub sites: Vec<Site>,
+    /// Words whose definitions use this one, sorted
+    pub used_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    pub file: String,
+    /// Zero based, like LSP positions
+    pub line: u32,
+    pub stack: Option<String>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Format::Markdown),
+            "html" | "htm" => Some(Format::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Every defined word of the index, sorted by name ignoring case
+pub fn entries(index: &DefinitionIndex, files: &HashMap<String, Rope>) -> Vec<Entry> {
+    let mut sites: HashMap<String, (String, Vec<Site>)> = HashMap::new();
+    let mut used_by: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for (file, file_index) in index.files() {
+        let stacks = files.get(file).map(stack_comments).unwrap_or_default();
+        for definition in &file_index.definitions {
+            let (_, entry) = sites
+                .entry(definition.name.to_uppercase())
+                .or_insert_with(|| (definition.name.clone(), vec![]));
+            entry.push(Site {
+                file: file.clone(),
+                line: definition.range.start.line,
+                stack: stacks
+                    .get(&(
+                        definition.selection_range.start.line,
+                        definition.selection_range.start.character,
+                    ))
+                    .cloned(),
+                doc: definition.doc.clone(),
+            });
+        }
+        for reference in &file_index.references {
+            if let Some(user) = file_index.user_of(reference) {
+                used_by
+                    .entry(reference.name.to_uppercase())
+                    .or_default()
+                    .insert(user.name.clone());
+            }
+        }
+    }
+    let mut ret: Vec<Entry> = sites
+        .into_iter()
+        .map(|(key, (name, mut sites))| {
+            sites.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+            Entry {
+                name,
+                sites,
+                used_by: used_by
+                    .remove(&key)
+                    .map(|users| users.into_iter().collect())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+    ret.sort_by(|a, b| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then(a.name.cmp(&b.name))
+    });
+    ret
+}
+
+/// Stack comments of colon definitions, keyed by where their name starts
+fn stack_comments(rope: &Rope) -> HashMap<(u32, u32), String> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    Program::parse(&tokens)
+        .definitions()
+        .filter_map(|definition| {
+            let name = definition.name?;
+            let stack = definition.stack_comment()?;
+            let start = name.to_position_start(rope);
+            Some(((start.line, start.character), stack.value.to_string()))
+        })
+        .collect()
+}
+
+/// Anchor of a word, words are spelled with any characters so the odd ones are spelled out
+fn anchor(name: &str) -> String {
+    let mut ret = "word-".to_string();
+    for chr in name.to_lowercase().chars() {
+        if chr.is_ascii_alphanumeric() {
+            ret.push(chr);
+        } else {
+            ret.push_str(&format!("-{:x}", chr as u32));
+        }
+    }
+    ret
+}
+
+pub fn render(entries: &[Entry], format: Format, root: Option<&Path>) -> String {
+    match format {
+        Format::Markdown => to_markdown(entries, root),
+        Format::Html => to_html(entries, root),
+    }
+}
+
+fn to_markdown(entries: &[Entry], root: Option<&Path>) -> String {
+    let mut ret = "# Glossary\n".to_string();
+    for entry in entries {
+        ret.push_str(&format!(
+            "\n<a id=\"{}\"></a>\n## `{}`\n",
+            anchor(&entry.name),
+            entry.name
+        ));
+        for site in &entry.sites {
+            if let Some(stack) = &site.stack {
+                ret.push_str(&format!("\n`{}`\n", stack));
+            }
+            if let Some(doc) = &site.doc {
+                ret.push_str(&format!("\n{}\n", doc));
+            }
+            let path = display_path(&site.file, root);
+            ret.push_str(&format!(
+                "\nDefined in [{}:{}]({}#L{})\n",
+                path,
+                site.line + 1,
+                path,
+                site.line + 1
+            ));
+        }
+        if !entry.used_by.is_empty() {
+            let users: Vec<String> = entry
+                .used_by
+                .iter()
+                .map(|user| format!("[`{}`](#{})", user, anchor(user)))
+                .collect();
+            ret.push_str(&format!("\nUsed by {}\n", users.join(", ")));
+        }
+    }
+    ret
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn to_html(entries: &[Entry], root: Option<&Path>) -> String {
+    let mut ret = "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Glossary</title></head>\n<body>\n<h1>Glossary</h1>\n".to_string();
+    for entry in entries {
+        ret.push_str(&format!(
+            "<h2 id=\"{}\"><code>{}</code></h2>\n",
+            anchor(&entry.name),
+            escape(&entry.name)
+        ));
+        for site in &entry.sites {
+            if let Some(stack) = &site.stack {
+                ret.push_str(&format!("<p><code>{}</code></p>\n", escape(stack)));
+            }
+            if let Some(doc) = &site.doc {
+                ret.push_str(&format!("<p>{}</p>\n", escape(doc).replace('\n', "<br>\n")));
+            }
+            let path = escape(&display_path(&site.file, root));
+            ret.push_str(&format!(
+                "<p>Defined in <a href=\"{}#L{}\">{}:{}</a></p>\n",
+                path,
+                site.line + 1,
+                path,
+                site.line + 1
+            ));
+        }
+        if !entry.used_by.is_empty() {
+            let users: Vec<String> = entry
+                .used_by
+                .iter()
+                .map(|user| {
+                    format!(
+                        "<a href=\"#{}\"><code>{}</code></a>",
+                        anchor(user),
+                        escape(user)
+                    )
+                })
+                .collect();
+            ret.push_str(&format!("<p>Used by {}</p>\n", users.join(", ")));
+        }
+    }
+    ret.push_str("</body>\n</html>\n");
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary(source: &str) -> Vec<Entry> {
+        let rope = Rope::from_str(source);
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let files = HashMap::from([("/src/a.forth".to_string(), rope)]);
+        entries(&index, &files)
+    }
+
+    #[test]
+    fn entries_are_sorted_with_their_users() {
+        let entries =
+            glossary("\\ Square it.\n: sq ( n -- n ) dup * ;\n: cube dup sq * ;\n: Area sq ;");
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(vec!["Area", "cube", "sq"], names);
+        let sq = &entries[2];
+        assert_eq!(vec!["Area", "cube"], sq.used_by);
+        assert_eq!(
+            vec![Site {
+                file: "/src/a.forth".to_string(),
+                line: 1,
+                stack: Some("( n -- n )".to_string()),
+                doc: Some("Square it.".to_string()),
+            }],
+            sq.sites
+        );
+    }
+
+    #[test]
+    fn markdown_links_sites_and_users() {
+        let entries = glossary(": sq ( n -- n ) dup * ;\n: 2sq sq sq ;");
+        assert_eq!(
+            "# Glossary\n\n<a id=\"word-2sq\"></a>\n## `2sq`\n\nDefined in [a.forth:2](a.forth#L2)\n\n<a id=\"word-sq\"></a>\n## `sq`\n\n`( n -- n )`\n\nDefined in [a.forth:1](a.forth#L1)\n\nUsed by [`2sq`](#word-2sq)\n",
+            render(&entries, Format::Markdown, Some(Path::new("/src")))
+        );
+    }
+
+    #[test]
+    fn html_escapes_names() {
+        let entries = glossary(": <sq> dup * ;");
+        let html = render(&entries, Format::Html, None);
+        assert!(html.contains("<h2 id=\"word--3csq-3e\"><code>&lt;sq&gt;</code></h2>"));
+        assert!(html.contains("<a href=\"/src/a.forth#L1\">/src/a.forth:1</a>"));
+    }
+}
@@ -0,0 +1,186 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{definition_index::DefinitionIndex, utils::display_path};
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+use ropey::Rope;
+use serde_json::json;
+
+/// Which user words each user word calls, and which files each file includes
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Graphs {
+    pub words: BTreeMap<String, BTreeSet<String>>,
+    pub files: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Dot,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "dot" | "gv" => Some(Format::Dot),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The call graph between defined words and the include graph between files, paths are
+/// relative to `root` when below it
+pub fn graphs(
+    index: &DefinitionIndex,
+    files: &HashMap<String, Rope>,
+    root: Option<&Path>,
+) -> Graphs {
+    let mut ret = Graphs::default();
+    // words are spelled like their first definition
+    let mut names: HashMap<String, String> = HashMap::new();
+    for (_, file_index) in index.files() {
+        for definition in &file_index.definitions {
+            names
+                .entry(definition.name.to_uppercase())
+                .or_insert_with(|| definition.name.clone());
+        }
+    }
+    for (_, file_index) in index.files() {
+        for definition in &file_index.definitions {
+            ret.words
+                .entry(names[&definition.name.to_uppercase()].clone())
+                .or_default();
+        }
+        for reference in &file_index.references {
+            let Some(called) = names.get(&reference.name.to_uppercase()) else {
+                continue;
+            };
+            if let Some(user) = file_index.user_of(reference) {
+                ret.words
+                    .entry(names[&user.name.to_uppercase()].clone())
+                    .or_default()
+                    .insert(called.clone());
+            }
+        }
+    }
+    let include_graph = index.include_graph();
+    for file in files.keys() {
+        ret.files.insert(
+            display_path(file, root),
+            include_graph
+                .includes(file)
+                .map(|included| display_path(included, root))
+                .collect(),
+        );
+    }
+    ret
+}
+
+pub fn render(graphs: &Graphs, format: Format) -> String {
+    match format {
+        Format::Dot => to_dot(graphs),
+        Format::Json => {
+            let ret = json!({ "words": graphs.words, "files": graphs.files });
+            format!(
+                "{}\n",
+                serde_json::to_string_pretty(&ret).expect("Must be able to serialize graphs")
+            )
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Both graphs as clusters of one digraph, node ids are prefixed so a word and a file may
+/// share a name
+fn to_dot(graphs: &Graphs) -> String {
+    let mut ret = "digraph forth {\n".to_string();
+    for (cluster, label, graph) in [
+        ("words", "Words", &graphs.words),
+        ("files", "Files", &graphs.files),
+    ] {
+        ret.push_str(&format!(
+            "  subgraph cluster_{} {{\n    label={};\n",
+            cluster,
+            quote(label)
+        ));
+        let id = |name: &str| quote(&format!("{}:{}", cluster, name));
+        for node in graph.keys() {
+            ret.push_str(&format!("    {} [label={}];\n", id(node), quote(node)));
+        }
+        for (from, targets) in graph {
+            for to in targets {
+                ret.push_str(&format!("    {} -> {};\n", id(from), id(to)));
+            }
+        }
+        ret.push_str("  }\n");
+    }
+    ret.push_str("}\n");
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> (DefinitionIndex, HashMap<String, Rope>) {
+        let files = HashMap::from([
+            (
+                "/src/main.forth".to_string(),
+                Rope::from_str("include lib.forth\n: main cube . ;"),
+            ),
+            (
+                "/src/lib.forth".to_string(),
+                Rope::from_str(": sq dup * ;\n: CUBE dup sq * ;"),
+            ),
+        ]);
+        let mut index = DefinitionIndex::default();
+        for (file, rope) in files.iter() {
+            index.update_file(file, rope);
+        }
+        (index, files)
+    }
+
+    #[test]
+    fn calls_and_includes() {
+        let (index, files) = workspace();
+        let graphs = graphs(&index, &files, Some(Path::new("/src")));
+        assert_eq!(
+            BTreeMap::from([
+                ("CUBE".to_string(), BTreeSet::from(["sq".to_string()])),
+                ("main".to_string(), BTreeSet::from(["CUBE".to_string()])),
+                ("sq".to_string(), BTreeSet::new()),
+            ]),
+            graphs.words
+        );
+        assert_eq!(
+            BTreeMap::from([
+                ("lib.forth".to_string(), BTreeSet::new()),
+                (
+                    "main.forth".to_string(),
+                    BTreeSet::from(["lib.forth".to_string()])
+                ),
+            ]),
+            graphs.files
+        );
+    }
+
+    #[test]
+    fn dot_quotes_names() {
+        let graphs = Graphs {
+            words: BTreeMap::from([("a\"b".to_string(), BTreeSet::from(["c".to_string()]))]),
+            files: BTreeMap::new(),
+        };
+        assert_eq!(
+            "digraph forth {\n  subgraph cluster_words {\n    label=\"Words\";\n    \"words:a\\\"b\" [label=\"a\\\"b\"];\n    \"words:a\\\"b\" -> \"words:c\";\n  }\n  subgraph cluster_files {\n    label=\"Files\";\n  }\n}\n",
+            render(&graphs, Format::Dot)
+        );
+    }
+}
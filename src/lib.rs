@@ -0,0 +1,23 @@
+pub mod ast;
+pub mod baseline;
+pub mod code_actions;
+pub mod config;
+pub mod definition_index;
+pub mod diagnostics;
+pub mod environment;
+pub mod error;
+pub mod exceptions;
+pub mod formatter;
+pub mod glossary;
+pub mod graph;
+pub mod loops;
+pub mod markers;
+pub mod prelude;
+pub mod project;
+pub mod scip;
+pub mod server;
+pub mod stack_effect;
+pub mod structures;
+pub mod suppressions;
+pub mod utils;
+pub mod words;
@@ -0,0 +1,117 @@
+use crate::ast::{Node, Program};
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use std::collections::HashSet;
+
+use forth_lexer::{
+    context::ContextTokens,
+    token::{Data, Token},
+};
+
+/// A use of `I` or `J` in a definition, with the loop whose index it reads
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopIndexUse<'a> {
+    pub word: Data<'a>,
+    /// The `DO` or `?DO` of the loop, None when fewer loops are active than the word needs
+    pub opener: Option<Data<'a>>,
+    /// Which loop of the definition it is, counting `DO`s from 1 in source order
+    pub number: Option<usize>,
+    /// How many loops are active at the word
+    pub active: usize,
+}
+
+/// Every `I` and `J` inside a definition
+pub fn loop_index_uses<'a>(tokens: &[Token<'a>]) -> Vec<LoopIndexUse<'a>> {
+    let code: HashSet<usize> = ContextTokens::new(tokens.iter().cloned())
+        .filter(|context| !context.in_string && !context.in_comment && !context.parsed)
+        .map(|context| context.token.get_data().start)
+        .collect();
+    let mut ret = vec![];
+    for definition in Program::parse(tokens).definitions() {
+        let mut count = 0;
+        walk(&definition.body, &mut vec![], &mut count, &code, &mut ret);
+    }
+    ret
+}
+
+fn walk<'a>(
+    nodes: &[Node<'a>],
+    active: &mut Vec<(usize, Data<'a>)>,
+    count: &mut usize,
+    code: &HashSet<usize>,
+    ret: &mut Vec<LoopIndexUse<'a>>,
+) {
+    for node in nodes {
+        match node {
+            Node::Error(_) => {}
+            Node::Phrase(phrase) => {
+                for token in &phrase.tokens {
+                    let Token::Word(word) = token else {
+                        continue;
+                    };
+                    let depth = match word.value.to_uppercase().as_str() {
+                        "I" => 0,
+                        "J" => 1,
+                        _ => continue,
+                    };
+                    if !code.contains(&word.start) {
+                        continue;
+                    }
+                    let target = active.iter().rev().nth(depth);
+                    ret.push(LoopIndexUse {
+                        word: *word,
+                        opener: target.map(|(_, opener)| *opener),
+                        number: target.map(|(number, _)| *number),
+                        active: active.len(),
+                    });
+                }
+            }
+            Node::Control(control) => {
+                let opener = control.branches[0].keyword;
+                let is_loop = ["DO", "?DO"]
+                    .iter()
+                    .any(|word| opener.value.eq_ignore_ascii_case(word));
+                if is_loop {
+                    *count += 1;
+                    active.push((*count, opener));
+                }
+                for branch in &control.branches {
+                    walk(&branch.body, active, count, code, ret);
+                }
+                if is_loop {
+                    active.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use forth_lexer::parser::Lexer;
+
+    #[test]
+    fn indices_read_the_innermost_loops() {
+        let source = ": grid 3 0 do 3 0 do i j * . loop loop 2 0 ?do i . loop ;\n\
+                      : stray i j .\" i\" ;";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.parse();
+        let uses: Vec<(usize, Option<usize>, usize)> = loop_index_uses(&tokens)
+            .iter()
+            .map(|found| (found.word.start, found.number, found.active))
+            .collect();
+        assert_eq!(
+            vec![
+                (21, Some(2), 2),
+                (23, Some(1), 2),
+                (47, Some(3), 1),
+                (66, None, 0),
+                (68, None, 0)
+            ],
+            uses
+        );
+    }
+}
@@ -1,109 +1,310 @@
-mod error;
-mod prelude;
-mod utils;
-mod words;
-
-use crate::prelude::*;
-use crate::utils::handlers::notification_did_change::handle_did_change_text_document;
-use crate::utils::handlers::notification_did_open::handle_did_open_text_document;
-use crate::utils::handlers::request_completion::handle_completion;
-use crate::utils::handlers::request_goto_definition::handle_goto_definition;
-use crate::utils::handlers::request_hover::handle_hover;
-use crate::utils::server_capabilities::forth_lsp_capabilities;
-use crate::words::Words;
-
-use std::collections::HashMap;
-use std::ffi::OsStr;
-use std::fs;
-use std::path::Path;
-
-use lsp_server::{Connection, Message};
-use lsp_types::InitializeParams;
+use forth_lsp::baseline::{relative_path, Baseline};
+use forth_lsp::config::Config;
+use forth_lsp::diagnostics::{document_diagnostics, project_diagnostics};
+use forth_lsp::glossary::{self, entries};
+use forth_lsp::graph::{self, graphs};
+use forth_lsp::prelude::*;
+use forth_lsp::project::{ProjectWords, Projects};
+use forth_lsp::scip::{self, scip_index};
+use forth_lsp::server::{initialize, load_workspace, main_loop};
+use forth_lsp::utils::position_encoding::PositionEncoding;
+use forth_lsp::utils::server_capabilities::forth_lsp_capabilities;
+
+use forth_lsp::definition_index::DefinitionIndex;
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
 
 use ropey::Rope;
 
-fn main() -> Result<()> {
-    // Note that  we must have our logging only write out to stderr.
-    eprintln!("starting generic LSP server");
+use lsp_server::Connection;
+use lsp_types::{DiagnosticSeverity, NumberOrString};
 
-    // Create the transport. Includes the stdio (stdin and stdout) versions but this could
-    // also be implemented to use sockets or HTTP.
-    let (connection, io_threads) = Connection::stdio();
+const USAGE: &str = "Usage: forth-lsp [--version | --capabilities | --config-schema | --help]
+       forth-lsp glossary <dir> [-o <file>] [--format markdown|html]
+       forth-lsp graph <dir> [-o <file>] [--format dot|json]
+       forth-lsp lint <dir> [--write-baseline <file>]
+       forth-lsp index <dir> [-o <file>] [--format scip]
 
-    // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
-    let server_capabilities = serde_json::to_value(forth_lsp_capabilities())?;
-    let initialization_params = connection.initialize(server_capabilities)?;
-    main_loop(connection, initialization_params)?;
-    io_threads.join()?;
+Without arguments the server speaks the Language Server Protocol over stdio.
 
-    // Shut down gracefully.
-    eprintln!("shutting down server");
+  -V, --version       Print the version and the commit it was built from
+      --capabilities  Print the server capabilities as JSON
+      --config-schema Print the JSON Schema of .forth-lsp.toml
+  -h, --help          Print this help
+  glossary            Write a glossary of the words defined under <dir>, to stdout
+                      unless -o is given. The format follows the extension of <file>
+                      unless --format is given
+  graph               Write the call graph of the words and the include graph of the
+                      files under <dir>, like glossary
+  lint                Print the diagnostics of the files under <dir> that its baseline
+                      does not record, failing when any is an error or a warning.
+                      --write-baseline records them all in <file> instead
+  index               Write a SCIP code intelligence index of the definitions, uses
+                      and documentation of the words under <dir>, like glossary";
+
+fn version() -> String {
+    format!(
+        "forth-lsp {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        env!("FORTH_LSP_GIT_HASH")
+    )
+}
+
+/// Directory, output file and format of `<dir> [-o <file>] [--format <format>]`, the format
+/// defaults to the extension of the output file and then to `default`
+fn export_args<F>(
+    args: &[String],
+    from_name: impl Fn(&str) -> Option<F>,
+    default: F,
+) -> Option<(&str, Option<&str>, F)> {
+    let mut dir = None;
+    let mut output = None;
+    let mut format = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(args.next()?.as_str()),
+            "--format" => format = Some(from_name(args.next()?)?),
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(arg.as_str()),
+            _ => return None,
+        }
+    }
+    let format = format
+        .or_else(|| {
+            output
+                .and_then(|output| Path::new(output).extension())
+                .and_then(|extension| extension.to_str())
+                .and_then(&from_name)
+        })
+        .unwrap_or(default);
+    Some((dir?, output, format))
+}
+
+/// Index `dir` and write what `export` makes of it to `output`, or stdout
+fn export(
+    dir: &str,
+    output: Option<&str>,
+    export: impl FnOnce(&Path, HashMap<String, Rope>, DefinitionIndex) -> Vec<u8>,
+) -> Result<()> {
+    let root = fs::canonicalize(dir).map_err(|source| Error::Path {
+        path: dir.into(),
+        source,
+    })?;
+    let (files, index) = load_workspace(&root)?;
+    let exported = export(&root, files, index);
+    match output {
+        Some(output) => fs::write(output, exported).map_err(|source| Error::Path {
+            path: output.into(),
+            source,
+        })?,
+        None => io::stdout().write_all(&exported)?,
+    }
     Ok(())
 }
 
-fn main_loop(connection: Connection, params: serde_json::Value) -> Result<()> {
-    eprintln!("Starting main loop");
-    let init: InitializeParams = serde_json::from_value(params)?;
-    let mut files = HashMap::<String, Rope>::new();
-    if let Some(roots) = init.workspace_folders {
-        eprintln!("Root: {:?}", roots);
-        for root in roots {
-            load_dir(root.uri.path(), &mut files)?;
+/// Directory and baseline to write of `<dir> [--write-baseline <file>]`
+fn lint_args(args: &[String]) -> Option<(&str, Option<&str>)> {
+    let mut dir = None;
+    let mut write_baseline = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--write-baseline" => write_baseline = Some(args.next()?.as_str()),
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(arg.as_str()),
+            _ => return None,
         }
     }
-    let data = Words::default();
-    for msg in &connection.receiver {
-        match msg {
-            Message::Request(request) => {
-                if connection.handle_shutdown(&request)? {
-                    return Ok(());
-                }
-                eprintln!("got request: {:?}", request.method);
-                if handle_hover(&request, &connection, &data, &mut files).is_ok() {
-                    continue;
-                }
-                if handle_completion(&request, &connection, &data, &mut files).is_ok() {
-                    continue;
+    Some((dir?, write_baseline))
+}
+
+/// Print the diagnostics of the files under `dir` as its project configures them, failing on
+/// errors and warnings, or record them all in `write_baseline`
+fn lint(dir: &str, write_baseline: Option<&str>) -> Result<ExitCode> {
+    let root = fs::canonicalize(dir).map_err(|source| Error::Path {
+        path: dir.into(),
+        source,
+    })?;
+    let (files, index) = load_workspace(&root)?;
+    let mut projects = Projects::default();
+    projects.add(&root);
+    let words = ProjectWords::new(&projects);
+    // files of include paths outside `dir` are not linted
+    let mut linted: Vec<(String, &String)> = files
+        .keys()
+        .filter_map(|file| Some((relative_path(file, &root)?, file)))
+        .collect();
+    linted.sort();
+    if let Some(write_baseline) = write_baseline {
+        let mut baseline = Baseline::default();
+        for (relative, file) in &linted {
+            let config = &projects.config_for(file).diagnostics;
+            let include_dirs = projects.include_dirs(file);
+            let diagnostics = document_diagnostics(
+                file,
+                &files,
+                words.for_file(file),
+                &index,
+                config,
+                &include_dirs,
+            );
+            baseline.record(relative, &files[*file], &diagnostics);
+        }
+        fs::write(write_baseline, baseline.to_json()).map_err(|source| Error::Path {
+            path: write_baseline.into(),
+            source,
+        })?;
+        let count: usize = baseline.issues.iter().map(|issue| issue.count).sum();
+        eprintln!("Recorded {} diagnostics in {}", count, write_baseline);
+        return Ok(ExitCode::SUCCESS);
+    }
+    let mut failing = 0;
+    for (relative, file) in &linted {
+        let mut diagnostics = project_diagnostics(
+            file,
+            &files,
+            &words,
+            &index,
+            &projects,
+            PositionEncoding::Utf32,
+        );
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+        for diagnostic in diagnostics {
+            let severity = match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => "error",
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                Some(DiagnosticSeverity::HINT) => "hint",
+                _ => "info",
+            };
+            let rule = match diagnostic.code {
+                Some(NumberOrString::String(code)) => format!(" [{}]", code),
+                _ => String::new(),
+            };
+            println!(
+                "{}:{}:{}: {}: {}{}",
+                relative,
+                diagnostic.range.start.line + 1,
+                diagnostic.range.start.character + 1,
+                severity,
+                diagnostic.message,
+                rule
+            );
+            if matches!(
+                diagnostic.severity,
+                Some(DiagnosticSeverity::ERROR | DiagnosticSeverity::WARNING)
+            ) {
+                failing += 1;
+            }
+        }
+    }
+    Ok(if failing == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            eprintln!("forth-lsp: {}", err.report());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<ExitCode> {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(arg) = args.get(1) {
+        match arg.as_str() {
+            "-V" | "--version" => println!("{}", version()),
+            "--capabilities" => println!(
+                "{}",
+                serde_json::to_string_pretty(&forth_lsp_capabilities())?
+            ),
+            "--config-schema" => {
+                println!("{}", serde_json::to_string_pretty(&Config::json_schema())?)
+            }
+            "-h" | "--help" => println!("{}", USAGE),
+            "glossary" => match export_args(
+                &args[2..],
+                glossary::Format::from_name,
+                glossary::Format::Markdown,
+            ) {
+                Some((dir, output, format)) => export(dir, output, |root, files, index| {
+                    glossary::render(&entries(&index, &files), format, Some(root)).into_bytes()
+                })?,
+                None => {
+                    eprintln!("Bad glossary arguments\n\n{}", USAGE);
+                    return Ok(ExitCode::from(2));
                 }
-                if handle_goto_definition(&request, &connection, &data, &mut files).is_ok() {
-                    continue;
+            },
+            "graph" => {
+                match export_args(&args[2..], graph::Format::from_name, graph::Format::Dot) {
+                    Some((dir, output, format)) => export(dir, output, |root, files, index| {
+                        graph::render(&graphs(&index, &files, Some(root)), format).into_bytes()
+                    })?,
+                    None => {
+                        eprintln!("Bad graph arguments\n\n{}", USAGE);
+                        return Ok(ExitCode::from(2));
+                    }
                 }
             }
-            Message::Response(resp) => {
-                eprintln!("got response: {resp:?}");
-            }
-            Message::Notification(notification) => {
-                eprintln!("got notification: {:?}", notification.method);
-                if handle_did_open_text_document(&notification, &mut files).is_ok() {
-                    continue;
+            "index" => match export_args(&args[2..], scip::Format::from_name, scip::Format::Scip) {
+                Some((dir, output, scip::Format::Scip)) => {
+                    export(dir, output, |root, _, index| {
+                        let mut projects = Projects::default();
+                        projects.add(root);
+                        let words = ProjectWords::new(&projects);
+                        let data = words.for_file(&root.to_string_lossy());
+                        scip_index(&index, data, root).encode(env!("CARGO_PKG_VERSION"))
+                    })?
                 }
-                if handle_did_change_text_document(&notification, &mut files).is_ok() {
-                    continue;
+                None => {
+                    eprintln!("Bad index arguments\n\n{}", USAGE);
+                    return Ok(ExitCode::from(2));
                 }
+            },
+            "lint" => match lint_args(&args[2..]) {
+                Some((dir, write_baseline)) => return lint(dir, write_baseline),
+                None => {
+                    eprintln!("Bad lint arguments\n\n{}", USAGE);
+                    return Ok(ExitCode::from(2));
+                }
+            },
+            _ => {
+                eprintln!("Unknown argument {}\n\n{}", arg, USAGE);
+                return Ok(ExitCode::from(2));
             }
         }
+        return Ok(ExitCode::SUCCESS);
     }
-    Ok(())
-}
 
-fn load_dir(
-    root: &str, //lsp_types::WorkspaceFolder,
-    files: &mut HashMap<String, Rope>,
-) -> Result<()> {
-    if let Ok(paths) = fs::read_dir(root) {
-        for path in paths {
-            if let Some(entry) = path?.path().to_str() {
-                if fs::metadata(entry)?.is_dir() {
-                    load_dir(entry, files)?;
-                } else if Path::new(entry).extension().and_then(OsStr::to_str) == Some("forth") {
-                    eprintln!("FORTH load {}", entry);
-                    let raw_content = fs::read(entry)?;
-                    let content = String::from_utf8_lossy(&raw_content);
-                    let rope = Rope::from_str(&content);
-                    files.insert(entry.to_string(), rope);
-                }
-            }
+    // Note that  we must have our logging only write out to stderr.
+    eprintln!("starting generic LSP server");
+
+    // Create the transport. Includes the stdio (stdin and stdout) versions but this could
+    // also be implemented to use sockets or HTTP.
+    let (connection, io_threads) = Connection::stdio();
+
+    // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
+    let server_capabilities = serde_json::to_value(forth_lsp_capabilities())?;
+    let exit_code = match initialize(&connection, server_capabilities)? {
+        Some(initialization_params) => main_loop(connection, initialization_params)?,
+        None => {
+            drop(connection);
+            ExitCode::FAILURE
         }
-    }
-    Ok(())
+    };
+    io_threads.join()?;
+
+    // Shut down gracefully.
+    eprintln!("shutting down server");
+    Ok(exit_code)
 }
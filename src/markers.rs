@@ -0,0 +1,142 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{ast::definition_name, diagnostics::defined_names};
+
+use forth_lexer::{
+    context::ContextTokens,
+    token::{Data, Token},
+};
+
+/// `MARKER name`, with the names defined after it in the same file. Running the marker
+/// forgets them, and itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker<'a> {
+    pub name: Data<'a>,
+    pub forgets: Vec<Data<'a>>,
+}
+
+/// A word used after a marker forgot it, and not defined again since
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForgottenUse<'a> {
+    pub word: Data<'a>,
+    /// Where the marker that forgot it ran
+    pub forgotten_by: Data<'a>,
+}
+
+/// Every `MARKER` in `tokens`
+pub fn markers<'a>(tokens: &[Token<'a>]) -> Vec<Marker<'a>> {
+    let names = defined_names(tokens);
+    code(tokens)
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [Token::Word(marker), name] if marker.value.eq_ignore_ascii_case("MARKER") => {
+                definition_name(name)
+            }
+            _ => None,
+        })
+        .map(|name| Marker {
+            name,
+            forgets: names
+                .iter()
+                .filter(|defined| defined.start > name.start)
+                .copied()
+                .collect(),
+        })
+        .collect()
+}
+
+/// Uses of words after a marker defined before them ran while interpreting the file. An
+/// approximation, markers run from definitions or other files are not followed
+pub fn forgotten_uses<'a>(tokens: &[Token<'a>]) -> Vec<ForgottenUse<'a>> {
+    let names = defined_names(tokens);
+    // definitions and what each interpreted run of a marker forgets, by position
+    let mut events: Vec<(usize, String, Option<Data>)> = names
+        .iter()
+        .map(|name| (name.start, name.value.to_uppercase(), None))
+        .collect();
+    let markers = markers(tokens);
+    for context in ContextTokens::new(tokens.iter().cloned()) {
+        let Token::Word(run) = context.token else {
+            continue;
+        };
+        if context.in_definition || context.in_string || context.in_comment || context.parsed {
+            continue;
+        }
+        let Some(marker) = markers.iter().find(|marker| {
+            marker.name.start < run.start && marker.name.value.eq_ignore_ascii_case(run.value)
+        }) else {
+            continue;
+        };
+        for forgotten in std::iter::once(&marker.name).chain(&marker.forgets) {
+            if forgotten.start < run.start {
+                events.push((run.start, forgotten.value.to_uppercase(), Some(run)));
+            }
+        }
+    }
+    events.sort_by_key(|(start, _, _)| *start);
+    let mut ret = vec![];
+    for context in ContextTokens::new(tokens.iter().cloned()) {
+        let Token::Word(word) = context.token else {
+            continue;
+        };
+        if context.in_string || context.in_comment || context.parsed {
+            continue;
+        }
+        if names.iter().any(|name| name.start == word.start) {
+            continue;
+        }
+        let name = word.value.to_uppercase();
+        let latest = events
+            .iter()
+            .rev()
+            .find(|(start, forgotten, _)| *start < word.start && *forgotten == name);
+        if let Some((_, _, Some(run))) = latest {
+            ret.push(ForgottenUse {
+                word,
+                forgotten_by: *run,
+            });
+        }
+    }
+    ret
+}
+
+/// Tokens outside comments and strings, that are not parsed by the word before them
+fn code<'a>(tokens: &[Token<'a>]) -> Vec<Token<'a>> {
+    ContextTokens::new(tokens.iter().cloned())
+        .filter(|context| !context.in_string && !context.in_comment && !context.parsed)
+        .map(|context| context.token)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use forth_lexer::parser::Lexer;
+
+    #[test]
+    fn markers_forget_what_follows_them() {
+        let source = ": keep ;\nmarker -app\n: sq dup * ;\nvariable count";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.parse();
+        let markers = markers(&tokens);
+        assert_eq!(1, markers.len());
+        assert_eq!("-app", markers[0].name.value);
+        let forgets: Vec<&str> = markers[0].forgets.iter().map(|name| name.value).collect();
+        assert_eq!(vec!["sq", "count"], forgets);
+    }
+
+    #[test]
+    fn uses_after_running_the_marker() {
+        let source =
+            "marker -app\n: sq dup * ;\n: four 2 sq ;\n-app\n3 sq four\n: sq 0 ;\n1 sq -app";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.parse();
+        let uses: Vec<(&str, usize)> = forgotten_uses(&tokens)
+            .iter()
+            .map(|forgotten| (forgotten.word.value, forgotten.forgotten_by.start))
+            .collect();
+        // `sq` is defined again, the marker forgot itself
+        assert_eq!(vec![("sq", 39), ("four", 39), ("-app", 39)], uses);
+    }
+}
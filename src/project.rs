@@ -0,0 +1,284 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    baseline::Baseline,
+    config::{Config, ConfigProblem, CONFIG_FILE_NAME},
+    utils::file_path,
+    words::{Word, Words},
+};
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// A directory with its own `.forth-lsp.toml`, either a workspace folder or a nested project
+#[derive(Debug)]
+pub struct Project {
+    pub root: PathBuf,
+    pub config: Config,
+    /// What of its `.forth-lsp.toml` was ignored
+    pub problems: Vec<ConfigProblem>,
+    /// The diagnostics `diagnostics.baseline` records
+    pub baseline: Option<Baseline>,
+    /// Found below a workspace folder, instead of being one
+    pub nested: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Projects {
+    projects: Vec<Project>,
+    fallback: Config,
+}
+
+impl Projects {
+    /// Register the workspace folder `root`, loading its config, unless it is already known
+    pub fn add(&mut self, root: &Path) {
+        if self.get(root).is_none() {
+            self.load(root, false);
+        }
+    }
+
+    /// Register `root`, a directory with a config below a workspace folder, unless it is
+    /// already known
+    pub fn add_nested(&mut self, root: &Path) {
+        if self.get(root).is_none() {
+            self.load(root, true);
+        }
+    }
+
+    /// Re-read the config of `root`. A missing file resets a workspace folder to defaults and
+    /// removes a nested project, leaving its files to the project around it
+    pub fn reload(&mut self, root: &Path) {
+        let nested = self.get(root).is_none_or(|project| project.nested);
+        if nested && !root.join(CONFIG_FILE_NAME).is_file() {
+            self.projects.retain(|project| project.root != root);
+            return;
+        }
+        self.load(root, nested);
+    }
+
+    fn load(&mut self, root: &Path, nested: bool) {
+        let (config, mut problems) = Config::load(root);
+        let baseline = config.diagnostics.baseline.as_ref().and_then(|baseline| {
+            Baseline::load(&root.join(baseline))
+                .map_err(|err| {
+                    problems.push(ConfigProblem {
+                        message: format!("Could not load the baseline: {}", err),
+                        range: None,
+                    })
+                })
+                .ok()
+        });
+        self.insert(root.to_path_buf(), config);
+        if let Some(project) = self.projects.last_mut() {
+            project.problems = problems;
+            project.baseline = baseline;
+            project.nested = nested;
+        }
+    }
+
+    pub fn insert(&mut self, root: PathBuf, config: Config) {
+        self.projects.retain(|project| project.root != root);
+        self.projects.push(Project {
+            root,
+            config,
+            problems: vec![],
+            baseline: None,
+            nested: false,
+        });
+    }
+
+    /// The project rooted at `root`
+    pub fn get(&self, root: &Path) -> Option<&Project> {
+        self.projects.iter().find(|project| project.root == root)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Project> {
+        self.projects.iter()
+    }
+
+    /// The innermost project containing `path`
+    pub fn project_for_path(&self, path: &Path) -> Option<&Project> {
+        self.projects
+            .iter()
+            .filter(|project| path.starts_with(&project.root))
+            .max_by_key(|project| project.root.components().count())
+    }
+
+    pub fn config_for_path(&self, path: &Path) -> &Config {
+        self.project_for_path(path)
+            .map(|project| &project.config)
+            .unwrap_or(&self.fallback)
+    }
+
+    /// Whether `path` matches the `source.exclude` globs of its project
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        match self.project_for_path(path) {
+            Some(project) => match path.strip_prefix(&project.root) {
+                Ok(relative) => project.config.source.is_excluded(relative),
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Directories of the `source.include_paths` of the project of `file`, searched for
+    /// included files after the directory of the file including them
+    pub fn include_dirs(&self, file: &str) -> Vec<PathBuf> {
+        let Some(project) = file_path(file).and_then(|path| self.project_for_path(&path)) else {
+            return vec![];
+        };
+        project
+            .config
+            .source
+            .resolve_include_paths(&project.root)
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .collect()
+    }
+
+    /// The config for a key of the files map, being either a path or a URI
+    pub fn config_for(&self, file: &str) -> &Config {
+        match file_path(file) {
+            Some(path) => self.config_for_path(&path),
+            None => &self.fallback,
+        }
+    }
+}
+
+/// The words known in each project, its `[builtin]` words in front of the standard ones.
+/// Built when configs load, as they only change then
+pub struct ProjectWords<'a> {
+    projects: &'a Projects,
+    words: HashMap<&'a Path, Words<'a>>,
+    fallback: Words<'a>,
+}
+
+impl<'a> ProjectWords<'a> {
+    pub fn new(projects: &'a Projects) -> ProjectWords<'a> {
+        let standard = Words::default();
+        let words = projects
+            .iter()
+            .map(|project| {
+                let builtin = &project.config.builtin;
+                let custom: Vec<Word> = builtin
+                    .words
+                    .iter()
+                    .map(|word| word.to_word(&standard))
+                    .collect();
+                (
+                    project.root.as_path(),
+                    Words::with_custom(&custom, builtin.common_extensions),
+                )
+            })
+            .collect();
+        let fallback = Words::with_custom(&[], projects.fallback.builtin.common_extensions);
+        ProjectWords {
+            projects,
+            words,
+            fallback,
+        }
+    }
+
+    /// The words of the project of `file`, a key of the files map
+    pub fn for_file(&self, file: &str) -> &Words<'a> {
+        file_path(file)
+            .and_then(|path| self.projects.project_for_path(&path))
+            .and_then(|project| self.words.get(project.root.as_path()))
+            .unwrap_or(&self.fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::ReferenceScope;
+
+    #[test]
+    fn nearest_project_wins() {
+        let mut projects = Projects::default();
+        projects.insert(PathBuf::from("/ws"), Config::default());
+        let nested = Config::from_toml("[references]\nscope = \"include-graph\"").unwrap();
+        projects.insert(PathBuf::from("/ws/nested"), nested);
+
+        let config = projects.config_for("/ws/main.forth");
+        assert_eq!(ReferenceScope::Workspace, config.references.scope);
+        let config = projects.config_for("file:///ws/nested/lib/a.forth");
+        assert_eq!(ReferenceScope::IncludeGraph, config.references.scope);
+    }
+
+    #[test]
+    fn excludes_relative_to_project_root() {
+        let mut projects = Projects::default();
+        let config = Config::from_toml("[source]\nexclude = [\"build\"]").unwrap();
+        projects.insert(PathBuf::from("/ws"), config);
+        assert!(projects.is_excluded(Path::new("/ws/build/a.forth")));
+        assert!(!projects.is_excluded(Path::new("/ws/src/build/a.forth")));
+        assert!(!projects.is_excluded(Path::new("/other/build/a.forth")));
+    }
+
+    #[test]
+    fn reload_reads_config_again() {
+        let root = crate::utils::test_dir("reload");
+        std::fs::create_dir_all(&root).unwrap();
+        let config_file = root.join(crate::config::CONFIG_FILE_NAME);
+        std::fs::write(&config_file, "[color]\nenabled = true").unwrap();
+        let mut projects = Projects::default();
+        projects.add(&root);
+        assert!(projects.config_for_path(&root).color.enabled);
+        std::fs::remove_file(&config_file).unwrap();
+        projects.reload(&root);
+        assert!(!projects.config_for_path(&root).color.enabled);
+    }
+
+    #[test]
+    fn reload_removes_nested_projects_without_config() {
+        let root = crate::utils::test_dir("reload-nested");
+        let nested = root.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(CONFIG_FILE_NAME), "[color]\nenabled = true").unwrap();
+        std::fs::write(nested.join(CONFIG_FILE_NAME), "[color]\nenabled = false").unwrap();
+        let mut projects = Projects::default();
+        projects.add(&root);
+        projects.add_nested(&nested);
+        let file = nested.join("a.forth");
+        assert!(!projects.config_for_path(&file).color.enabled);
+        std::fs::remove_file(nested.join(CONFIG_FILE_NAME)).unwrap();
+        projects.reload(&nested);
+        assert!(projects.get(&nested).is_none());
+        assert!(projects.config_for_path(&file).color.enabled);
+    }
+
+    #[test]
+    fn projects_know_their_own_words() {
+        let mut projects = Projects::default();
+        let config = Config::from_toml("[[builtin.words]]\nname = \"frob\"").unwrap();
+        projects.insert(PathBuf::from("/ws/a"), config);
+        let config = Config::from_toml("[builtin]\ncommon_extensions = false").unwrap();
+        projects.insert(PathBuf::from("/ws/b"), config);
+        let words = ProjectWords::new(&projects);
+        let known = |file: &str, word: &str| {
+            words
+                .for_file(file)
+                .words
+                .iter()
+                .any(|known| known.token.eq_ignore_ascii_case(word))
+        };
+        assert!(known("/ws/a/main.forth", "frob"));
+        assert!(known("file:///ws/a/main.forth", "-rot"));
+        assert!(!known("/ws/b/main.forth", "frob"));
+        assert!(!known("/ws/b/main.forth", "-rot"));
+        assert!(known("/elsewhere/main.forth", "-rot"));
+    }
+
+    #[test]
+    fn outside_projects_uses_defaults() {
+        let mut projects = Projects::default();
+        let config = Config::from_toml("[color]\nenabled = true").unwrap();
+        projects.insert(PathBuf::from("/ws"), config);
+        assert!(projects.config_for("/ws/a.forth").color.enabled);
+        assert!(!projects.config_for("/elsewhere/a.forth").color.enabled);
+    }
+}
@@ -0,0 +1,448 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    baseline::relative_path,
+    definition_index::{Definition, DefinitionIndex, UseSite},
+    utils::display_path,
+    words::Words,
+};
+
+use std::{collections::BTreeMap, path::Path};
+
+use lsp_types::Range;
+
+/// Formats of a persisted code intelligence index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// The protobuf `Index` message of https://github.com/sourcegraph/scip
+    Scip,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "scip" => Some(Format::Scip),
+            _ => None,
+        }
+    }
+}
+
+/// The definitions, references and hover documentation of a workspace, for code hosts to
+/// navigate without a running server
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScipIndex {
+    /// `file://` URI of the workspace root
+    pub project_root: String,
+    pub documents: Vec<Document>,
+    /// Standard words used by the documents, with their documentation
+    pub external_symbols: Vec<SymbolInformation>,
+}
+
+/// A file below the root, with the words in it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Document {
+    /// Relative to the root, with `/` between directories
+    pub relative_path: String,
+    pub occurrences: Vec<Occurrence>,
+    /// The words defined in the file
+    pub symbols: Vec<SymbolInformation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    /// Counting chars, like the index
+    pub range: Range,
+    pub symbol: String,
+    /// Whether this is where the symbol is defined, not a use of it
+    pub definition: bool,
+    /// The whole definition, for definitions
+    pub enclosing_range: Option<Range>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolInformation {
+    pub symbol: String,
+    pub display_name: String,
+    /// Markdown shown on hover
+    pub documentation: Vec<String>,
+}
+
+/// Index the files below `root` with the symbols of words defined in the workspace, named
+/// after their file, and those of the standard words of `data`
+pub fn scip_index(index: &DefinitionIndex, data: &Words, root: &Path) -> ScipIndex {
+    let package = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let graph = index.include_graph();
+    let mut external: BTreeMap<String, SymbolInformation> = BTreeMap::new();
+    let mut documents: Vec<Document> = index
+        .files()
+        .filter_map(|(file, file_index)| {
+            let relative = relative_path(file, root)?;
+            let mut document = Document {
+                relative_path: relative,
+                ..Default::default()
+            };
+            for definition in &file_index.definitions {
+                let symbol = user_symbol(&package, root, file, definition);
+                document.occurrences.push(Occurrence {
+                    range: definition.selection_range,
+                    symbol: symbol.clone(),
+                    definition: true,
+                    enclosing_range: Some(definition.range),
+                });
+                document.symbols.push(SymbolInformation {
+                    symbol,
+                    display_name: definition.name.clone(),
+                    documentation: vec![user_documentation(definition)],
+                });
+            }
+            for reference in &file_index.references {
+                let declaration = file_index
+                    .definitions
+                    .iter()
+                    .any(|definition| definition.selection_range == reference.range);
+                if declaration {
+                    continue;
+                }
+                let order = graph.load_order(file, reference.range.start);
+                let site = UseSite {
+                    file,
+                    position: reference.range.start,
+                    order: &order,
+                };
+                let definitions = index.resolve(&reference.name, site);
+                for (defined_in, definition) in &definitions {
+                    document.occurrences.push(Occurrence {
+                        range: reference.range,
+                        symbol: user_symbol(&package, root, defined_in, definition),
+                        definition: false,
+                        enclosing_range: None,
+                    });
+                }
+                let builtin = data
+                    .words
+                    .iter()
+                    .find(|word| word.token.eq_ignore_ascii_case(&reference.name));
+                if let (true, Some(builtin)) = (definitions.is_empty(), builtin) {
+                    let symbol = standard_symbol(builtin.token);
+                    external
+                        .entry(symbol.clone())
+                        .or_insert_with(|| SymbolInformation {
+                            symbol: symbol.clone(),
+                            display_name: builtin.token.to_string(),
+                            documentation: vec![builtin.documentation()],
+                        });
+                    document.occurrences.push(Occurrence {
+                        range: reference.range,
+                        symbol,
+                        definition: false,
+                        enclosing_range: None,
+                    });
+                }
+            }
+            document
+                .occurrences
+                .sort_by_key(|occurrence| occurrence.range.start);
+            Some(document)
+        })
+        .collect();
+    documents.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    ScipIndex {
+        project_root: format!("file://{}", root.display()),
+        documents,
+        external_symbols: external.into_values().collect(),
+    }
+}
+
+/// `scip-forth . <package> . path/to/file.fs/NAME.`, with the path relative to the root
+/// when the file is below it
+fn user_symbol(package: &str, root: &Path, file: &str, definition: &Definition) -> String {
+    let path = relative_path(file, root).unwrap_or_else(|| display_path(file, None));
+    let namespaces: String = path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("{}/", descriptor(part)))
+        .collect();
+    format!(
+        "scip-forth . {} . {}{}.",
+        package_name(package),
+        namespaces,
+        descriptor(&definition.name.to_uppercase())
+    )
+}
+
+/// `scip-forth . forth-standard . NAME.`
+fn standard_symbol(name: &str) -> String {
+    format!(
+        "scip-forth . forth-standard . {}.",
+        descriptor(&name.to_uppercase())
+    )
+}
+
+/// `name` as a SCIP descriptor name, in backticks unless it is a simple identifier
+fn descriptor(name: &str) -> String {
+    let simple = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-' | '$'));
+    if simple {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+/// `name` as the package of a symbol, `.` when there is none
+fn package_name(name: &str) -> String {
+    if name.is_empty() {
+        ".".to_string()
+    } else {
+        name.replace(' ', "  ")
+    }
+}
+
+fn user_documentation(definition: &Definition) -> String {
+    let mut ret = format!("# `{}`", definition.name);
+    if let Some(doc) = &definition.doc {
+        ret.push_str(&format!("\n\n{}", doc));
+    }
+    if let Some(constant) = &definition.constant {
+        ret.push_str(&format!("\n\nConstant `{}`", constant));
+    }
+    ret
+}
+
+/// Protobuf fields of the SCIP schema
+mod field {
+    pub const INDEX_METADATA: u32 = 1;
+    pub const INDEX_DOCUMENTS: u32 = 2;
+    pub const INDEX_EXTERNAL_SYMBOLS: u32 = 3;
+    pub const METADATA_TOOL_INFO: u32 = 2;
+    pub const METADATA_PROJECT_ROOT: u32 = 3;
+    pub const METADATA_TEXT_DOCUMENT_ENCODING: u32 = 4;
+    pub const TOOL_INFO_NAME: u32 = 1;
+    pub const TOOL_INFO_VERSION: u32 = 2;
+    pub const DOCUMENT_RELATIVE_PATH: u32 = 1;
+    pub const DOCUMENT_OCCURRENCES: u32 = 2;
+    pub const DOCUMENT_SYMBOLS: u32 = 3;
+    pub const DOCUMENT_LANGUAGE: u32 = 4;
+    pub const DOCUMENT_POSITION_ENCODING: u32 = 6;
+    pub const OCCURRENCE_RANGE: u32 = 1;
+    pub const OCCURRENCE_SYMBOL: u32 = 2;
+    pub const OCCURRENCE_SYMBOL_ROLES: u32 = 3;
+    pub const OCCURRENCE_ENCLOSING_RANGE: u32 = 7;
+    pub const SYMBOL_SYMBOL: u32 = 1;
+    pub const SYMBOL_DOCUMENTATION: u32 = 3;
+    pub const SYMBOL_DISPLAY_NAME: u32 = 6;
+}
+
+/// `TextEncoding.UTF8`
+const UTF8_TEXT: i32 = 1;
+/// `PositionEncoding.UTF32CodeUnitOffsetFromLineStart`, chars like our ranges
+const UTF32_POSITIONS: i32 = 3;
+/// `SymbolRole.Definition`
+const DEFINITION_ROLE: i32 = 1;
+
+impl ScipIndex {
+    /// The protobuf encoding of the index, tagged with `tool_version`
+    pub fn encode(&self, tool_version: &str) -> Vec<u8> {
+        let mut tool_info = Proto::default();
+        tool_info.string(field::TOOL_INFO_NAME, "forth-lsp");
+        tool_info.string(field::TOOL_INFO_VERSION, tool_version);
+        let mut metadata = Proto::default();
+        metadata.message(field::METADATA_TOOL_INFO, tool_info);
+        metadata.string(field::METADATA_PROJECT_ROOT, &self.project_root);
+        metadata.int32(field::METADATA_TEXT_DOCUMENT_ENCODING, UTF8_TEXT);
+        let mut ret = Proto::default();
+        ret.message(field::INDEX_METADATA, metadata);
+        for document in &self.documents {
+            let mut message = Proto::default();
+            message.string(field::DOCUMENT_RELATIVE_PATH, &document.relative_path);
+            for occurrence in &document.occurrences {
+                let mut encoded = Proto::default();
+                encoded.packed_int32(field::OCCURRENCE_RANGE, &scip_range(occurrence.range));
+                encoded.string(field::OCCURRENCE_SYMBOL, &occurrence.symbol);
+                if occurrence.definition {
+                    encoded.int32(field::OCCURRENCE_SYMBOL_ROLES, DEFINITION_ROLE);
+                }
+                if let Some(range) = occurrence.enclosing_range {
+                    encoded.packed_int32(field::OCCURRENCE_ENCLOSING_RANGE, &scip_range(range));
+                }
+                message.message(field::DOCUMENT_OCCURRENCES, encoded);
+            }
+            for symbol in &document.symbols {
+                message.message(field::DOCUMENT_SYMBOLS, symbol.encode());
+            }
+            message.string(field::DOCUMENT_LANGUAGE, "forth");
+            message.int32(field::DOCUMENT_POSITION_ENCODING, UTF32_POSITIONS);
+            ret.message(field::INDEX_DOCUMENTS, message);
+        }
+        for symbol in &self.external_symbols {
+            ret.message(field::INDEX_EXTERNAL_SYMBOLS, symbol.encode());
+        }
+        ret.0
+    }
+}
+
+impl SymbolInformation {
+    fn encode(&self) -> Proto {
+        let mut ret = Proto::default();
+        ret.string(field::SYMBOL_SYMBOL, &self.symbol);
+        for documentation in &self.documentation {
+            ret.string(field::SYMBOL_DOCUMENTATION, documentation);
+        }
+        ret.string(field::SYMBOL_DISPLAY_NAME, &self.display_name);
+        ret
+    }
+}
+
+/// `[line, character, end character]` on one line, `[line, character, end line, end
+/// character]` otherwise
+fn scip_range(range: Range) -> Vec<i32> {
+    let (start, end) = (range.start, range.end);
+    if start.line == end.line {
+        vec![
+            start.line as i32,
+            start.character as i32,
+            end.character as i32,
+        ]
+    } else {
+        vec![
+            start.line as i32,
+            start.character as i32,
+            end.line as i32,
+            end.character as i32,
+        ]
+    }
+}
+
+/// A protobuf message being written, fields left at their default are not written
+#[derive(Debug, Default)]
+struct Proto(Vec<u8>);
+
+impl Proto {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn int32(&mut self, field: u32, value: i32) {
+        if value != 0 {
+            self.key(field, 0);
+            // negative numbers take ten bytes, sign extended
+            self.varint(value as i64 as u64);
+        }
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.key(field, 2);
+        self.varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+    }
+
+    fn message(&mut self, field: u32, message: Proto) {
+        self.bytes(field, &message.0);
+    }
+
+    fn packed_int32(&mut self, field: u32, values: &[i32]) {
+        let mut packed = Proto::default();
+        for value in values {
+            packed.varint(*value as i64 as u64);
+        }
+        self.bytes(field, &packed.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use lsp_types::Position;
+    use ropey::Rope;
+
+    #[test]
+    fn protobuf_wire_format() {
+        let mut proto = Proto::default();
+        proto.int32(1, 150);
+        proto.string(2, "testing");
+        proto.packed_int32(4, &[3, 270, 86942]);
+        proto.int32(5, 0);
+        assert_eq!(
+            vec![
+                0x08, 0x96, 0x01, 0x12, 0x07, b't', b'e', b's', b't', b'i', b'n', b'g', 0x22, 0x06,
+                0x03, 0x8e, 0x02, 0x9e, 0xa7, 0x05
+            ],
+            proto.0
+        );
+    }
+
+    #[test]
+    fn definitions_uses_and_standard_words() {
+        let root = Path::new("/ws/app");
+        let files = HashMap::from([
+            (
+                "/ws/app/lib/math.fs".to_string(),
+                Rope::from_str("\\ Square it.\n: sq dup * ;"),
+            ),
+            (
+                "/ws/app/main.fs".to_string(),
+                Rope::from_str("include lib/math.fs\n3 sq ."),
+            ),
+            ("/ws/vendor/x.fs".to_string(), Rope::from_str(": x ;")),
+        ]);
+        let mut index = DefinitionIndex::default();
+        for (file, rope) in &files {
+            index.update_file(file, rope);
+        }
+        let scip = scip_index(&index, &Words::default(), root);
+        let paths: Vec<&str> = scip
+            .documents
+            .iter()
+            .map(|document| document.relative_path.as_str())
+            .collect();
+        assert_eq!(vec!["lib/math.fs", "main.fs"], paths);
+        let sq = "scip-forth . app . lib/`math.fs`/SQ.";
+        let math = &scip.documents[0];
+        assert_eq!(sq, math.symbols[0].symbol);
+        assert!(math.occurrences[0].definition);
+        assert_eq!(Position::new(1, 2), math.occurrences[0].range.start);
+        let uses: Vec<(u32, &str)> = scip.documents[1]
+            .occurrences
+            .iter()
+            .map(|occurrence| (occurrence.range.start.character, occurrence.symbol.as_str()))
+            .collect();
+        assert!(uses.contains(&(2, sq)));
+        assert!(uses.contains(&(5, "scip-forth . forth-standard . `.`.")));
+        let external: Vec<&str> = scip
+            .external_symbols
+            .iter()
+            .map(|symbol| symbol.symbol.as_str())
+            .collect();
+        assert!(external.contains(&"scip-forth . forth-standard . DUP."));
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            scip_range(Range::new(Position::new(1, 2), Position::new(3, 4)))
+        );
+        assert_eq!(
+            vec![1, 2, 4],
+            scip_range(Range::new(Position::new(1, 2), Position::new(1, 4)))
+        );
+    }
+}
@@ -0,0 +1,670 @@
+use crate::config::CONFIG_FILE_NAME;
+use crate::definition_index::{DefinitionIndex, FileIndex};
+use crate::diagnostics::publish_all_diagnostics;
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::project::{ProjectWords, Projects};
+use crate::utils::duplicate_keys;
+use crate::utils::handlers::notification_did_change::handle_did_change_text_document;
+use crate::utils::handlers::notification_did_change_watched_files::{
+    handle_did_change_watched_files, register_config_watcher, report_config_problems,
+};
+use crate::utils::handlers::notification_did_close::handle_did_close_text_document;
+use crate::utils::handlers::notification_did_open::handle_did_open_text_document;
+use crate::utils::handlers::notification_did_save::handle_did_save_text_document;
+use crate::utils::handlers::notification_set_trace::handle_set_trace;
+use crate::utils::handlers::request_code_action::handle_code_action;
+use crate::utils::handlers::request_color_presentation::handle_color_presentation;
+use crate::utils::handlers::request_completion::handle_completion;
+use crate::utils::handlers::request_debug_tokens::handle_debug_tokens;
+use crate::utils::handlers::request_document_color::handle_document_color;
+use crate::utils::handlers::request_document_symbol::handle_document_symbol;
+use crate::utils::handlers::request_execute_command::handle_execute_command;
+use crate::utils::handlers::request_folding_range::handle_folding_range;
+use crate::utils::handlers::request_formatting::handle_formatting;
+use crate::utils::handlers::request_goto_definition::handle_goto_definition;
+use crate::utils::handlers::request_hover::handle_hover;
+use crate::utils::handlers::request_inlay_hint::handle_inlay_hint;
+use crate::utils::handlers::request_linked_editing_range::handle_linked_editing_range;
+use crate::utils::handlers::request_moniker::handle_moniker;
+use crate::utils::handlers::request_prepare_rename::handle_prepare_rename;
+use crate::utils::handlers::request_reference_count::handle_reference_count;
+use crate::utils::handlers::request_references::handle_references;
+use crate::utils::handlers::request_rename::handle_rename;
+use crate::utils::handlers::request_semantic_tokens::handle_semantic_tokens_full;
+use crate::utils::handlers::request_stack_effect::handle_stack_effect;
+use crate::utils::handlers::request_will_save_wait_until::handle_will_save_wait_until;
+use crate::utils::handlers::request_workspace_symbol::handle_workspace_symbol;
+use crate::utils::outgoing::Outgoing;
+use crate::utils::position_encoding::PositionEncoding;
+use crate::utils::trace::log_trace;
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use ignore::{WalkBuilder, WalkState};
+use lsp_server::{Connection, ErrorCode, Message, Response};
+use lsp_types::{
+    notification::{DidChangeWatchedFiles, Exit, Initialized, Notification as _},
+    request::{Initialize, Request as _, Shutdown},
+    ClientCapabilities, InitializeParams, TraceValue,
+};
+use rayon::prelude::*;
+
+use ropey::Rope;
+
+/// Answer the `initialize` request with `capabilities` and return its params. Other requests
+/// are refused as not initialized and notifications dropped, `None` when the client exits
+pub fn initialize(
+    connection: &Connection,
+    mut capabilities: serde_json::Value,
+) -> Result<Option<serde_json::Value>> {
+    for msg in &connection.receiver {
+        match msg {
+            Message::Request(request) if request.method == Initialize::METHOD => {
+                let client: ClientCapabilities =
+                    serde_json::from_value(request.params["capabilities"].clone())
+                        .unwrap_or_default();
+                capabilities["positionEncoding"] =
+                    serde_json::to_value(PositionEncoding::negotiate(&client).kind())?;
+                let result = serde_json::json!({
+                    "capabilities": capabilities,
+                    "serverInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                });
+                connection
+                    .sender
+                    .send(Message::Response(Response::new_ok(request.id, result)))
+                    .map_err(|err| Error::SendError(err.to_string()))?;
+                return Ok(Some(request.params));
+            }
+            Message::Request(request) => connection
+                .sender
+                .send(Message::Response(Response::new_err(
+                    request.id,
+                    ErrorCode::ServerNotInitialized as i32,
+                    format!("Expected initialize, got {}", request.method),
+                )))
+                .map_err(|err| Error::SendError(err.to_string()))?,
+            Message::Notification(notification) if notification.method == Exit::METHOD => {
+                return Ok(None);
+            }
+            Message::Notification(_) | Message::Response(_) => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Serve `connection` until the client exits, `params` are the initialize params. Exiting
+/// without a `shutdown` request first is a failure
+pub fn main_loop(connection: Connection, params: serde_json::Value) -> Result<ExitCode> {
+    eprintln!("Starting main loop");
+    let init: InitializeParams = serde_json::from_value(params)?;
+    let encoding = PositionEncoding::negotiate(&init.capabilities);
+    let mut files = HashMap::<String, Rope>::new();
+    let mut index = DefinitionIndex::default();
+    let mut versions = HashMap::<String, i32>::new();
+    // documents whose changes could not be applied, until the client sends their whole text
+    let mut out_of_sync = HashSet::<String>::new();
+    let mut projects = Projects::default();
+    let started = Instant::now();
+    if let Some(roots) = init.workspace_folders {
+        eprintln!("Root: {:?}", roots);
+        for root in roots {
+            projects.add(Path::new(root.uri.path()));
+            load_dir(root.uri.path(), &mut files, &mut index, &mut projects)?;
+        }
+    } else if let Some(root) = init.root_uri {
+        projects.add(Path::new(root.path()));
+    }
+    load_include_paths(&mut files, &mut index, &mut projects)?;
+    index.record_index(started.elapsed());
+    eprintln!(
+        "Indexed {} files in {:?}",
+        files.len(),
+        index.stats().last_index
+    );
+    for project in projects.iter() {
+        report_config_problems(&connection, project, false)?;
+    }
+    let mut outgoing = Outgoing::new(&init.capabilities);
+    register_config_watcher(&connection, &mut outgoing, &init.capabilities)?;
+    let mut words = ProjectWords::new(&projects);
+    let mut trace = init.trace.unwrap_or(TraceValue::Off);
+    let mut shut_down = false;
+    for msg in &connection.receiver {
+        match msg {
+            Message::Request(request) => {
+                if shut_down || request.method == Shutdown::METHOD {
+                    let response = if shut_down {
+                        Response::new_err(
+                            request.id,
+                            ErrorCode::InvalidRequest as i32,
+                            format!("Got {} after shutdown", request.method),
+                        )
+                    } else {
+                        shut_down = true;
+                        Response::new_ok(request.id, ())
+                    };
+                    connection
+                        .sender
+                        .send(Message::Response(response))
+                        .map_err(|err| Error::SendError(err.to_string()))?;
+                    continue;
+                }
+                let started = Instant::now();
+                log_trace(
+                    &connection,
+                    trace,
+                    || format!("Received request '{} - ({})'", request.method, request.id),
+                    || format!("Params: {}", request.params),
+                )?;
+                let handled = 'handled: {
+                    if let Some(result) = claimed(handle_hover(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &mut index,
+                        &init.capabilities,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_completion(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &index,
+                        &init.capabilities,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_goto_definition(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &mut index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_document_color(
+                        &request,
+                        &connection,
+                        &projects,
+                        &mut files,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) =
+                        claimed(handle_color_presentation(&request, &connection, &projects))
+                    {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) =
+                        claimed(handle_inlay_hint(&request, &connection, &files, encoding))
+                    {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_linked_editing_range(
+                        &request,
+                        &connection,
+                        &mut files,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_prepare_rename(
+                        &request,
+                        &connection,
+                        &mut files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_rename(
+                        &request,
+                        &connection,
+                        &projects,
+                        &mut files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_references(
+                        &request,
+                        &connection,
+                        &projects,
+                        &mut files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_moniker(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) =
+                        claimed(handle_reference_count(&request, &connection, &index))
+                    {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) =
+                        claimed(handle_stack_effect(&request, &connection, &words, &files))
+                    {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) =
+                        claimed(handle_debug_tokens(&request, &connection, &files, encoding))
+                    {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_document_symbol(
+                        &request,
+                        &connection,
+                        &mut files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_workspace_symbol(
+                        &request,
+                        &connection,
+                        &files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) =
+                        claimed(handle_folding_range(&request, &connection, &files))
+                    {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_semantic_tokens_full(
+                        &request,
+                        &connection,
+                        &files,
+                        &index,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_formatting(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_will_save_wait_until(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &files,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_code_action(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &versions,
+                        &index,
+                        &init.capabilities,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_execute_command(
+                        &request,
+                        &connection,
+                        &projects,
+                        &words,
+                        &files,
+                        &index,
+                        &mut outgoing,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    None
+                };
+                let response = match handled {
+                    Some(Ok(())) => {
+                        log_trace(
+                            &connection,
+                            trace,
+                            || {
+                                format!(
+                                    "Sending response '{} - ({})'. Processing request took {}ms",
+                                    request.method,
+                                    request.id,
+                                    started.elapsed().as_millis()
+                                )
+                            },
+                            String::new,
+                        )?;
+                        continue;
+                    }
+                    // failed handlers still owe the client a response
+                    Some(Err(err)) => {
+                        let err = Error::Request {
+                            method: request.method,
+                            id: request.id.clone(),
+                            source: Box::new(err),
+                        };
+                        eprintln!("{}", err.report());
+                        Response {
+                            id: request.id,
+                            result: None,
+                            error: Some(err.into()),
+                        }
+                    }
+                    None => {
+                        eprintln!("Unhandled request {}", request.method);
+                        Response::new_err(
+                            request.id,
+                            ErrorCode::MethodNotFound as i32,
+                            format!("Unknown method {}", request.method),
+                        )
+                    }
+                };
+                connection
+                    .sender
+                    .send(Message::Response(response))
+                    .map_err(|err| Error::SendError(err.to_string()))?;
+            }
+            Message::Response(resp) => {
+                log_trace(
+                    &connection,
+                    trace,
+                    || format!("Received response '{}'", resp.id),
+                    || format!("{:?}", resp),
+                )?;
+                if outgoing.complete(&resp).is_none() {
+                    eprintln!("Response to unknown request {}", resp.id);
+                }
+            }
+            Message::Notification(notification) => {
+                log_trace(
+                    &connection,
+                    trace,
+                    || format!("Received notification '{}'", notification.method),
+                    || format!("Params: {}", notification.params),
+                )?;
+                if notification.method == Exit::METHOD {
+                    return Ok(if shut_down {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::FAILURE
+                    });
+                }
+                if shut_down || notification.method == Initialized::METHOD {
+                    continue;
+                }
+                let handled = 'handled: {
+                    if let Some(result) = claimed(handle_set_trace(&notification, &mut trace)) {
+                        break 'handled Some(result);
+                    }
+                    if notification.method == DidChangeWatchedFiles::METHOD {
+                        let reloaded = handle_did_change_watched_files(
+                            &notification,
+                            &connection,
+                            &mut projects,
+                        );
+                        // the words come from the configs, which are only read here
+                        words = ProjectWords::new(&projects);
+                        match reloaded {
+                            Ok(true) => {
+                                publish_all_diagnostics(
+                                    &connection,
+                                    &projects,
+                                    &words,
+                                    &files,
+                                    &index,
+                                    &versions,
+                                    encoding,
+                                )?;
+                                break 'handled Some(Ok(()));
+                            }
+                            Ok(false) => break 'handled Some(Ok(())),
+                            Err(err) => break 'handled Some(Err(err)),
+                        }
+                    }
+                    if let Some(result) = claimed(handle_did_open_text_document(
+                        &notification,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &mut index,
+                        &mut versions,
+                        &mut out_of_sync,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_did_change_text_document(
+                        &notification,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &mut index,
+                        &mut versions,
+                        &mut out_of_sync,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_did_save_text_document(
+                        &notification,
+                        &connection,
+                        &mut outgoing,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &mut index,
+                        &versions,
+                        &mut out_of_sync,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    if let Some(result) = claimed(handle_did_close_text_document(
+                        &notification,
+                        &connection,
+                        &projects,
+                        &words,
+                        &mut files,
+                        &mut index,
+                        &mut versions,
+                        &mut out_of_sync,
+                        encoding,
+                    )) {
+                        break 'handled Some(result);
+                    }
+                    None
+                };
+                if let Some(Err(err)) = handled {
+                    eprintln!("{}: {}", notification.method, err.report());
+                }
+                debug_assert!(
+                    duplicate_keys(files.keys()).is_empty(),
+                    "Files loaded under more than one key: {:?}",
+                    duplicate_keys(files.keys())
+                );
+            }
+        }
+    }
+    // the client went away without exiting
+    Ok(ExitCode::FAILURE)
+}
+
+/// The outcome of a handler that took the message, None when the message is for another
+/// handler
+fn claimed(result: Result<()>) -> Option<Result<()>> {
+    match result {
+        Err(Error::ExtractRequestError(_) | Error::ExtractNotificationError(_)) => None,
+        result => Some(result),
+    }
+}
+
+/// Index the sources under `root` and its include paths, the way a workspace folder is
+pub fn load_workspace(root: &Path) -> Result<(HashMap<String, Rope>, DefinitionIndex)> {
+    let mut files = HashMap::new();
+    let mut index = DefinitionIndex::default();
+    let mut projects = Projects::default();
+    projects.add(root);
+    let entry = root
+        .to_str()
+        .ok_or_else(|| Error::NoSuchFile(root.display().to_string()))?;
+    load_dir(entry, &mut files, &mut index, &mut projects)?;
+    load_include_paths(&mut files, &mut index, &mut projects)?;
+    Ok((files, index))
+}
+
+fn load_include_paths(
+    files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    projects: &mut Projects,
+) -> Result<()> {
+    let include_paths: Vec<PathBuf> = projects
+        .iter()
+        .flat_map(|project| project.config.source.resolve_include_paths(&project.root))
+        .collect();
+    for include_path in include_paths {
+        if include_path.is_dir() {
+            if let Some(entry) = include_path.to_str() {
+                load_dir(entry, files, index, projects)?;
+            }
+        } else {
+            load_file(&include_path, files, index)?;
+        }
+    }
+    Ok(())
+}
+
+fn load_dir(
+    root: &str, //lsp_types::WorkspaceFolder,
+    files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    projects: &mut Projects,
+) -> Result<()> {
+    let gitignore = projects.config_for_path(Path::new(root)).source.gitignore;
+    let project_dirs = Mutex::new(vec![]);
+    let candidates = Mutex::new(vec![]);
+    WalkBuilder::new(root)
+        .git_ignore(gitignore)
+        .git_global(gitignore)
+        .git_exclude(gitignore)
+        .ignore(gitignore)
+        .require_git(false)
+        .build_parallel()
+        .run(|| {
+            Box::new(|entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) => {
+                        eprintln!("Failed to walk {}: {}", root, err);
+                        return WalkState::Continue;
+                    }
+                };
+                let path = entry.into_path();
+                if path.is_dir() {
+                    // nested projects bring their own config
+                    if path.join(CONFIG_FILE_NAME).is_file() {
+                        project_dirs.lock().expect("Walker panicked").push(path);
+                    }
+                } else {
+                    candidates.lock().expect("Walker panicked").push(path);
+                }
+                WalkState::Continue
+            })
+        });
+    for dir in project_dirs.into_inner().expect("Walker panicked") {
+        projects.add_nested(&dir);
+    }
+    let sources: Vec<PathBuf> = candidates
+        .into_inner()
+        .expect("Walker panicked")
+        .into_iter()
+        .filter(|path| {
+            let extensions = &projects.config_for_path(path).source.extensions;
+            path.extension()
+                .and_then(OsStr::to_str)
+                .is_some_and(|extension| extensions.iter().any(|ext| ext == extension))
+                && !projects.is_excluded(path)
+        })
+        .collect();
+    let loaded: Vec<(String, Rope, FileIndex)> = sources
+        .par_iter()
+        .filter_map(|path| match read_file(path) {
+            Ok(loaded) => Some(loaded),
+            Err(err) => {
+                eprintln!("Failed to load {}: {}", path.display(), err);
+                None
+            }
+        })
+        .collect();
+    for (entry, rope, file_index) in loaded {
+        index.insert(entry.clone(), file_index);
+        files.insert(entry, rope);
+    }
+    Ok(())
+}
+
+fn load_file(
+    path: &Path,
+    files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+) -> Result<()> {
+    let (entry, rope, file_index) = read_file(path)?;
+    index.insert(entry.clone(), file_index);
+    files.insert(entry, rope);
+    Ok(())
+}
+
+fn read_file(path: &Path) -> Result<(String, Rope, FileIndex)> {
+    let entry = path.to_string_lossy().to_string();
+    eprintln!("FORTH load {}", entry);
+    let raw_content = fs::read(path)?;
+    let content = String::from_utf8_lossy(&raw_content);
+    let rope = Rope::from_str(&content);
+    let file_index = FileIndex::from_rope(&rope);
+    Ok((entry, rope, file_index))
+}
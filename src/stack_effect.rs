@@ -0,0 +1,256 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::{Program, CLOSERS, MIDDLES, OPENERS},
+    diagnostics::is_number,
+    words::Words,
+};
+
+use std::collections::HashMap;
+
+use forth_lexer::{context::ContextTokens, parser::Lexer, token::Token};
+use ropey::Rope;
+
+/// Cells a stack comment like `( x1 x2 -- x3 )` takes and leaves. None for comments with
+/// alternatives, unknown depths or other stacks like `R:` and `F:`
+pub fn parse(comment: &str) -> Option<(usize, usize)> {
+    let inner = comment.strip_prefix('(')?.strip_suffix(')')?;
+    let (before, after) = inner.split_once("--")?;
+    if after.contains("--") {
+        return None;
+    }
+    Some((cells(before)?, cells(after)?))
+}
+
+fn cells(items: &str) -> Option<usize> {
+    // `n1 | u1 n2 | u2` lists types a cell may have, `0 | x x` different stacks
+    let alternatives: Vec<Vec<&str>> = items
+        .split('|')
+        .map(|items| items.split_whitespace().collect())
+        .collect();
+    if alternatives.len() > 1 {
+        let last = alternatives.len() - 1;
+        let types = alternatives
+            .iter()
+            .enumerate()
+            .all(|(ix, items)| items.len() == if ix == 0 || ix == last { 1 } else { 2 });
+        if !types {
+            return None;
+        }
+    }
+    // the first item after `|` is another type of the cell before it
+    let items = alternatives
+        .iter()
+        .enumerate()
+        .flat_map(|(ix, items)| items.iter().skip(if ix == 0 { 0 } else { 1 }));
+    let mut ret = 0;
+    for item in items {
+        let lower = item.to_lowercase();
+        if lower.ends_with(':') || lower.contains('*') || lower.contains("...") {
+            return None;
+        }
+        // double cell numbers are called d, ud and xd, optionally numbered
+        let name = lower.trim_end_matches(|chr: char| chr.is_ascii_digit());
+        ret += if matches!(name, "d" | "ud" | "xd") {
+            2
+        } else {
+            1
+        };
+    }
+    Some(ret)
+}
+
+/// Stack comments of the builtin words and of colon definitions in `files`, keyed by upper
+/// cased word
+pub fn known_effects(data: &Words, files: &HashMap<String, Rope>) -> HashMap<String, String> {
+    let mut known: HashMap<String, String> = data
+        .words
+        .iter()
+        .rev()
+        .map(|word| (word.token.to_uppercase(), word.stack.to_string()))
+        .collect();
+    for rope in files.values() {
+        let progn = rope.to_string();
+        let mut lexer = Lexer::new(progn.as_str());
+        let tokens = lexer.parse();
+        for definition in Program::parse(&tokens).definitions() {
+            if let (Some(name), Some(stack)) = (definition.name, definition.stack_comment()) {
+                known.insert(name.value.to_uppercase(), stack.value.to_string());
+            }
+        }
+    }
+    known
+}
+
+/// A word or number of straight-line code and what it does to the stack
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub word: String,
+    pub taken: usize,
+    pub left: usize,
+    /// Cells on the stack after this step, relative to before the code ran
+    pub depth: isize,
+}
+
+/// Effect of a known, non branching word
+fn word_effect(word: &str, known: &HashMap<String, String>) -> Option<(usize, usize)> {
+    let word = word.to_uppercase();
+    let branches = [OPENERS.as_slice(), &MIDDLES, &CLOSERS];
+    if branches.iter().any(|words| words.contains(&word.as_str())) {
+        return None;
+    }
+    known.get(&word).and_then(|comment| parse(comment))
+}
+
+/// Steps of `tokens` from the stack comments in `known`, keyed by upper cased word. Stops
+/// at the first word that is unknown or branches, returned as well. `['] x CATCH` takes
+/// what `x` takes and leaves what it leaves plus the THROW code
+pub fn trace<'a>(
+    tokens: &[Token<'a>],
+    known: &HashMap<String, String>,
+) -> (Vec<Step>, Option<Token<'a>>) {
+    let code: Vec<Token<'a>> = ContextTokens::new(tokens.iter().cloned())
+        .filter(|context| !context.in_string && !context.in_comment)
+        .map(|context| context.token)
+        .collect();
+    let mut steps = vec![];
+    let mut depth: isize = 0;
+    let mut ix = 0;
+    while ix < code.len() {
+        let token = &code[ix];
+        ix += 1;
+        let caught = match (token, code.get(ix), code.get(ix + 1)) {
+            (Token::Word(tick), Some(Token::Word(name)), Some(Token::Word(catch)))
+                if matches!(tick.value, "'" | "[']")
+                    && catch.value.eq_ignore_ascii_case("CATCH") =>
+            {
+                word_effect(name.value, known).map(|effect| (tick.value, catch.value, effect))
+            }
+            _ => None,
+        };
+        if let Some((tick, catch, (taken, left))) = caught {
+            ix += 2;
+            for (word, taken, left) in [(tick, 0, 1), (catch, taken + 1, left + 1)] {
+                depth += left as isize - taken as isize;
+                steps.push(Step {
+                    word: word.to_string(),
+                    taken,
+                    left,
+                    depth,
+                });
+            }
+            continue;
+        }
+        let (word, (taken, left)) = match token {
+            Token::Number(data) => (data.value, (0, 1)),
+            Token::Word(data) if is_number(data.value) => (data.value, (0, 1)),
+            Token::Word(data) => match word_effect(data.value, known) {
+                Some(effect) => (data.value, effect),
+                None => return (steps, Some(token.clone())),
+            },
+            _ => continue,
+        };
+        depth += left as isize - taken as isize;
+        steps.push(Step {
+            word: word.to_string(),
+            taken,
+            left,
+            depth,
+        });
+    }
+    (steps, None)
+}
+
+/// Cells `tokens` take and leave when run in order, from the stack comments in `known`,
+/// keyed by upper cased word. None when a word is unknown or the code branches
+pub fn infer(tokens: &[Token], known: &HashMap<String, String>) -> Option<(usize, usize)> {
+    let (steps, None) = trace(tokens, known) else {
+        return None;
+    };
+    Some(effect(&steps))
+}
+
+/// Cells `steps` take and leave together
+pub fn effect(steps: &[Step]) -> (usize, usize) {
+    let mut lowest: isize = 0;
+    let mut depth: isize = 0;
+    for step in steps {
+        lowest = lowest.min(depth - step.taken as isize);
+        depth = step.depth;
+    }
+    (-lowest as usize, (depth - lowest) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forth_lexer::parser::Lexer;
+
+    #[test]
+    fn parses_stack_comments() {
+        assert_eq!(Some((2, 0)), parse("( x a-addr -- )"));
+        assert_eq!(Some((2, 2)), parse("( xd -- c-addr u )"));
+        assert_eq!(Some((0, 0)), parse("( -- )"));
+        assert_eq!(Some((2, 1)), parse("( n1 | u1 n2 | u2 -- n3 | u3 )"));
+        assert_eq!(None, parse("( x -- 0 | x x )"));
+        assert_eq!(None, parse("( x -- ) ( R: -- x )"));
+        assert_eq!(None, parse("( i*x -- j*x )"));
+    }
+
+    #[test]
+    fn infers_straight_line_code() {
+        let known = HashMap::from([
+            ("DUP".to_string(), "( x -- x x )".to_string()),
+            ("*".to_string(), "( n1 n2 -- n3 )".to_string()),
+            ("+".to_string(), "( n1 n2 -- n3 )".to_string()),
+            (".\"".to_string(), "( -- )".to_string()),
+        ]);
+        let infer = |source: &str| {
+            let mut lexer = Lexer::new(source);
+            infer(&lexer.parse(), &known)
+        };
+        assert_eq!(Some((1, 1)), infer("dup * ( square )"));
+        assert_eq!(Some((2, 1)), infer("+ 1 +"));
+        assert_eq!(Some((0, 1)), infer(".\" a b\" 1"));
+        assert_eq!(None, infer("dup if then"));
+        assert_eq!(None, infer("frob"));
+    }
+
+    #[test]
+    fn infers_catch_from_the_caught_word() {
+        let known = HashMap::from([
+            ("DUP".to_string(), "( x -- x x )".to_string()),
+            ("*".to_string(), "( n1 n2 -- n3 )".to_string()),
+            (
+                "CATCH".to_string(),
+                "( i * x xt -- j * x 0 | i * x n )".to_string(),
+            ),
+        ]);
+        let infer = |source: &str| {
+            let mut lexer = Lexer::new(source);
+            infer(&lexer.parse(), &known)
+        };
+        assert_eq!(Some((2, 2)), infer("['] * catch"));
+        assert_eq!(Some((1, 4)), infer("dup ['] dup CATCH"));
+        assert_eq!(None, infer("['] frob catch"));
+        assert_eq!(None, infer("catch"));
+    }
+
+    #[test]
+    fn traces_until_an_unknown_word() {
+        let known = HashMap::from([
+            ("DUP".to_string(), "( x -- x x )".to_string()),
+            ("+".to_string(), "( n1 n2 -- n3 )".to_string()),
+        ]);
+        let mut lexer = Lexer::new("dup + 2 frob dup");
+        let tokens = lexer.parse();
+        let (steps, stop) = trace(&tokens, &known);
+        let depths: Vec<(&str, isize)> = steps
+            .iter()
+            .map(|step| (step.word.as_str(), step.depth))
+            .collect();
+        assert_eq!(vec![("dup", 1), ("+", 0), ("2", 1)], depths);
+        assert_eq!((1, 2), effect(&steps));
+        assert_eq!(Some("frob"), stop.map(|token| token.get_data().value));
+    }
+}
@@ -0,0 +1,167 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use forth_lexer::token::{Data, Token};
+
+/// Field defining words of Forth 2012 with the size of the field they add, `+FIELD`
+/// takes its size from the stack
+pub const FIELD_WORDS: [(&str, &str); 7] = [
+    ("FIELD:", "1 CELLS"),
+    ("CFIELD:", "1 CHARS"),
+    ("2FIELD:", "2 CELLS"),
+    ("FFIELD:", "1 FLOATS"),
+    ("SFFIELD:", "1 SFLOATS"),
+    ("DFFIELD:", "1 DFLOATS"),
+    ("+FIELD", ""),
+];
+
+/// `BEGIN-STRUCTURE name ... END-STRUCTURE`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure<'a> {
+    pub begin: Data<'a>,
+    pub name: Data<'a>,
+    pub fields: Vec<Field<'a>>,
+    /// `END-STRUCTURE`, missing while the structure is being typed
+    pub end: Option<Data<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field<'a> {
+    /// The first token of the field, its size expression when given on the stack
+    pub start: Data<'a>,
+    pub name: Data<'a>,
+    /// Sizes of the fields before this one, `0` for the first field
+    pub offset: String,
+}
+
+impl Structure<'_> {
+    /// `END-STRUCTURE`, or the last field while the structure is unterminated
+    pub fn last(&self) -> Data<'_> {
+        self.end
+            .or_else(|| self.fields.last().map(|field| field.name))
+            .unwrap_or(self.name)
+    }
+}
+
+/// Every structure in `tokens`, a new `BEGIN-STRUCTURE` closes one left open
+pub fn structures<'a>(tokens: &[Token<'a>]) -> Vec<Structure<'a>> {
+    let mut ret: Vec<Structure> = vec![];
+    let mut open: Option<Structure> = None;
+    let mut sizes: Vec<String> = vec![];
+    // where the size expression of the next `+FIELD` starts
+    let mut expression = 0;
+    let mut ix = 0;
+    while ix < tokens.len() {
+        let (Token::Word(word), next) = (&tokens[ix], tokens.get(ix + 1)) else {
+            ix += 1;
+            continue;
+        };
+        let upper = word.value.to_uppercase();
+        if upper == "BEGIN-STRUCTURE" {
+            if let Some(Token::Word(name)) = next {
+                ret.extend(open.take());
+                open = Some(Structure {
+                    begin: *word,
+                    name: *name,
+                    fields: vec![],
+                    end: None,
+                });
+                sizes.clear();
+                ix += 2;
+                expression = ix;
+                continue;
+            }
+        } else if upper == "END-STRUCTURE" {
+            if let Some(mut structure) = open.take() {
+                structure.end = Some(*word);
+                ret.push(structure);
+            }
+        } else if let (Some(structure), Some(Token::Word(name))) = (open.as_mut(), next) {
+            if let Some((_, size)) = FIELD_WORDS.iter().find(|(field, _)| *field == upper) {
+                let (start, size) = if size.is_empty() {
+                    let expression: Vec<Data> = tokens[expression..ix]
+                        .iter()
+                        .filter(|token| {
+                            !matches!(token, Token::Comment(_) | Token::StackComment(_))
+                        })
+                        .map(|token| *token.get_data())
+                        .collect();
+                    let values: Vec<&str> = expression.iter().map(|data| data.value).collect();
+                    (
+                        expression.first().copied().unwrap_or(*word),
+                        values.join(" "),
+                    )
+                } else {
+                    (*word, size.to_string())
+                };
+                structure.fields.push(Field {
+                    start,
+                    name: *name,
+                    offset: if sizes.is_empty() {
+                        "0".to_string()
+                    } else {
+                        sizes.join(" + ")
+                    },
+                });
+                sizes.push(size);
+                ix += 2;
+                expression = ix;
+                continue;
+            }
+        }
+        ix += 1;
+    }
+    ret.extend(open);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use forth_lexer::parser::Lexer;
+
+    #[test]
+    fn fields_and_offsets() {
+        let mut lexer = Lexer::new(
+            "begin-structure point\n  field: p.x\n  cfield: p.tag\n  2 cells +field p.pad\nend-structure",
+        );
+        let tokens = lexer.parse();
+        let structures = structures(&tokens);
+        assert_eq!(1, structures.len());
+        let point = &structures[0];
+        assert_eq!("point", point.name.value);
+        assert!(point.end.is_some());
+        let fields: Vec<(&str, &str)> = point
+            .fields
+            .iter()
+            .map(|field| (field.name.value, field.offset.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("p.x", "0"),
+                ("p.tag", "1 CELLS"),
+                ("p.pad", "1 CELLS + 1 CHARS")
+            ],
+            fields
+        );
+        assert_eq!("2", point.fields[2].start.value);
+    }
+
+    #[test]
+    fn unterminated_structures() {
+        let mut lexer = Lexer::new("begin-structure a field: a.x\nbegin-structure b field: b.x");
+        let tokens = lexer.parse();
+        let structures = structures(&tokens);
+        assert_eq!(2, structures.len());
+        assert!(structures[0].end.is_none());
+        assert_eq!("a.x", structures[0].last().value);
+        assert_eq!(1, structures[1].fields.len());
+    }
+
+    #[test]
+    fn fields_outside_structures_are_ignored() {
+        let mut lexer = Lexer::new("0 cell +field x");
+        assert!(structures(&lexer.parse()).is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::utils::{data_to_position::ToPosition, position_encoding::data_range};
+
+use forth_lexer::{parser::Lexer, token::Token};
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Range};
+use ropey::Rope;
+
+/// Starts comments directing diagnostics, like `\ forth-lsp: ignore[undefined-word]`
+pub const DIRECTIVE_PREFIX: &str = "forth-lsp:";
+
+/// `Diagnostic::code` of a directive suppressing nothing, or naming an unknown rule
+pub const UNUSED_SUPPRESSION_CODE: &str = "unused-suppression";
+
+/// Lines a directive suppresses diagnostics on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    Line(u32),
+    File,
+}
+
+/// A `\ forth-lsp:` comment suppressing the diagnostics of some rules:
+///
+/// - `allow <rule>...` on the next line
+/// - `ignore[<rule>,...]` on its own line, or on the next when nothing precedes it
+/// - `ignore-file[<rule>,...]` anywhere in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+    /// The comment
+    pub range: Range,
+    pub scope: Scope,
+    pub rules: Vec<String>,
+    /// Whether each of the `rules` suppressed a diagnostic
+    pub used: Vec<bool>,
+}
+
+impl Directive {
+    fn covers(&self, line: u32) -> bool {
+        match self.scope {
+            Scope::Line(covered) => covered == line,
+            Scope::File => true,
+        }
+    }
+}
+
+/// Every directive in the comments of `rope`
+pub fn directives(rope: &Rope) -> Vec<Directive> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let mut ret = vec![];
+    let mut previous_line = None;
+    for token in lexer.parse() {
+        let data = *token.get_data();
+        let line = data.to_position_start(rope).line;
+        let alone = previous_line != Some(line);
+        previous_line = Some(data.to_position_end(rope).line);
+        let Token::Comment(comment) = token else {
+            continue;
+        };
+        let directive = comment
+            .value
+            .strip_prefix('\\')
+            .and_then(|text| text.trim().strip_prefix(DIRECTIVE_PREFIX))
+            .map(str::trim);
+        let Some(directive) = directive else {
+            continue;
+        };
+        let (scope, rules) = if let Some(rules) = directive.strip_prefix("allow ") {
+            (Scope::Line(line + 1), rules)
+        } else if let Some(rules) = bracketed(directive, "ignore-file") {
+            (Scope::File, rules)
+        } else if let Some(rules) = bracketed(directive, "ignore") {
+            (Scope::Line(if alone { line + 1 } else { line }), rules)
+        } else {
+            continue;
+        };
+        let rules: Vec<String> = rules
+            .split(|chr: char| chr == ',' || chr.is_whitespace())
+            .filter(|rule| !rule.is_empty())
+            .map(str::to_owned)
+            .collect();
+        ret.push(Directive {
+            range: data_range(&comment, rope),
+            scope,
+            used: vec![false; rules.len()],
+            rules,
+        });
+    }
+    ret
+}
+
+/// The text between the brackets of `name[...]`
+fn bracketed<'a>(directive: &'a str, name: &str) -> Option<&'a str> {
+    directive
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('[')?
+        .split_once(']')
+        .map(|(rules, _)| rules)
+}
+
+/// Remove the diagnostics a directive suppresses, marking the rules that did as used
+pub fn suppress(diagnostics: &mut Vec<Diagnostic>, directives: &mut [Directive]) {
+    diagnostics.retain(|diagnostic| {
+        let Some(NumberOrString::String(rule)) = &diagnostic.code else {
+            return true;
+        };
+        let mut suppressed = false;
+        for directive in directives.iter_mut() {
+            if !directive.covers(diagnostic.range.start.line) {
+                continue;
+            }
+            for (ix, suppressing) in directive.rules.iter().enumerate() {
+                if suppressing == rule {
+                    directive.used[ix] = true;
+                    suppressed = true;
+                }
+            }
+        }
+        !suppressed
+    });
+}
+
+/// Hints for the rules of `directives` that suppressed nothing, or are not one of `rules`
+pub fn unused_suppressions(directives: &[Directive], rules: &[&str]) -> Vec<Diagnostic> {
+    let mut ret = vec![];
+    for directive in directives {
+        for (rule, used) in directive.rules.iter().zip(&directive.used) {
+            let message = if !rules.contains(&rule.as_str()) {
+                format!("Unknown rule `{}`", rule)
+            } else if !used {
+                format!("Nothing to suppress for `{}`", rule)
+            } else {
+                continue;
+            };
+            ret.push(Diagnostic {
+                range: directive.range,
+                severity: Some(DiagnosticSeverity::HINT),
+                code: Some(NumberOrString::String(UNUSED_SUPPRESSION_CODE.to_string())),
+                source: Some("forth-lsp".to_string()),
+                message,
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Default::default()
+            });
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directive_scopes() {
+        let rope = Rope::from_str(
+            "\\ forth-lsp: allow a b\nx \\ forth-lsp: ignore[c, d]\n\\ forth-lsp: ignore[e]\n\\ forth-lsp: ignore-file [f]\n\\ forth-lsp: frob",
+        );
+        let found: Vec<(Scope, Vec<String>)> = directives(&rope)
+            .into_iter()
+            .map(|directive| (directive.scope, directive.rules))
+            .collect();
+        let rules = |rules: &[&str]| rules.iter().map(|rule| rule.to_string()).collect();
+        assert_eq!(
+            vec![
+                (Scope::Line(1), rules(&["a", "b"])),
+                (Scope::Line(1), rules(&["c", "d"])),
+                (Scope::Line(3), rules(&["e"])),
+                (Scope::File, rules(&["f"])),
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn suppressing_marks_rules_used() {
+        let diagnostic = |line, code: &str| Diagnostic {
+            range: Range::new(
+                lsp_types::Position::new(line, 0),
+                lsp_types::Position::new(line, 1),
+            ),
+            code: Some(NumberOrString::String(code.to_string())),
+            ..Default::default()
+        };
+        let rope = Rope::from_str("\\ forth-lsp: ignore[a, b]\nx\n\\ forth-lsp: ignore-file[c, z]");
+        let mut directives = directives(&rope);
+        let mut diagnostics = vec![diagnostic(1, "a"), diagnostic(2, "a"), diagnostic(5, "c")];
+        suppress(&mut diagnostics, &mut directives);
+        assert_eq!(vec![diagnostic(2, "a")], diagnostics);
+        let messages: Vec<String> = unused_suppressions(&directives, &["a", "b", "c"])
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect();
+        assert_eq!(
+            vec!["Nothing to suppress for `b`", "Unknown rule `z`"],
+            messages
+        );
+    }
+}
@@ -0,0 +1,40 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use std::path::Path;
+
+/// Characters in one screen of a block file
+pub const BLOCK_SIZE: usize = 1024;
+/// Characters per line of a screen, 16 lines make a block
+pub const BLOCK_LINE_SIZE: usize = 64;
+
+/// Block files are fixed 1024 byte screens, usually without newlines
+pub fn is_block_file(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("blk"))
+}
+
+/// Screen and line within it, both counted from 0 like Forth does, for a char offset
+pub fn screen_line(ix: usize) -> (usize, usize) {
+    (ix / BLOCK_SIZE, ix % BLOCK_SIZE / BLOCK_LINE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_files() {
+        assert!(is_block_file("/src/app.blk"));
+        assert!(is_block_file("file:///src/APP.BLK"));
+        assert!(!is_block_file("/src/app.forth"));
+    }
+
+    #[test]
+    fn screens_and_lines() {
+        assert_eq!((0, 0), screen_line(0));
+        assert_eq!((0, 1), screen_line(64));
+        assert_eq!((42, 7), screen_line(42 * 1024 + 7 * 64 + 10));
+    }
+}
@@ -0,0 +1,120 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::words::Words;
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use lsp_types::{Location, Position, Range, Url};
+
+pub const BUILTIN_DOCS_FILE_NAME: &str = "FORTH-2012.md";
+
+/// Markdown documenting every builtin, and the line of the heading of each word
+pub fn render_builtin_docs<'a>(data: &Words<'a>) -> (String, Vec<(&'a str, u32)>) {
+    let mut ret = String::from("<!-- Generated by forth-lsp, edits are overwritten -->\n\n");
+    let mut lines = vec![];
+    for word in &data.words {
+        lines.push((word.token, ret.lines().count() as u32));
+        ret.push_str(&word.documentation());
+        ret.push_str("\n\n");
+    }
+    (ret, lines)
+}
+
+/// Where `word` is documented in the generated file, written on first use
+pub fn builtin_location(data: &Words, word: &str) -> Option<Location> {
+    let (content, lines) = render_builtin_docs(data);
+    let line = lines
+        .iter()
+        .find(|(token, _)| token.eq_ignore_ascii_case(word))
+        .map(|(_, line)| *line)?;
+    let path = builtin_docs_path(&content);
+    if let Err(err) = write_read_only(&path, &content) {
+        eprintln!("Failed to write {}: {}", path.display(), err);
+        return None;
+    }
+    Some(Location {
+        uri: Url::from_file_path(&path).ok()?,
+        range: Range {
+            start: Position::new(line, 0),
+            end: Position::new(line, 0),
+        },
+    })
+}
+
+/// A directory of the current user for each rendering, so servers with different builtin
+/// words never write over each other's file
+fn builtin_docs_path(content: &str) -> PathBuf {
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    env::temp_dir()
+        .join(format!("forth-lsp-{}", user))
+        .join(format!("{:016x}", hasher.finish()))
+        .join(BUILTIN_DOCS_FILE_NAME)
+}
+
+/// Write `content` unless it is there already, leaving the file read-only so editors
+/// warn before changing it
+fn write_read_only(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if let Ok(metadata) = fs::metadata(path) {
+        let mut permissions = metadata.permissions();
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(path, permissions)?;
+    }
+    fs::write(path, content)?;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headings_line_up() {
+        let data = Words::default();
+        let (content, lines) = render_builtin_docs(&data);
+        let all: Vec<&str> = content.lines().collect();
+        for (token, line) in lines {
+            assert!(all[line as usize].starts_with(&format!("# `{}`", token)));
+        }
+    }
+
+    #[test]
+    fn location_of_builtin() {
+        let data = Words::default();
+        let location = builtin_location(&data, "dup").unwrap();
+        let path = location.uri.to_file_path().unwrap();
+        let content = fs::read_to_string(path).unwrap();
+        let line = content.lines().nth(location.range.start.line as usize);
+        assert!(line.unwrap().starts_with("# `DUP`"));
+        assert!(builtin_location(&data, "no-such-word").is_none());
+    }
+
+    #[test]
+    fn path_depends_on_the_words() {
+        let data = Words::default();
+        let (content, _) = render_builtin_docs(&data);
+        assert_eq!(builtin_docs_path(&content), builtin_docs_path(&content));
+        let mut fewer = Words::default();
+        fewer.words.pop();
+        let (other, _) = render_builtin_docs(&fewer);
+        assert_ne!(builtin_docs_path(&content), builtin_docs_path(&other));
+    }
+}
@@ -1,5 +1,8 @@
+use crate::utils::ropey::bounds::RopeBoundsCheck;
+
 use forth_lexer::token::Data;
 use lsp_types::Position;
+
 pub trait ToPosition {
     fn to_position_start(&self, rope: &ropey::Rope) -> Position;
     fn to_position_end(&self, rope: &ropey::Rope) -> Position;
@@ -23,7 +26,9 @@ impl<'a> ToPosition for Data<'a> {
 }
 
 fn to_line_char(chix: usize, rope: &ropey::Rope) -> (u32, u32) {
-    let start_line = rope.char_to_line(chix) as u32;
-    let start_char = (chix - rope.line_to_char(start_line as usize)) as u32;
-    (start_line, start_char)
+    // data of a stale parse may point past the end, it then means the end
+    let position = rope
+        .position_of(chix.min(rope.len_chars()))
+        .unwrap_or_default();
+    (position.line, position.character)
 }
@@ -10,8 +10,7 @@ impl<T> FindVariantSublists<T> for Vec<T> {
         for i in 0..self.len() {
             let disc = (0..find.len())
                 .filter(|j| i + j < self.len())
-                .map(|j| discriminant(&self[i + j]) == find[j])
-                .all(|x| x);
+                .all(|j| discriminant(&self[i + j]) == find[j]);
             if disc {
                 ret.push(&self[i..(i + find.len())])
             }
@@ -2,19 +2,50 @@
 use crate::prelude::*;
 
 pub mod notification_did_change;
+pub mod notification_did_change_watched_files;
+pub mod notification_did_close;
 pub mod notification_did_open;
+pub mod notification_did_save;
+pub mod notification_set_trace;
+pub mod request_code_action;
+pub mod request_color_presentation;
 pub mod request_completion;
+pub mod request_debug_tokens;
+pub mod request_document_color;
+pub mod request_document_symbol;
+pub mod request_execute_command;
+pub mod request_folding_range;
+pub mod request_formatting;
 pub mod request_goto_definition;
 pub mod request_hover;
+pub mod request_inlay_hint;
+pub mod request_linked_editing_range;
+pub mod request_moniker;
+pub mod request_prepare_rename;
+pub mod request_reference_count;
+pub mod request_references;
+pub mod request_rename;
+pub mod request_semantic_tokens;
+pub mod request_stack_effect;
+pub mod request_will_save_wait_until;
+pub mod request_workspace_symbol;
 
-use lsp_server::{Notification, Request, RequestId};
+use lsp_server::{ExtractError, Notification, Request, RequestId};
 
+/// The params of `req` when it is an `R`, a request for another method is an
+/// [`Error::ExtractRequestError`] so the next handler can try it
 pub fn cast<R>(req: Request) -> Result<(RequestId, R::Params)>
 where
     R: lsp_types::request::Request,
     R::Params: serde::de::DeserializeOwned,
 {
-    req.extract(R::METHOD).map_err(Error::ExtractRequestError)
+    req.extract(R::METHOD).map_err(|err| match err {
+        ExtractError::JsonError { method, error } => Error::InvalidParams {
+            method,
+            source: error,
+        },
+        err => Error::ExtractRequestError(err),
+    })
 }
 
 pub fn cast_notification<N>(req: Notification) -> Result<N::Params>
@@ -22,6 +53,11 @@ where
     N: lsp_types::notification::Notification,
     N::Params: serde::de::DeserializeOwned,
 {
-    req.extract(N::METHOD)
-        .map_err(Error::ExtractNotificationError)
+    req.extract(N::METHOD).map_err(|err| match err {
+        ExtractError::JsonError { method, error } => Error::InvalidParams {
+            method,
+            source: error,
+        },
+        err => Error::ExtractNotificationError(err),
+    })
 }
@@ -1,33 +1,108 @@
 #[allow(unused_imports)]
 use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    diagnostics::{project_diagnostics, publish_dependent_diagnostics, publish_diagnostics},
+    project::{ProjectWords, Projects},
+    utils::{position_encoding::PositionEncoding, ropey::bounds::RopeBoundsCheck},
+};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use lsp_server::Notification;
+use lsp_server::{Connection, Message, Notification};
+use lsp_types::Url;
 use ropey::Rope;
+use serde_json::json;
 
 use super::cast_notification;
 
+/// Custom notification asking the client to send the whole text of a document again, in a
+/// change without a range, after changes arrived out of order or did not fit the document
+pub const RESYNC_METHOD: &str = "forth-lsp/resync";
+
+/// Apply changes to an open document. Changes older than what was applied already are
+/// dropped. Changes that do not fit the text leave the document out of sync: its changes are
+/// ignored until a change replacing the whole text, a save with text or reopening it
+#[allow(clippy::too_many_arguments)]
 pub fn handle_did_change_text_document(
     notification: &Notification,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
     files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    versions: &mut HashMap<String, i32>,
+    out_of_sync: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Result<()> {
     match cast_notification::<lsp_types::notification::DidChangeTextDocument>(notification.clone())
     {
         Ok(params) => {
-            let rope = files
-                .get_mut(&params.text_document.uri.to_string())
-                .expect("Must be able to get rope for lang");
+            let uri = params.text_document.uri;
+            let file = uri.to_string();
+            let version = params.text_document.version;
+            let current = versions.get(&file).copied();
+            if current.is_some_and(|current| version <= current) {
+                eprintln!(
+                    "Ignoring change of {} to version {}, already at {:?}",
+                    file, version, current
+                );
+                return Ok(());
+            }
+            let Some(rope) = files.get_mut(&file) else {
+                return Err(Error::NoSuchFile(file));
+            };
+            versions.insert(file.clone(), version);
             for change in params.content_changes {
-                let range = change.range.unwrap_or_default();
-                let start =
-                    rope.line_to_char(range.start.line as usize) + range.start.character as usize;
-                let end = rope.line_to_char(range.end.line as usize) + range.end.character as usize;
-                rope.remove(start..end);
-                rope.insert(start, change.text.as_str());
+                let Some(range) = change.range else {
+                    *rope = Rope::from_str(&change.text);
+                    out_of_sync.remove(&file);
+                    continue;
+                };
+                if out_of_sync.contains(&file) {
+                    continue;
+                }
+                let Some(range) = rope.char_range(encoding.decode_range(rope, range)) else {
+                    eprintln!(
+                        "Ignoring change of {} outside the document at {:?}",
+                        file, range
+                    );
+                    return request_resync(connection, &uri, &file, Some(version), out_of_sync);
+                };
+                rope.remove(range.clone());
+                rope.insert(range.start, change.text.as_str());
             }
-            Ok(())
+            if out_of_sync.contains(&file) {
+                return Ok(());
+            }
+            let changed = index.update_file(&file, rope);
+            let diagnostics = project_diagnostics(&file, files, words, index, projects, encoding);
+            publish_diagnostics(connection, uri, diagnostics, Some(version))?;
+            publish_dependent_diagnostics(
+                connection, projects, words, files, index, versions, &file, &changed, encoding,
+            )
         }
-        Err(_) => todo!(),
+        Err(err) => Err(err),
+    }
+}
+
+/// Mark `file` out of sync and ask the client for its whole text, once
+fn request_resync(
+    connection: &Connection,
+    uri: &Url,
+    file: &str,
+    version: Option<i32>,
+    out_of_sync: &mut HashSet<String>,
+) -> Result<()> {
+    if !out_of_sync.insert(file.to_owned()) {
+        return Ok(());
     }
+    let params = json!({ "textDocument": { "uri": uri, "version": version } });
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            RESYNC_METHOD.to_string(),
+            params,
+        )))
+        .map_err(|err| Error::SendError(err.to_string()))
 }
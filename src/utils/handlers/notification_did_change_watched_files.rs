@@ -0,0 +1,157 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    config::CONFIG_FILE_NAME,
+    diagnostics::{code_description, publish_diagnostics, CONFIG_CODE},
+    project::{Project, Projects},
+    utils::{
+        file_path,
+        outgoing::{Outgoing, Pending},
+    },
+};
+
+use std::path::{Path, PathBuf};
+
+use lsp_server::{Connection, Message, Notification};
+use lsp_types::{
+    notification::{DidChangeWatchedFiles, Notification as _, ShowMessage},
+    request::RegisterCapability,
+    ClientCapabilities, Diagnostic, DiagnosticSeverity, DidChangeWatchedFilesRegistrationOptions,
+    FileSystemWatcher, GlobPattern, MessageType, NumberOrString, Registration, RegistrationParams,
+    ShowMessageParams, Url,
+};
+
+use super::cast_notification;
+
+/// Reload the config of every project whose `.forth-lsp.toml` changed, returns whether any did
+pub fn handle_did_change_watched_files(
+    notification: &Notification,
+    connection: &Connection,
+    projects: &mut Projects,
+) -> Result<bool> {
+    match cast_notification::<DidChangeWatchedFiles>(notification.clone()) {
+        Ok(params) => {
+            let roots: Vec<PathBuf> = params
+                .changes
+                .iter()
+                .filter_map(|change| file_path(change.uri.as_str()))
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name == CONFIG_FILE_NAME)
+                })
+                .filter_map(|path| path.parent().map(|root| root.to_path_buf()))
+                .collect();
+            for root in &roots {
+                eprintln!("Reloading {}", root.join(CONFIG_FILE_NAME).display());
+                projects.reload(root);
+                match projects.get(root) {
+                    Some(project) => report_config_problems(connection, project, true)?,
+                    None => clear_config_problems(connection, root)?,
+                }
+            }
+            Ok(!roots.is_empty())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Warn about the parts of the config of `project` that were ignored, and mark them in the
+/// config file. With `clear` this also clears the marks when nothing was ignored
+pub fn report_config_problems(
+    connection: &Connection,
+    project: &Project,
+    clear: bool,
+) -> Result<()> {
+    if project.problems.is_empty() && !clear {
+        return Ok(());
+    }
+    let path = project.root.join(CONFIG_FILE_NAME);
+    if !project.problems.is_empty() {
+        let problems: Vec<String> = project
+            .problems
+            .iter()
+            .map(|problem| match problem.range {
+                Some(range) => format!("line {}: {}", range.start.line + 1, problem.message),
+                None => problem.message.clone(),
+            })
+            .collect();
+        let params = ShowMessageParams {
+            typ: MessageType::WARNING,
+            message: format!(
+                "Ignored parts of {}, using defaults instead:\n{}",
+                path.display(),
+                problems.join("\n")
+            ),
+        };
+        connection
+            .sender
+            .send(Message::Notification(Notification::new(
+                ShowMessage::METHOD.to_string(),
+                params,
+            )))
+            .map_err(|err| Error::SendError(err.to_string()))?;
+    }
+    let Ok(uri) = Url::from_file_path(&path) else {
+        return Ok(());
+    };
+    let diagnostics = project
+        .problems
+        .iter()
+        .map(|problem| Diagnostic {
+            range: problem.range.unwrap_or_default(),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(CONFIG_CODE.to_string())),
+            code_description: code_description(CONFIG_CODE),
+            source: Some("forth-lsp".to_string()),
+            message: problem.message.clone(),
+            ..Default::default()
+        })
+        .collect();
+    publish_diagnostics(connection, uri, diagnostics, None)
+}
+
+/// Clear the marks of the config file of `root`, a project that is gone
+fn clear_config_problems(connection: &Connection, root: &Path) -> Result<()> {
+    match Url::from_file_path(root.join(CONFIG_FILE_NAME)) {
+        Ok(uri) => publish_diagnostics(connection, uri, vec![], None),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Ask the client to notify us about `.forth-lsp.toml` changes, if it can register watchers
+pub fn register_config_watcher(
+    connection: &Connection,
+    outgoing: &mut Outgoing,
+    client: &ClientCapabilities,
+) -> Result<()> {
+    let supported = client
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files)
+        .and_then(|watched_files| watched_files.dynamic_registration)
+        .unwrap_or(false);
+    if !supported {
+        return Ok(());
+    }
+    let options = DidChangeWatchedFilesRegistrationOptions {
+        watchers: vec![FileSystemWatcher {
+            glob_pattern: GlobPattern::String(format!("**/{}", CONFIG_FILE_NAME)),
+            kind: None,
+        }],
+    };
+    let params = RegistrationParams {
+        registrations: vec![Registration {
+            id: "forth-lsp-config-watcher".to_string(),
+            method: <DidChangeWatchedFiles as lsp_types::notification::Notification>::METHOD
+                .to_string(),
+            register_options: Some(serde_json::to_value(options)?),
+        }],
+    };
+    outgoing.send(
+        connection,
+        <RegisterCapability as lsp_types::request::Request>::METHOD,
+        params,
+        Pending::Registration,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    diagnostics::{publish_dependent_diagnostics, publish_diagnostics},
+    project::{ProjectWords, Projects},
+    utils::{file_path, position_encoding::PositionEncoding},
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use lsp_server::{Connection, Notification};
+use lsp_types::notification::DidCloseTextDocument;
+use ropey::Rope;
+
+use super::cast_notification;
+
+/// Hand a closed document back to the disk: a source of a project is indexed from disk again
+/// under its path, anything else is forgotten
+#[allow(clippy::too_many_arguments)]
+pub fn handle_did_close_text_document(
+    notification: &Notification,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    versions: &mut HashMap<String, i32>,
+    out_of_sync: &mut HashSet<String>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast_notification::<DidCloseTextDocument>(notification.clone()) {
+        Ok(params) => {
+            let uri = params.text_document.uri;
+            let file = uri.to_string();
+            versions.remove(&file);
+            out_of_sync.remove(&file);
+            if files.remove(&file).is_none() {
+                return Ok(());
+            }
+            let scanned = file_path(&file).filter(|path| {
+                path.is_file()
+                    && projects.project_for_path(path).is_some()
+                    && !projects.is_excluded(path)
+            });
+            let changed = match scanned {
+                Some(path) => {
+                    let key = path.to_string_lossy().to_string();
+                    let rope = Rope::from_str(&String::from_utf8_lossy(&fs::read(&path)?));
+                    index.rename_file(&file, &key);
+                    let changed = index.update_file(&key, &rope);
+                    files.insert(key, rope);
+                    changed
+                }
+                None => index.remove_file(&file),
+            };
+            // diagnostics are for open documents only
+            publish_diagnostics(connection, uri, vec![], None)?;
+            publish_dependent_diagnostics(
+                connection, projects, words, files, index, versions, &file, &changed, encoding,
+            )
+        }
+        Err(err) => Err(err),
+    }
+}
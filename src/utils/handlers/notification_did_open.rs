@@ -1,28 +1,53 @@
 #[allow(unused_imports)]
 use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    diagnostics::{project_diagnostics, publish_dependent_diagnostics, publish_diagnostics},
+    project::{ProjectWords, Projects},
+    utils::{claim_key, position_encoding::PositionEncoding},
+};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use lsp_server::Notification;
+use lsp_server::{Connection, Notification};
 use ropey::Rope;
 
 use super::cast_notification;
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_did_open_text_document(
     notification: &Notification,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
     files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    versions: &mut HashMap<String, i32>,
+    out_of_sync: &mut HashSet<String>,
+    encoding: PositionEncoding,
 ) -> Result<()> {
     match cast_notification::<lsp_types::notification::DidOpenTextDocument>(notification.clone()) {
         Ok(params) => {
-            if let std::collections::hash_map::Entry::Vacant(e) =
-                files.entry(params.text_document.uri.to_string())
-            {
-                let rope = Rope::from_str(params.text_document.text.as_str());
-                e.insert(rope);
-            }
-            Ok(())
+            let file = params.text_document.uri.to_string();
+            // the client owns the document from now on, its text wins over what is on disk
+            claim_key(&file, files, index);
+            let rope = Rope::from_str(params.text_document.text.as_str());
+            let changed = index.update_file(&file, &rope);
+            let version = params.text_document.version;
+            versions.insert(file.clone(), version);
+            out_of_sync.remove(&file);
+            files.insert(file.clone(), rope);
+            let diagnostics = project_diagnostics(&file, files, words, index, projects, encoding);
+            publish_diagnostics(
+                connection,
+                params.text_document.uri,
+                diagnostics,
+                Some(version),
+            )?;
+            publish_dependent_diagnostics(
+                connection, projects, words, files, index, versions, &file, &changed, encoding,
+            )
         }
-        Err(Error::ExtractNotificationError(req)) => Err(Error::ExtractNotificationError(req)),
-        Err(err) => panic!("{err:?}"),
+        Err(err) => Err(err),
     }
 }
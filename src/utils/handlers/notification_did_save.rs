@@ -0,0 +1,80 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    diagnostics::{project_diagnostics, publish_dependent_diagnostics, publish_diagnostics},
+    project::{ProjectWords, Projects},
+    utils::{claim_key, file_path, outgoing::Outgoing, position_encoding::PositionEncoding},
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use lsp_server::{Connection, Notification};
+use lsp_types::{notification::DidSaveTextDocument, WorkspaceEdit};
+use ropey::Rope;
+
+use super::{cast_notification, request_formatting::formatting_edits};
+
+/// Re-index a saved document from the saved text, or from disk when the client left it out,
+/// and ask the client to format it when `format.on_save` is set
+#[allow(clippy::too_many_arguments)]
+pub fn handle_did_save_text_document(
+    notification: &Notification,
+    connection: &Connection,
+    outgoing: &mut Outgoing,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    versions: &HashMap<String, i32>,
+    out_of_sync: &mut HashSet<String>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast_notification::<DidSaveTextDocument>(notification.clone()) {
+        Ok(params) => {
+            let uri = params.text_document.uri;
+            let file = uri.to_string();
+            let text = match params.text {
+                Some(text) => {
+                    // the saved text is what the client has
+                    out_of_sync.remove(&file);
+                    text
+                }
+                None => {
+                    let path = file_path(&file).ok_or_else(|| Error::NoSuchFile(file.clone()))?;
+                    fs::read_to_string(path)?
+                }
+            };
+            let rope = Rope::from_str(&text);
+            claim_key(&file, files, index);
+            let changed = index.update_file(&file, &rope);
+            let config = &projects.config_for(&file).format;
+            let mut edits = if config.on_save {
+                formatting_edits(&rope, config, words.for_file(&file))
+            } else {
+                vec![]
+            };
+            encoding.encode_edits(&rope, &mut edits);
+            files.insert(file.clone(), rope);
+            let diagnostics = project_diagnostics(&file, files, words, index, projects, encoding);
+            let version = versions.get(&file).copied();
+            publish_diagnostics(connection, uri.clone(), diagnostics, version)?;
+            publish_dependent_diagnostics(
+                connection, projects, words, files, index, versions, &file, &changed, encoding,
+            )?;
+            if edits.is_empty() {
+                return Ok(());
+            }
+            let edit = WorkspaceEdit {
+                changes: Some(HashMap::from([(uri, edits)])),
+                ..Default::default()
+            };
+            outgoing.apply_edit(connection, "Format on save", edit)?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,17 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use lsp_server::Notification;
+use lsp_types::{notification::SetTrace, TraceValue};
+
+use super::cast_notification;
+
+pub fn handle_set_trace(notification: &Notification, trace: &mut TraceValue) -> Result<()> {
+    match cast_notification::<SetTrace>(notification.clone()) {
+        Ok(params) => {
+            *trace = params.value;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,364 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    code_actions::{
+        fix_all::{fix_all, FIX_ALL_KIND},
+        normalize_word_case::{normalize_word_case, NORMALIZE_WORD_CASE_KIND},
+        organize_definitions::{organize_definitions, ORGANIZE_DEFINITIONS_KIND},
+        variable_value::convert_variable_value,
+    },
+    config::WordCase,
+    definition_index::DefinitionIndex,
+    diagnostics::{unmatched_paren_fix, whitespace_fix, MISSING_INCLUDE_CODE, UNDEFINED_WORD_CODE},
+    project::{ProjectWords, Projects},
+    utils::{file_path, position_encoding::PositionEncoding, ropey::bounds::RopeBoundsCheck},
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{
+    request::CodeActionRequest, AnnotatedTextEdit, ChangeAnnotation, ClientCapabilities,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionResponse, CreateFile, Diagnostic,
+    DocumentChangeOperation, DocumentChanges, NumberOrString, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Range, ResourceOp, ResourceOperationKind,
+    TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+use ropey::Rope;
+
+use super::cast;
+
+const ORGANIZE_DEFINITIONS_ANNOTATION: &str = "organize-definitions";
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_code_action(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &mut HashMap<String, Rope>,
+    versions: &HashMap<String, i32>,
+    index: &DefinitionIndex,
+    client: &ClientCapabilities,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<CodeActionRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let only = params.context.only.unwrap_or_default();
+            let mut ret: CodeActionResponse = vec![];
+            if wants(&only, CodeActionKind::QUICKFIX.as_str()) {
+                for diagnostic in &params.context.diagnostics {
+                    let range = encoding.decode_range(rope, diagnostic.range);
+                    if let Some(suggestion) = suggestion(diagnostic) {
+                        let edit = TextEdit {
+                            range,
+                            new_text: suggestion.to_owned(),
+                        };
+                        ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                            title: format!("Replace with `{}`", suggestion),
+                            kind: Some(CodeActionKind::QUICKFIX),
+                            diagnostics: Some(vec![diagnostic.clone()]),
+                            edit: Some(WorkspaceEdit {
+                                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                                ..Default::default()
+                            }),
+                            is_preferred: Some(true),
+                            ..Default::default()
+                        }));
+                    }
+                    if let Some(path) = missing_include(diagnostic).filter(|_| creates(client)) {
+                        let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                        if let Ok(uri) = Url::from_file_path(&path) {
+                            ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                                title: format!("Create `{}`", name),
+                                kind: Some(CodeActionKind::QUICKFIX),
+                                diagnostics: Some(vec![diagnostic.clone()]),
+                                edit: Some(WorkspaceEdit {
+                                    document_changes: Some(DocumentChanges::Operations(vec![
+                                        DocumentChangeOperation::Op(ResourceOp::Create(
+                                            CreateFile {
+                                                uri,
+                                                options: None,
+                                                annotation_id: None,
+                                            },
+                                        )),
+                                    ])),
+                                    ..Default::default()
+                                }),
+                                is_preferred: Some(true),
+                                ..Default::default()
+                            }));
+                        }
+                    }
+                    let decoded = Diagnostic {
+                        range,
+                        ..diagnostic.clone()
+                    };
+                    if let Some(edit) = whitespace_fix(&decoded) {
+                        let title = if edit.new_text.is_empty() {
+                            "Remove trailing whitespace"
+                        } else {
+                            "Add a line break at the end of the file"
+                        };
+                        ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                            title: title.to_string(),
+                            kind: Some(CodeActionKind::QUICKFIX),
+                            diagnostics: Some(vec![diagnostic.clone()]),
+                            edit: Some(WorkspaceEdit {
+                                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                                ..Default::default()
+                            }),
+                            is_preferred: Some(true),
+                            ..Default::default()
+                        }));
+                    }
+                    if let Some(edit) = unmatched_paren_fix(rope, &decoded) {
+                        ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                            title: "Remove unmatched `)`".to_string(),
+                            kind: Some(CodeActionKind::QUICKFIX),
+                            diagnostics: Some(vec![diagnostic.clone()]),
+                            edit: Some(WorkspaceEdit {
+                                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                                ..Default::default()
+                            }),
+                            is_preferred: Some(true),
+                            ..Default::default()
+                        }));
+                    }
+                }
+            }
+            if wants(&only, FIX_ALL_KIND) {
+                let edits = fix_all(rope, words.for_file(uri.as_str()), index);
+                if !edits.is_empty() {
+                    ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: "Fix all auto-fixable problems".to_string(),
+                        kind: Some(CodeActionKind::new(FIX_ALL_KIND)),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(HashMap::from([(uri.clone(), edits)])),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }));
+                }
+            }
+            if wants(&only, ORGANIZE_DEFINITIONS_KIND) {
+                if let Some(text) = organize_definitions(rope) {
+                    let edit = TextEdit {
+                        range: whole_document(rope),
+                        new_text: text,
+                    };
+                    let version = versions.get(&uri.to_string()).copied();
+                    ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: "Organize definitions".to_string(),
+                        kind: Some(CodeActionKind::new(ORGANIZE_DEFINITIONS_KIND)),
+                        edit: Some(previewed_edit(uri.clone(), version, edit, client)),
+                        ..Default::default()
+                    }));
+                }
+            }
+            let format = &projects.config_for(uri.as_str()).format;
+            let recases = [
+                Some(format.word_case),
+                format.control_case,
+                format.defining_case,
+            ]
+            .iter()
+            .any(|case| case.is_some_and(|case| case != WordCase::Preserve));
+            if recases && wants(&only, NORMALIZE_WORD_CASE_KIND) {
+                let edits = normalize_word_case(rope, format, words.for_file(uri.as_str()));
+                if !edits.is_empty() {
+                    ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: "Normalize word case in file".to_string(),
+                        kind: Some(CodeActionKind::new(NORMALIZE_WORD_CASE_KIND)),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(HashMap::from([(uri.clone(), edits)])),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }));
+                }
+            }
+            if wants(&only, CodeActionKind::REFACTOR_REWRITE.as_str()) {
+                if let Some((title, edit)) = rope
+                    .char_at(encoding.decode(rope, params.range.start))
+                    .and_then(|ix| convert_variable_value(uri.as_str(), rope, ix, files, index))
+                {
+                    ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title,
+                        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                        edit: Some(edit),
+                        ..Default::default()
+                    }));
+                }
+            }
+            let rope_of = |uri: &str| {
+                files.get(uri).or_else(|| {
+                    let path = file_path(uri)?;
+                    files.get(path.to_str()?)
+                })
+            };
+            for action in ret.iter_mut() {
+                if let CodeActionOrCommand::CodeAction(CodeAction {
+                    edit: Some(edit), ..
+                }) = action
+                {
+                    encoding.encode_workspace_edit(rope_of, edit);
+                }
+            }
+            let result = serde_json::to_value(ret).expect("Must be able to serialize code actions");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The word suggested by an undefined word diagnostic of ours
+fn suggestion(diagnostic: &Diagnostic) -> Option<&str> {
+    let code = NumberOrString::String(UNDEFINED_WORD_CODE.to_string());
+    if diagnostic.code.as_ref() != Some(&code) {
+        return None;
+    }
+    diagnostic.data.as_ref()?.get("suggestion")?.as_str()
+}
+
+/// The path a missing include diagnostic of ours would find the file at
+fn missing_include(diagnostic: &Diagnostic) -> Option<String> {
+    let code = NumberOrString::String(MISSING_INCLUDE_CODE.to_string());
+    if diagnostic.code.as_ref() != Some(&code) {
+        return None;
+    }
+    Some(diagnostic.data.as_ref()?.get("path")?.as_str()?.to_string())
+}
+
+/// Whether the client applies workspace edits that create files
+fn creates(client: &ClientCapabilities) -> bool {
+    client
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.workspace_edit.as_ref())
+        .and_then(|workspace_edit| workspace_edit.resource_operations.as_ref())
+        .is_some_and(|operations| operations.contains(&ResourceOperationKind::Create))
+}
+
+/// Whether the client asked for `kind`, `only` lists kinds or their parents
+fn wants(only: &[CodeActionKind], kind: &str) -> bool {
+    only.is_empty()
+        || only.iter().any(|requested| {
+            kind == requested.as_str() || kind.starts_with(&format!("{}.", requested.as_str()))
+        })
+}
+
+fn whole_document(rope: &Rope) -> Range {
+    rope.whole_range()
+}
+
+/// An edit the client shows for confirmation before applying it, when it supports that
+fn previewed_edit(
+    uri: Url,
+    version: Option<i32>,
+    edit: TextEdit,
+    client: &ClientCapabilities,
+) -> WorkspaceEdit {
+    let supports_annotations = client
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.workspace_edit.as_ref())
+        .is_some_and(|workspace_edit| {
+            workspace_edit.document_changes == Some(true)
+                && workspace_edit.change_annotation_support.is_some()
+        });
+    if !supports_annotations {
+        return WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, vec![edit])])),
+            ..Default::default()
+        };
+    }
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Edits(vec![TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+            edits: vec![OneOf::Right(AnnotatedTextEdit {
+                text_edit: edit,
+                annotation_id: ORGANIZE_DEFINITIONS_ANNOTATION.to_string(),
+            })],
+        }])),
+        change_annotations: Some(HashMap::from([(
+            ORGANIZE_DEFINITIONS_ANNOTATION.to_string(),
+            ChangeAnnotation {
+                label: "Organize definitions".to_string(),
+                needs_confirmation: Some(true),
+                description: Some(
+                    "Define words before their first use, declarations first".to_string(),
+                ),
+            },
+        )])),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn only_filters_by_kind_prefix() {
+        assert!(wants(&[], ORGANIZE_DEFINITIONS_KIND));
+        assert!(wants(&[CodeActionKind::SOURCE], ORGANIZE_DEFINITIONS_KIND));
+        assert!(!wants(
+            &[CodeActionKind::QUICKFIX],
+            ORGANIZE_DEFINITIONS_KIND
+        ));
+        assert!(!wants(
+            &[CodeActionKind::new("source.organize")],
+            ORGANIZE_DEFINITIONS_KIND
+        ));
+    }
+
+    #[test]
+    fn suggestion_of_undefined_words() {
+        let mut diagnostic = Diagnostic {
+            code: Some(NumberOrString::String(UNDEFINED_WORD_CODE.to_string())),
+            data: Some(serde_json::json!({ "suggestion": "DUP" })),
+            ..Default::default()
+        };
+        assert_eq!(Some("DUP"), suggestion(&diagnostic));
+        diagnostic.code = None;
+        assert_eq!(None, suggestion(&diagnostic));
+    }
+
+    #[test]
+    fn path_of_missing_includes() {
+        let mut diagnostic = Diagnostic {
+            code: Some(NumberOrString::String(MISSING_INCLUDE_CODE.to_string())),
+            data: Some(serde_json::json!({ "path": "/src/lib.fs" })),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some("/src/lib.fs".to_string()),
+            missing_include(&diagnostic)
+        );
+        diagnostic.code = Some(NumberOrString::String(UNDEFINED_WORD_CODE.to_string()));
+        assert_eq!(None, missing_include(&diagnostic));
+    }
+
+    #[test]
+    fn whole_document_range() {
+        let range = whole_document(&Rope::from_str(": a ;\n: b ;"));
+        assert_eq!(Position::new(1, 5), range.end);
+    }
+}
@@ -0,0 +1,45 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{project::Projects, utils::hex_color::to_hex_color};
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::ColorPresentationRequest, ColorPresentation, TextEdit};
+
+use super::cast;
+
+pub fn handle_color_presentation(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+) -> Result<()> {
+    match cast::<ColorPresentationRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let mut ret: Vec<ColorPresentation> = vec![];
+            let config = projects.config_for(params.text_document.uri.as_ref());
+            if config.color.enabled {
+                let label = to_hex_color(&params.color);
+                ret.push(ColorPresentation {
+                    text_edit: Some(TextEdit {
+                        range: params.range,
+                        new_text: label.clone(),
+                    }),
+                    label,
+                    additional_text_edits: None,
+                });
+            }
+            let result = serde_json::to_value(ret)
+                .expect("Must be able to serialize the ColorPresentation list");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
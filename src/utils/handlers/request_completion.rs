@@ -1,57 +1,116 @@
 #[allow(unused_imports)]
 use crate::prelude::*;
 use crate::{
+    ast::Program,
+    definition_index::{DefKind, DefinitionIndex},
+    diagnostics::COMPILE_ONLY_WORDS,
+    environment::{environment_query_at, environment_query_documentation},
+    project::{ProjectWords, Projects},
+    stack_effect::{infer, known_effects},
     utils::{
-        ropey::{get_ix::GetIx, word_at::WordAt, RopeSliceIsLower},
-        HashMapGetForLSPParams,
+        file_path,
+        include_graph::IncludeGraph,
+        position_encoding::PositionEncoding,
+        ropey::{bounds::RopeBoundsCheck, get_ix::GetIx},
+        token_at::WordTokenAt,
     },
-    words::Words,
+    words::{Words, ENVIRONMENT_QUERIES},
 };
 
 use std::collections::HashMap;
 
+use forth_lexer::{parser::Lexer, token::Token};
 use lsp_server::{Connection, Message, Request, Response};
-use lsp_types::{request::Completion, CompletionItem, CompletionResponse};
+use lsp_types::{
+    request::Completion, ClientCapabilities, CompletionItem, CompletionItemKind,
+    CompletionItemLabelDetails, CompletionList, CompletionResponse, CompletionTextEdit,
+    CompletionTriggerKind, InsertTextFormat, Position, Range, TextEdit,
+};
 use ropey::Rope;
 
 use super::cast;
 
+/// Control words completed together with their closing word, the cursor ends up between them
+pub const SNIPPET_PAIRS: [(&str, &str); 5] = [
+    ("IF", "THEN"),
+    ("DO", "LOOP"),
+    ("?DO", "LOOP"),
+    ("BEGIN", "UNTIL"),
+    ("CASE", "ENDCASE"),
+];
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_completion(
     req: &Request,
     connection: &Connection,
-    data: &Words,
+    projects: &Projects,
+    words: &ProjectWords,
     files: &mut HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    client: &ClientCapabilities,
+    encoding: PositionEncoding,
 ) -> Result<()> {
     match cast::<Completion>(req.clone()) {
-        Ok((id, params)) => {
-            eprintln!("#{id}: {params:?}");
-            let rope = if let Some(rope) = files.for_position_param(&params.text_document_position)
-            {
+        Ok((id, mut params)) => {
+            let uri = &params.text_document_position.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
                 rope
             } else {
                 return Err(Error::NoSuchFile(
                     params.text_document_position.text_document.uri.to_string(),
                 ));
             };
-            let mut ix = rope.get_ix(&params);
-            if ix >= rope.len_chars() {
-                return Err(Error::OutOfBounds(ix));
-            }
-            if let Some(char_at_ix) = rope.get_char(ix) {
-                if char_at_ix.is_whitespace() && ix > 0 {
-                    ix -= 1;
+            let position = &mut params.text_document_position.position;
+            *position = encoding.decode(rope, *position);
+            let ix = rope.get_ix(&params)?;
+            let position = params.text_document_position.position;
+            let data = words.for_file(uri.as_str());
+            let items = match environment_query_items(rope, ix) {
+                Some(items) => Some(items),
+                None => {
+                    stack_comment_item(rope, ix, position, data, files, supports_snippets(client))
+                        .map(|item| vec![item])
                 }
+            };
+            if let Some(mut items) = items {
+                for item in items.iter_mut() {
+                    if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+                        edit.range = encoding.encode_range(rope, edit.range);
+                    }
+                }
+                let result = serde_json::to_value(CompletionResponse::Array(items))
+                    .expect("Must be able to serialize the CompletionResponse");
+                return connection
+                    .sender
+                    .send(Message::Response(Response::new_ok(id, result)))
+                    .map_err(|err| Error::SendError(err.to_string()));
             }
-            let word = rope.word_at(ix);
-            let result = if word.len_chars() > 0 {
-                eprintln!("Found word {}", word);
-                let use_lower = word.is_lowercase();
+            // the cursor sits right after the typed prefix, which counts as being on it
+            let word = index
+                .words(uri.as_str())
+                .word_at(ix)
+                .map(|span| span.text(rope))
+                .unwrap_or_default();
+            let triggered = params.context.as_ref().is_some_and(|context| {
+                context.trigger_kind == CompletionTriggerKind::TRIGGER_CHARACTER
+            });
+            let result = if !word.is_empty() {
+                let use_lower = word.ends_with(char::is_lowercase);
+                let prefix = word.to_lowercase();
+                let label_details = supports_label_details(client);
+                let compiling = in_definition(rope, ix);
+                let snippets = supports_snippets(client)
+                    && projects
+                        .config_for(params.text_document_position.text_document.uri.as_str())
+                        .completion
+                        .snippets
+                    && compiling;
+                let graph = index.include_graph();
                 let mut ret = vec![];
-                let candidates = data.words.iter().filter(|x| {
-                    x.token
-                        .to_lowercase()
-                        .starts_with(word.to_string().to_lowercase().as_str())
-                });
+                let candidates = data
+                    .words
+                    .iter()
+                    .filter(|x| x.token.to_lowercase().starts_with(prefix.as_str()));
                 for candidate in candidates {
                     let label = candidate.token.to_owned();
                     let label = if use_lower {
@@ -59,19 +118,69 @@ pub fn handle_completion(
                     } else {
                         label
                     };
-                    ret.push(CompletionItem {
+                    if snippets {
+                        ret.extend(snippet_item(&label, candidate.documentation()));
+                    }
+                    let distance = builtin_distance(candidate.token, compiling);
+                    let (source, documentation) = match distance {
+                        Distance::CompileOnly => (
+                            "core, compile only",
+                            format!(
+                                "{}\n\n*Only valid inside a definition*",
+                                candidate.documentation()
+                            ),
+                        ),
+                        _ => ("core", candidate.documentation()),
+                    };
+                    ret.push(completion_item(
                         label,
-                        detail: Some(candidate.stack.to_owned()),
-                        documentation: Some(lsp_types::Documentation::MarkupContent(
-                            lsp_types::MarkupContent {
-                                kind: lsp_types::MarkupKind::Markdown,
-                                value: candidate.documentation(),
-                            },
-                        )),
-                        ..Default::default()
-                    });
+                        candidate.stack,
+                        source,
+                        documentation,
+                        label_details,
+                        distance,
+                    ));
+                }
+                for (file, file_index) in index.files() {
+                    let distance = distance(file, uri.as_str(), graph);
+                    let source = file.rsplit('/').next().unwrap_or(file);
+                    let candidates = file_index
+                        .definitions
+                        .iter()
+                        .filter(|definition| definition.name.to_lowercase().starts_with(&prefix));
+                    for definition in candidates {
+                        let stack = definition.stack.as_deref().unwrap_or_default();
+                        let doc = definition
+                            .doc
+                            .as_ref()
+                            .map(|doc| format!("\n\n{}", doc))
+                            .unwrap_or_default();
+                        ret.push(CompletionItem {
+                            kind: Some(completion_kind(definition.kind)),
+                            ..completion_item(
+                                definition.name.to_owned(),
+                                stack,
+                                source,
+                                format!(
+                                    "# `{}`   `{}`{}\n\nDefined in {}",
+                                    definition.name, stack, doc, file
+                                ),
+                                label_details,
+                                distance,
+                            )
+                        });
+                    }
+                }
+                if triggered {
+                    // a single trigger character matches broadly, ask the client to
+                    // re-request while the user keeps typing
+                    Some(CompletionResponse::List(CompletionList {
+                        is_incomplete: true,
+                        items: ret,
+                    }))
+                } else {
+                    Some(CompletionResponse::Array(ret))
                 }
-                Some(CompletionResponse::Array(ret))
             } else {
                 None
             };
@@ -88,7 +197,429 @@ pub fn handle_completion(
                 .map_err(|err| Error::SendError(err.to_string()))?;
             Ok(())
         }
-        Err(Error::ExtractRequestError(req)) => Err(Error::ExtractRequestError(req)),
-        Err(err) => panic!("{err:?}"),
+        Err(err) => Err(err),
+    }
+}
+
+fn supports_label_details(client: &ClientCapabilities) -> bool {
+    client
+        .text_document
+        .as_ref()
+        .and_then(|text_document| text_document.completion.as_ref())
+        .and_then(|completion| completion.completion_item.as_ref())
+        .and_then(|item| item.label_details_support)
+        .unwrap_or(false)
+}
+
+fn supports_snippets(client: &ClientCapabilities) -> bool {
+    client
+        .text_document
+        .as_ref()
+        .and_then(|text_document| text_document.completion.as_ref())
+        .and_then(|completion| completion.completion_item.as_ref())
+        .and_then(|item| item.snippet_support)
+        .unwrap_or(false)
+}
+
+/// Whether `ix` is inside a colon definition, typed up to there or complete
+fn in_definition(rope: &Rope, ix: usize) -> bool {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let program = Program::parse(&tokens);
+    let mut definitions = program.definitions();
+    definitions.any(|definition| definition.span.start < ix && ix <= definition.span.end)
+}
+
+/// `IF $0 THEN` for `IF` and the other `SNIPPET_PAIRS`, in the case of `label`, ranked with
+/// the compile-only builtins
+fn snippet_item(label: &str, documentation: String) -> Option<CompletionItem> {
+    let (_, closer) = SNIPPET_PAIRS
+        .iter()
+        .find(|(opener, _)| opener.eq_ignore_ascii_case(label))?;
+    let closer = if label.chars().any(|chr| chr.is_lowercase()) {
+        closer.to_lowercase()
+    } else {
+        closer.to_string()
+    };
+    Some(CompletionItem {
+        label: format!("{} … {}", label, closer),
+        kind: Some(CompletionItemKind::SNIPPET),
+        filter_text: Some(label.to_owned()),
+        sort_text: Some(format!(
+            "{}{}",
+            Distance::Compiling as u8,
+            label.to_lowercase()
+        )),
+        insert_text: Some(format!("{} $0 {}", label, closer)),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        documentation: Some(lsp_types::Documentation::MarkupContent(
+            lsp_types::MarkupContent {
+                kind: lsp_types::MarkupKind::Markdown,
+                value: documentation,
+            },
+        )),
+        ..Default::default()
+    })
+}
+
+/// How far a candidate is defined from the document being completed, nearer ranks first.
+/// Standard words only valid in compile state rank ahead of the others inside a definition,
+/// and behind them outside one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Distance {
+    File,
+    Included,
+    Workspace,
+    Compiling,
+    Builtin,
+    CompileOnly,
+}
+
+/// Rank of the standard word `token`, by whether the cursor is in a definition
+fn builtin_distance(token: &str, compiling: bool) -> Distance {
+    let compile_only = COMPILE_ONLY_WORDS
+        .iter()
+        .any(|word| word.eq_ignore_ascii_case(token));
+    match (compile_only, compiling) {
+        (false, _) => Distance::Builtin,
+        (true, true) => Distance::Compiling,
+        (true, false) => Distance::CompileOnly,
+    }
+}
+
+/// Distance of the definitions in `file` from `key`, the document being completed. Files
+/// `key` includes directly are nearer than the rest of the workspace
+fn distance(file: &str, key: &str, graph: &IncludeGraph) -> Distance {
+    let path = file_path(file);
+    if file == key || (path.is_some() && path == file_path(key)) {
+        return Distance::File;
+    }
+    let included = graph
+        .includes(key)
+        .any(|included| included == file || (path.is_some() && path == file_path(included)));
+    if included {
+        Distance::Included
+    } else {
+        Distance::Workspace
+    }
+}
+
+/// The kind of completion item for a definition of `kind`
+fn completion_kind(kind: DefKind) -> CompletionItemKind {
+    match kind {
+        DefKind::Colon | DefKind::Noname | DefKind::Synonym | DefKind::Defer => {
+            CompletionItemKind::FUNCTION
+        }
+        DefKind::Variable | DefKind::Value | DefKind::Create | DefKind::Buffer => {
+            CompletionItemKind::VARIABLE
+        }
+        DefKind::Constant => CompletionItemKind::CONSTANT,
+        DefKind::Marker => CompletionItemKind::MODULE,
+        DefKind::Structure => CompletionItemKind::STRUCT,
+        DefKind::Field => CompletionItemKind::FIELD,
+    }
+}
+
+fn completion_item(
+    label: String,
+    stack: &str,
+    source: &str,
+    documentation: String,
+    label_details: bool,
+    distance: Distance,
+) -> CompletionItem {
+    let (detail, label_details) = if label_details {
+        (
+            None,
+            Some(CompletionItemLabelDetails {
+                detail: Some(format!(" {}", stack)),
+                description: Some(source.to_owned()),
+            }),
+        )
+    } else {
+        (Some(stack.to_owned()), None)
+    };
+    CompletionItem {
+        sort_text: Some(format!("{}{}", distance as u8, label.to_lowercase())),
+        label,
+        label_details,
+        detail,
+        documentation: Some(lsp_types::Documentation::MarkupContent(
+            lsp_types::MarkupContent {
+                kind: lsp_types::MarkupKind::Markdown,
+                value: documentation,
+            },
+        )),
+        ..Default::default()
+    }
+}
+
+/// A `( -- )` skeleton right after `: name `, or after `: name (`, with the cells the body
+/// takes and leaves when they can be inferred from the stack comments of its words
+fn stack_comment_item(
+    rope: &Rope,
+    ix: usize,
+    position: Position,
+    data: &Words,
+    files: &HashMap<String, Rope>,
+    snippets: bool,
+) -> Option<CompletionItem> {
+    let line_start = rope.line_start(rope.position_of(ix)?.line as usize)?;
+    let before = rope.slice_chars(line_start, ix)?.to_string();
+    let (head, typed) = match before.strip_suffix('(') {
+        Some(head) => (head, 1),
+        None => (before.as_str(), 0),
+    };
+    if !head.ends_with(char::is_whitespace) {
+        return None;
+    }
+    let mut words = head.split_whitespace().rev();
+    let (_, colon) = (words.next()?, words.next()?);
+    if colon != ":" {
+        return None;
+    }
+    let name_end = line_start + head.trim_end().chars().count();
+    // a typed `(` opens a comment that swallows the body
+    let mut source = rope.clone();
+    if typed == 1 {
+        source.remove(ix - 1..ix);
+        source.insert_char(ix - 1, ' ');
+    }
+    let progn = source.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let program = Program::parse(&tokens);
+    let definition = program
+        .definitions()
+        .find(|definition| definition.name.is_some_and(|name| name.end == name_end))?;
+    if definition.stack_comment().is_some() {
+        return None;
+    }
+    let known = known_effects(data, files);
+    let body: Vec<Token> = definition
+        .levels()
+        .into_iter()
+        .skip(2)
+        .map(|(token, _)| token)
+        .filter(|token| !matches!(token, Token::Semicolon(_)))
+        .collect();
+    let (taken, left) = if body.is_empty() {
+        (0, 0)
+    } else {
+        infer(&body, &known).unwrap_or((0, 0))
+    };
+    let cells = |range: std::ops::Range<usize>| -> String {
+        range
+            .map(|n| format!("x{}", n))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let (inputs, outputs) = (cells(1..taken + 1), cells(taken + 1..taken + left + 1));
+    let (new_text, format) = if snippets {
+        let stop = |n: usize, cells: &str| match cells {
+            "" => format!("${}", n),
+            cells => format!("${{{}:{}}}", n, cells),
+        };
+        (
+            format!("( {} -- {} )$0", stop(1, &inputs), stop(2, &outputs)),
+            InsertTextFormat::SNIPPET,
+        )
+    } else {
+        let side = |cells: &str| {
+            if cells.is_empty() {
+                String::new()
+            } else {
+                format!("{} ", cells)
+            }
+        };
+        (
+            format!("( {}-- {})", side(&inputs), side(&outputs)),
+            InsertTextFormat::PLAIN_TEXT,
+        )
+    };
+    let start = Position::new(position.line, position.character - typed);
+    Some(CompletionItem {
+        label: "( -- )".to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some("Stack comment".to_string()),
+        filter_text: Some("(".to_string()),
+        insert_text_format: Some(format),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range {
+                start,
+                end: position,
+            },
+            new_text,
+        })),
+        ..Default::default()
+    })
+}
+
+/// The standard queries starting with what is typed of the `ENVIRONMENT?` string at `ix`
+fn environment_query_items(rope: &Rope, ix: usize) -> Option<Vec<CompletionItem>> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let query = environment_query_at(&tokens, ix)?;
+    let typed = rope
+        .slice_chars(query.start, ix)?
+        .to_string()
+        .to_uppercase();
+    let range = Range {
+        start: rope.position_of(query.start)?,
+        end: rope.position_of(query.end)?,
+    };
+    let items = ENVIRONMENT_QUERIES
+        .iter()
+        .filter(|(name, _, _)| name.starts_with(typed.as_str()))
+        .map(|(name, answer, _)| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::CONSTANT),
+            detail: Some(format!("( -- {} true )", answer)),
+            documentation: environment_query_documentation(name).map(|value| {
+                lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+                    kind: lsp_types::MarkupKind::Markdown,
+                    value,
+                })
+            }),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: name.to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect();
+    Some(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippets_close_control_words() {
+        let item = snippet_item("if", String::new()).unwrap();
+        assert_eq!("if … then", item.label);
+        assert_eq!(Some("if $0 then".to_string()), item.insert_text);
+        let item = snippet_item("?DO", String::new()).unwrap();
+        assert_eq!(Some("?DO $0 LOOP".to_string()), item.insert_text);
+        assert!(snippet_item("DUP", String::new()).is_none());
+    }
+
+    #[test]
+    fn snippets_only_inside_definitions() {
+        let rope = Rope::from_str(": a 1 if ;\nif\n: b do");
+        assert!(in_definition(&rope, 7));
+        assert!(!in_definition(&rope, 12));
+        assert!(in_definition(&rope, 20));
+    }
+
+    fn stack_comment(source: &str, snippets: bool) -> Option<String> {
+        let rope = Rope::from_str(source);
+        let ix = source.find('|')?;
+        let mut rope = rope;
+        rope.remove(ix..ix + 1);
+        let position = rope.position_of(ix)?;
+        let files = HashMap::from([("a.forth".to_string(), rope.clone())]);
+        let item = stack_comment_item(&rope, ix, position, &Words::default(), &files, snippets)?;
+        match item.text_edit? {
+            CompletionTextEdit::Edit(edit) => Some(edit.new_text),
+            CompletionTextEdit::InsertAndReplace(_) => None,
+        }
+    }
+
+    #[test]
+    fn stack_comment_after_definition_name() {
+        assert_eq!(
+            Some("( $1 -- $2 )$0".to_string()),
+            stack_comment(": hi |", true)
+        );
+        assert_eq!(
+            Some("( x1 -- x2 )".to_string()),
+            stack_comment(": sq (|dup * ;", false)
+        );
+        assert_eq!(
+            Some("( ${1:x1 x2 x3} -- ${2:x4} )$0".to_string()),
+            stack_comment(": sum3 | + + ;", true)
+        );
+        assert_eq!(
+            Some("( x1 -- x2 )".to_string()),
+            stack_comment(": a ( n -- n ) 1 + ;\n: b | a a ;", false)
+        );
+        assert_eq!(
+            Some("( -- )".to_string()),
+            stack_comment(": c | if then ;", false)
+        );
+        assert_eq!(None, stack_comment(": d | ( n -- ) drop ;", false));
+        assert_eq!(None, stack_comment("dup |", false));
+    }
+
+    #[test]
+    fn label_details_replace_detail() {
+        let item = completion_item(
+            "DUP".into(),
+            "( x -- x x )",
+            "core",
+            "".into(),
+            true,
+            Distance::Builtin,
+        );
+        assert!(item.detail.is_none());
+        let details = item.label_details.unwrap();
+        assert_eq!(Some(" ( x -- x x )".to_string()), details.detail);
+        assert_eq!(Some("core".to_string()), details.description);
+
+        let item = completion_item(
+            "DUP".into(),
+            "( x -- x x )",
+            "core",
+            "".into(),
+            false,
+            Distance::Builtin,
+        );
+        assert_eq!(Some("( x -- x x )".to_string()), item.detail);
+        assert!(item.label_details.is_none());
+    }
+
+    #[test]
+    fn nearer_definitions_rank_first() {
+        let files = HashMap::from([
+            (
+                "file:///p/main.forth".to_string(),
+                Rope::from_str("include lib.forth\n"),
+            ),
+            (
+                "/p/main.forth".to_string(),
+                Rope::from_str("include lib.forth\n"),
+            ),
+            ("/p/lib.forth".to_string(), Rope::new()),
+            ("/p/other.forth".to_string(), Rope::new()),
+        ]);
+        let graph = IncludeGraph::from_files(&files);
+        let key = "file:///p/main.forth";
+        assert_eq!(Distance::File, distance("/p/main.forth", key, &graph));
+        assert_eq!(Distance::Included, distance("/p/lib.forth", key, &graph));
+        assert_eq!(Distance::Workspace, distance("/p/other.forth", key, &graph));
+        let item = completion_item(
+            "sq".into(),
+            "",
+            "lib.forth",
+            "".into(),
+            false,
+            Distance::Included,
+        );
+        assert_eq!(Some("1sq".to_string()), item.sort_text);
+    }
+
+    #[test]
+    fn compile_only_words_rank_by_state() {
+        assert_eq!(Distance::Compiling, builtin_distance("literal", true));
+        assert_eq!(Distance::CompileOnly, builtin_distance("LITERAL", false));
+        assert_eq!(Distance::Builtin, builtin_distance("DUP", true));
+        assert!(Distance::Compiling < Distance::Builtin);
+        assert!(Distance::Builtin < Distance::CompileOnly);
+        assert!(in_definition(&Rope::from_str(": sq dup ;"), 5));
+        assert!(!in_definition(&Rope::from_str(": sq dup ; 1 "), 12));
     }
 }
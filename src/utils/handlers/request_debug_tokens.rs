@@ -0,0 +1,127 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::utils::position_encoding::PositionEncoding;
+
+use std::collections::HashMap;
+
+use forth_lexer::{parser::Lexer, token::Token};
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{Range, TextDocumentIdentifier};
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use super::cast;
+
+/// Custom request for the tokens the lexer reads from a document, to attach to lexer bug
+/// reports
+pub enum DebugTokens {}
+
+impl lsp_types::request::Request for DebugTokens {
+    type Params = DebugTokensParams;
+    type Result = Vec<DebugToken>;
+    const METHOD: &'static str = "forth-lsp/debugTokens";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugTokensParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct DebugToken {
+    /// The variant of the token, like `Word` or `StackComment`
+    pub kind: String,
+    pub range: Range,
+    pub text: String,
+}
+
+pub fn handle_debug_tokens(
+    req: &Request,
+    connection: &Connection,
+    files: &HashMap<String, Rope>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<DebugTokens>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let result = debug_tokens(rope, encoding);
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The tokens every feature works from, without the whitespace between them
+fn debug_tokens(rope: &Rope, encoding: PositionEncoding) -> Vec<DebugToken> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    lexer
+        .parse()
+        .iter()
+        .map(|token| {
+            let data = token.get_data();
+            DebugToken {
+                kind: kind(token).to_string(),
+                range: encoding.data_range(data, rope),
+                text: data.value.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn kind(token: &Token) -> &'static str {
+    match token {
+        Token::Illegal(_) => "Illegal",
+        Token::Eof(_) => "Eof",
+        Token::Colon(_) => "Colon",
+        Token::Semicolon(_) => "Semicolon",
+        Token::Word(_) => "Word",
+        Token::Number(_) => "Number",
+        Token::Comment(_) => "Comment",
+        Token::StackComment(_) => "StackComment",
+        Token::Whitespace(_) => "Whitespace",
+        Token::Newline(_) => "Newline",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn tokens_with_their_kind_and_range() {
+        let tokens = debug_tokens(
+            &Rope::from_str(": sq ( n -- n*n )\n  dup * ; \\ square"),
+            PositionEncoding::Utf32,
+        );
+        let kinds: Vec<&str> = tokens.iter().map(|token| token.kind.as_str()).collect();
+        assert_eq!(
+            vec![
+                "Colon",
+                "Word",
+                "Comment",
+                "Word",
+                "Word",
+                "Semicolon",
+                "Comment"
+            ],
+            kinds
+        );
+        assert_eq!("( n -- n*n )", tokens[2].text);
+        assert_eq!(
+            Range::new(Position::new(1, 2), Position::new(1, 5)),
+            tokens[3].range
+        );
+    }
+}
@@ -0,0 +1,62 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    project::Projects,
+    utils::{hex_color::parse_hex_color, position_encoding::PositionEncoding},
+};
+
+use std::collections::HashMap;
+
+use forth_lexer::{parser::Lexer, token::Token};
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::DocumentColor, ColorInformation};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_document_color(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    files: &mut HashMap<String, Rope>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<DocumentColor>(req.clone()) {
+        Ok((id, params)) => {
+            let mut ret: Vec<ColorInformation> = vec![];
+            let config = projects.config_for(params.text_document.uri.as_ref());
+            if config.color.enabled {
+                let rope = if let Some(rope) = files.get(&params.text_document.uri.to_string()) {
+                    rope
+                } else {
+                    return Err(Error::NoSuchFile(params.text_document.uri.to_string()));
+                };
+                let progn = rope.to_string();
+                let mut lexer = Lexer::new(progn.as_str());
+                for token in lexer.parse() {
+                    if let Token::Number(data) = token {
+                        if let Some(color) = parse_hex_color(data.value) {
+                            ret.push(ColorInformation {
+                                range: encoding.data_range(&data, rope),
+                                color,
+                            });
+                        }
+                    }
+                }
+            }
+            let result = serde_json::to_value(ret)
+                .expect("Must be able to serialize the ColorInformation list");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,284 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    ast::Program,
+    definition_index::{noname_binding, DefKind, DefinitionIndex},
+    utils::{
+        data_to_position::ToPosition, include_graph::include_targets,
+        position_encoding::PositionEncoding,
+    },
+};
+
+use std::collections::HashMap;
+
+use forth_lexer::{parser::Lexer, token::Data};
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{
+    request::DocumentSymbolRequest, DocumentSymbol, DocumentSymbolResponse, Range, SymbolKind,
+};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_document_symbol(
+    req: &Request,
+    connection: &Connection,
+    files: &mut HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<DocumentSymbolRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let mut symbols = document_symbols(rope, index, uri.as_str());
+            encoding.encode_symbols(rope, &mut symbols);
+            let result = Some(DocumentSymbolResponse::Nested(symbols));
+            let result = serde_json::to_value(result)
+                .expect("Must be able to serialize the DocumentSymbolResponse");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The symbol kind shown for a kind of definition
+pub fn symbol_kind(kind: DefKind) -> SymbolKind {
+    match kind {
+        DefKind::Colon | DefKind::Noname | DefKind::Synonym | DefKind::Defer => {
+            SymbolKind::FUNCTION
+        }
+        DefKind::Variable | DefKind::Value | DefKind::Create => SymbolKind::VARIABLE,
+        DefKind::Constant => SymbolKind::CONSTANT,
+        DefKind::Buffer => SymbolKind::ARRAY,
+        DefKind::Marker => SymbolKind::NAMESPACE,
+        DefKind::Structure => SymbolKind::STRUCT,
+        DefKind::Field => SymbolKind::FIELD,
+    }
+}
+
+/// Definitions indexed for `file` and its includes in source order, fields nested in their
+/// structure. `:NONAME` definitions show the word they are bound to
+fn document_symbols(rope: &Rope, index: &DefinitionIndex, file: &str) -> Vec<DocumentSymbol> {
+    let mut ret = vec![];
+    let mut fields: HashMap<&str, Vec<DocumentSymbol>> = HashMap::new();
+    for definition in index.symbols(file) {
+        // named after their binding below
+        if definition.kind == DefKind::Noname {
+            continue;
+        }
+        let symbol = symbol(
+            &definition.name,
+            symbol_kind(definition.kind),
+            definition.range,
+            definition.selection_range,
+            vec![],
+        );
+        match &definition.field_of {
+            Some(field_of) => fields.entry(&field_of.structure).or_default().push(symbol),
+            None => ret.push(symbol),
+        }
+    }
+    for symbol in ret.iter_mut() {
+        if symbol.kind == SymbolKind::STRUCT {
+            let children = fields.remove(symbol.name.as_str()).unwrap_or_default();
+            symbol.children = (!children.is_empty()).then_some(children);
+        }
+    }
+    // neither `:NONAME` definitions, as written, nor includes are indexed
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let range = |begin: &Data, end: &Data| Range {
+        start: begin.to_position_start(rope),
+        end: end.to_position_end(rope),
+    };
+    for definition in Program::parse(&tokens).definitions() {
+        let Some(name) = definition.name.filter(|_| definition.is_noname()) else {
+            continue;
+        };
+        let end = Data::new(definition.span.end, definition.span.end, "");
+        // anonymous, but named by what it is bound to, like `:noname is greet`
+        let label = match noname_binding(&tokens, definition) {
+            Some((binder, target)) => format!(":{} {} {}", name.value, binder.value, target.value),
+            None => format!(":{}", name.value),
+        };
+        ret.push(symbol(
+            &label,
+            SymbolKind::FUNCTION,
+            range(&definition.colon, &definition.semicolon.unwrap_or(end)),
+            range(&name, &name),
+            vec![],
+        ));
+    }
+    for target in include_targets(&tokens) {
+        let Some(ix) = tokens
+            .iter()
+            .position(|token| token.get_data().start == target.start)
+        else {
+            continue;
+        };
+        // `INCLUDE name`, or `S" name" INCLUDED`
+        let directive = tokens[ix - 1].get_data();
+        let end = if directive.value.eq_ignore_ascii_case("S\"") {
+            tokens[ix + 1].get_data()
+        } else {
+            &target
+        };
+        ret.push(symbol(
+            target.value,
+            SymbolKind::FILE,
+            range(directive, end),
+            range(&target, &target),
+            vec![],
+        ));
+    }
+    ret.sort_by_key(|symbol| (symbol.range.start.line, symbol.range.start.character));
+    ret
+}
+
+fn symbol(
+    name: &str,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name: name.to_owned(),
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    fn symbols_of(source: &str) -> Vec<DocumentSymbol> {
+        let rope = Rope::from_str(source);
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        document_symbols(&rope, &index, "/src/a.forth")
+    }
+
+    #[test]
+    fn fields_nest_under_structures() {
+        let symbols = symbols_of(
+            "begin-structure point\n  field: p.x\n  field: p.y\nend-structure\n: origin 0 0 ;",
+        );
+        let names: Vec<&str> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(vec!["point", "origin"], names);
+        assert_eq!(SymbolKind::STRUCT, symbols[0].kind);
+        let fields: Vec<&str> = symbols[0]
+            .children
+            .iter()
+            .flatten()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(vec!["p.x", "p.y"], fields);
+    }
+
+    #[test]
+    fn markers_and_includes() {
+        let symbols = symbols_of("include lib.fs\nmarker -app\ns\" more.fs\" included");
+        let symbols: Vec<(&str, SymbolKind, u32, u32)> = symbols
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol.kind,
+                    symbol.range.start.character,
+                    symbol.range.end.character,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("lib.fs", SymbolKind::FILE, 0, 14),
+                ("-app", SymbolKind::NAMESPACE, 0, 11),
+                ("more.fs", SymbolKind::FILE, 0, 20),
+            ],
+            symbols
+        );
+    }
+
+    #[test]
+    fn nonames_show_their_binding() {
+        let names: Vec<String> =
+            symbols_of("defer greet\n:noname .\" hi\" ; is greet\n:noname 1 ;")
+                .into_iter()
+                .map(|symbol| symbol.name)
+                .collect();
+        assert_eq!(vec!["greet", ":noname is greet", ":noname"], names);
+    }
+
+    #[test]
+    fn defining_words_set_the_kind() {
+        let symbols: Vec<(String, SymbolKind)> =
+            symbols_of("variable count\n5 constant five\n0 value x\n: sq dup * ;")
+                .into_iter()
+                .map(|symbol| (symbol.name, symbol.kind))
+                .collect();
+        assert_eq!(
+            vec![
+                ("count".to_string(), SymbolKind::VARIABLE),
+                ("five".to_string(), SymbolKind::CONSTANT),
+                ("x".to_string(), SymbolKind::VARIABLE),
+                ("sq".to_string(), SymbolKind::FUNCTION),
+            ],
+            symbols
+        );
+    }
+
+    #[test]
+    fn definitions_span_colon_to_semicolon() {
+        let symbols = symbols_of(
+            ": greet ( -- )
+  .\" hi\"
+  cr ;
+: half 1",
+        );
+        let extents: Vec<(Range, Range)> = symbols
+            .iter()
+            .map(|symbol| (symbol.range, symbol.selection_range))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    Range::new(Position::new(0, 0), Position::new(2, 6)),
+                    Range::new(Position::new(0, 2), Position::new(0, 7)),
+                ),
+                (
+                    Range::new(Position::new(3, 0), Position::new(3, 8)),
+                    Range::new(Position::new(3, 2), Position::new(3, 6)),
+                ),
+            ],
+            extents
+        );
+    }
+}
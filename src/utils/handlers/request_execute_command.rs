@@ -0,0 +1,261 @@
+use crate::definition_index::DefinitionIndex;
+use crate::glossary::{self, entries};
+use crate::graph::{self, graphs};
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    project::{ProjectWords, Projects},
+    utils::{
+        duplicate_keys, file_path, file_uri, outgoing::Outgoing,
+        position_encoding::PositionEncoding,
+    },
+};
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{Notification as _, Progress},
+    request::ExecuteCommand,
+    ProgressParams, ProgressParamsValue, ProgressToken, Url, WorkDoneProgress,
+    WorkDoneProgressBegin, WorkDoneProgressEnd, WorkDoneProgressReport, WorkspaceEdit,
+};
+use ropey::Rope;
+use serde_json::json;
+
+use super::{cast, request_formatting::formatting_edits};
+
+/// Counts and sizes of the index, to help diagnose slow workspaces
+pub const STATS_COMMAND: &str = "forth-lsp.stats";
+
+/// Glossary of the user words, in the format named by the optional argument
+pub const GLOSSARY_COMMAND: &str = "forth-lsp.glossary";
+
+/// Call graph of the words and include graph of the files, in the format named by the
+/// optional argument
+pub const GRAPH_COMMAND: &str = "forth-lsp.graph";
+
+/// Format every file of the workspace through `workspace/applyEdit`, with the `dry-run`
+/// argument only report which files formatting would change
+pub const FORMAT_WORKSPACE_COMMAND: &str = "forth-lsp.formatWorkspace";
+
+/// Files loaded under more than one key, like a path and a URI, each a list of its keys.
+/// Should be empty, anything else means duplicate definitions
+pub const DUPLICATE_FILES_COMMAND: &str = "forth-lsp.duplicateFiles";
+
+/// Commands the client may run through `workspace/executeCommand`
+pub const COMMANDS: [&str; 5] = [
+    STATS_COMMAND,
+    GLOSSARY_COMMAND,
+    GRAPH_COMMAND,
+    FORMAT_WORKSPACE_COMMAND,
+    DUPLICATE_FILES_COMMAND,
+];
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_execute_command(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    outgoing: &mut Outgoing,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<ExecuteCommand>(req.clone()) {
+        Ok((id, params)) => {
+            let resp = match params.command.as_str() {
+                STATS_COMMAND => {
+                    let result = stats(index);
+                    eprintln!("Stats: {}", result);
+                    Response {
+                        id,
+                        result: Some(result),
+                        error: None,
+                    }
+                }
+                GLOSSARY_COMMAND => match format_argument(
+                    &params.arguments,
+                    glossary::Format::from_name,
+                    glossary::Format::Markdown,
+                ) {
+                    Some(format) => {
+                        Response::new_ok(id, glossary::render(&entries(index, files), format, None))
+                    }
+                    None => unknown_format(id, &params.arguments),
+                },
+                GRAPH_COMMAND => match format_argument(
+                    &params.arguments,
+                    graph::Format::from_name,
+                    graph::Format::Json,
+                ) {
+                    Some(format) => {
+                        Response::new_ok(id, graph::render(&graphs(index, files, None), format))
+                    }
+                    None => unknown_format(id, &params.arguments),
+                },
+                FORMAT_WORKSPACE_COMMAND => match params.arguments.first() {
+                    None => {
+                        let token = params.work_done_progress_params.work_done_token;
+                        let (edit, report) =
+                            format_workspace(connection, projects, words, files, token, encoding)?;
+                        outgoing.apply_edit(connection, "Format workspace", edit)?;
+                        Response::new_ok(id, report)
+                    }
+                    Some(argument) if argument == "dry-run" => {
+                        let token = params.work_done_progress_params.work_done_token;
+                        let (_, report) =
+                            format_workspace(connection, projects, words, files, token, encoding)?;
+                        Response::new_ok(id, report)
+                    }
+                    Some(argument) => Response::new_err(
+                        id,
+                        ErrorCode::InvalidParams as i32,
+                        format!("Unknown argument {}", argument),
+                    ),
+                },
+                DUPLICATE_FILES_COMMAND => Response::new_ok(id, duplicate_keys(files.keys())),
+                command => Response::new_err(
+                    id,
+                    ErrorCode::InvalidParams as i32,
+                    format!("Unknown command {}", command),
+                ),
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The format named by the first argument, `default` without arguments
+fn format_argument<F>(
+    arguments: &[serde_json::Value],
+    from_name: impl Fn(&str) -> Option<F>,
+    default: F,
+) -> Option<F> {
+    match arguments.first() {
+        Some(name) => from_name(name.as_str()?),
+        None => Some(default),
+    }
+}
+
+fn unknown_format(id: RequestId, arguments: &[serde_json::Value]) -> Response {
+    Response::new_err(
+        id,
+        ErrorCode::InvalidParams as i32,
+        format!("Unknown format {}", arguments[0]),
+    )
+}
+
+/// Edits formatting the files of the workspace, and which files they change. Files outside
+/// every project are only formatted while open, as they are libraries from include paths,
+/// excluded files never are
+fn format_workspace(
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+    token: Option<ProgressToken>,
+    encoding: PositionEncoding,
+) -> Result<(WorkspaceEdit, serde_json::Value)> {
+    // an open document is keyed by its URI, and by its path when it was also scanned
+    let mut sources: BTreeMap<PathBuf, (&str, Url)> = BTreeMap::new();
+    for file in files.keys() {
+        let (Some(path), Some(uri)) = (file_path(file), file_uri(file)) else {
+            continue;
+        };
+        let open = Url::parse(file).is_ok();
+        if projects.is_excluded(&path)
+            || (!open && projects.project_for_path(&path).is_none())
+            || (!open && sources.contains_key(&path))
+        {
+            continue;
+        }
+        sources.insert(path, (file, uri));
+    }
+    progress(
+        connection,
+        &token,
+        WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: "Formatting workspace".to_string(),
+            percentage: Some(0),
+            ..Default::default()
+        }),
+    )?;
+    let total = sources.len();
+    let mut changes = HashMap::new();
+    let mut report = vec![];
+    for (done, (file, uri)) in sources.into_values().enumerate() {
+        let config = &projects.config_for(file).format;
+        let rope = &files[file];
+        let mut edits = formatting_edits(rope, config, words.for_file(file));
+        encoding.encode_edits(rope, &mut edits);
+        report.push(json!({ "uri": uri, "changed": !edits.is_empty() }));
+        if !edits.is_empty() {
+            changes.insert(uri.clone(), edits);
+        }
+        progress(
+            connection,
+            &token,
+            WorkDoneProgress::Report(WorkDoneProgressReport {
+                message: Some(uri.to_string()),
+                percentage: Some((100 * (done + 1) / total) as u32),
+                ..Default::default()
+            }),
+        )?;
+    }
+    let changed = changes.len();
+    progress(
+        connection,
+        &token,
+        WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: Some(format!("{} of {} files changed", changed, report.len())),
+        }),
+    )?;
+    let edit = WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    };
+    Ok((edit, json!({ "files": report, "changed": changed })))
+}
+
+/// Report work done progress, when the client gave a token for it
+fn progress(
+    connection: &Connection,
+    token: &Option<ProgressToken>,
+    value: WorkDoneProgress,
+) -> Result<()> {
+    let Some(token) = token else {
+        return Ok(());
+    };
+    let params = ProgressParams {
+        token: token.clone(),
+        value: ProgressParamsValue::WorkDone(value),
+    };
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            Progress::METHOD.to_string(),
+            params,
+        )))
+        .map_err(|err| Error::SendError(err.to_string()))
+}
+
+fn stats(index: &DefinitionIndex) -> serde_json::Value {
+    let stats = index.stats();
+    json!({
+        "files": stats.files,
+        "definitions": stats.definitions,
+        "references": stats.references,
+        "indexBytes": stats.bytes,
+        "lastIndexMillis": stats.last_index.as_millis() as u64,
+    })
+}
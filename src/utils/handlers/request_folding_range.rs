@@ -0,0 +1,129 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::utils::data_to_position::ToPosition;
+
+use std::collections::HashMap;
+
+use forth_lexer::{parser::Lexer, token::Token};
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::FoldingRangeRequest, FoldingRange, FoldingRangeKind};
+use ropey::Rope;
+
+use super::cast;
+
+/// Characters banner lines like `\ ------` and `\ ==== io ====` are drawn with
+const BANNER_CHARACTERS: [char; 6] = ['-', '=', '*', '#', '~', '_'];
+
+pub fn handle_folding_range(
+    req: &Request,
+    connection: &Connection,
+    files: &HashMap<String, Rope>,
+) -> Result<()> {
+    match cast::<FoldingRangeRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let result = serde_json::to_value(comment_folds(rope))
+                .expect("Must be able to serialize folding ranges");
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Stretches of two or more lines holding nothing but a `\` comment, summarized by their
+/// first line that is more than a banner
+fn comment_folds(rope: &Rope) -> Vec<FoldingRange> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    // line and text of every comment that has its line to itself
+    let mut lines: Vec<(u32, &str)> = vec![];
+    let mut previous_line = None;
+    for token in &tokens {
+        let data = token.get_data();
+        let line = data.to_position_start(rope).line;
+        let alone = previous_line != Some(line);
+        previous_line = Some(data.to_position_end(rope).line);
+        if let Token::Comment(data) = token {
+            if alone && data.value.starts_with('\\') {
+                lines.push((line, data.value));
+            }
+        }
+    }
+    let mut ret = vec![];
+    let mut stretch: Vec<(u32, &str)> = vec![];
+    for (line, text) in lines {
+        if stretch.last().is_some_and(|(last, _)| last + 1 != line) {
+            ret.extend(fold(&stretch));
+            stretch.clear();
+        }
+        stretch.push((line, text));
+    }
+    ret.extend(fold(&stretch));
+    ret
+}
+
+fn fold(stretch: &[(u32, &str)]) -> Option<FoldingRange> {
+    let (first, _) = stretch.first()?;
+    let (last, _) = stretch.last()?;
+    if first == last {
+        return None;
+    }
+    let collapsed_text = stretch.iter().find_map(|(_, text)| {
+        let text = text
+            .trim_start_matches('\\')
+            .trim_matches(|chr: char| chr.is_whitespace() || BANNER_CHARACTERS.contains(&chr));
+        (!text.is_empty()).then(|| text.to_string())
+    });
+    Some(FoldingRange {
+        start_line: *first,
+        end_line: *last,
+        kind: Some(FoldingRangeKind::Comment),
+        collapsed_text,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_comment_banners() {
+        let rope = Rope::from_str(
+            "\\ ------\n\\ == io ==\n\\ ------\n: a ; \\ not alone\n\\ one line\n\n\\ x\n\\\n",
+        );
+        let folds = comment_folds(&rope);
+        let spans: Vec<(u32, u32, Option<String>)> = folds
+            .into_iter()
+            .map(|fold| (fold.start_line, fold.end_line, fold.collapsed_text))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 2, Some("io".to_string())),
+                (6, 7, Some("x".to_string()))
+            ],
+            spans
+        );
+    }
+
+    #[test]
+    fn folds_comments_ending_the_file() {
+        let rope = Rope::from_str(": a ;\r\n\\ one\r\n\\ two\r\n\\");
+        let folds = comment_folds(&rope);
+        let spans: Vec<(u32, u32)> = folds
+            .into_iter()
+            .map(|fold| (fold.start_line, fold.end_line))
+            .collect();
+        assert_eq!(vec![(1, 3)], spans);
+    }
+}
@@ -0,0 +1,65 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    config::FormatConfig,
+    formatter::format_source,
+    project::{ProjectWords, Projects},
+    utils::{position_encoding::PositionEncoding, ropey::bounds::RopeBoundsCheck},
+    words::Words,
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::Formatting, TextEdit};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_formatting(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &mut HashMap<String, Rope>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<Formatting>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let config = &projects.config_for(uri.as_str()).format;
+            let mut ret = formatting_edits(rope, config, words.for_file(uri.as_str()));
+            encoding.encode_edits(rope, &mut ret);
+            let result = serde_json::to_value(ret).expect("Must be able to serialize edits");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// An edit replacing the whole of `rope` with its formatted text, none when it is formatted
+pub fn formatting_edits(rope: &Rope, config: &FormatConfig, data: &Words) -> Vec<TextEdit> {
+    let source = rope.to_string();
+    let formatted = format_source(&source, config, data);
+    if formatted == source {
+        return vec![];
+    }
+    vec![TextEdit {
+        range: rope.whole_range(),
+        new_text: formatted,
+    }]
+}
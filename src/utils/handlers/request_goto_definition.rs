@@ -1,23 +1,23 @@
 #[allow(unused_imports)]
 use crate::prelude::*;
 use crate::{
+    definition_index::{Definition, DefinitionIndex, UseSite},
+    project::{ProjectWords, Projects},
     utils::{
-        data_to_position::ToPosition,
-        find_variant_sublists_from_to::FindVariantSublistsFromTo,
-        ropey::{get_ix::GetIx, word_on_or_before::WordOnOrBefore},
+        builtin_docs::builtin_location,
+        file_uri,
+        include_graph::{load_included_files, IncludeGraph},
+        position_encoding::PositionEncoding,
+        ropey::get_ix::GetIx,
+        token_at::WordTokenAt,
         HashMapGetForLSPParams,
     },
-    words::Words,
 };
 
-use std::{collections::HashMap, mem::discriminant};
+use std::collections::HashMap;
 
-use forth_lexer::{
-    parser::Lexer,
-    token::{Data, Token},
-};
 use lsp_server::{Connection, Message, Request, Response};
-use lsp_types::{request::GotoDefinition, GotoDefinitionResponse, Location, Range, Url};
+use lsp_types::{request::GotoDefinition, GotoDefinitionResponse, Location};
 use ropey::Rope;
 
 use super::cast;
@@ -25,13 +25,14 @@ use super::cast;
 pub fn handle_goto_definition(
     req: &Request,
     connection: &Connection,
-    _data: &Words,
+    projects: &Projects,
+    words: &ProjectWords,
     files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    encoding: PositionEncoding,
 ) -> Result<()> {
     match cast::<GotoDefinition>(req.clone()) {
-        Ok((id, params)) => {
-            eprintln!("#{id}: {params:?}");
-            let mut ret: Vec<Location> = vec![];
+        Ok((id, mut params)) => {
             let rope = if let Some(rope) =
                 files.for_position_param(&params.text_document_position_params)
             {
@@ -45,38 +46,54 @@ pub fn handle_goto_definition(
                         .to_string(),
                 ));
             };
-            let ix = rope.get_ix(&params);
-            if ix >= rope.len_chars() {
-                return Err(Error::OutOfBounds(ix));
+            let position = &mut params.text_document_position_params.position;
+            *position = encoding.decode(rope, *position);
+            let ix = rope.get_ix(&params)?;
+            let key = params
+                .text_document_position_params
+                .text_document
+                .uri
+                .to_string();
+            let word = index
+                .words(&key)
+                .word_at(ix)
+                .map(|span| span.text(rope))
+                .unwrap_or_default();
+            if !word.is_empty() && !index.is_defined(&word) {
+                // it may be defined in an included file outside the workspace
+                load_included_files(&key, files, index);
             }
-            let word = rope.word_on_or_before(ix).to_string();
-            for (file, rope) in files.iter() {
-                eprintln!("Word: {}", word);
-                let progn = rope.to_string();
-                let mut lexer = Lexer::new(progn.as_str());
-                let tokens = lexer.parse();
-
-                for result in tokens.find_variant_sublists_from_to(
-                    discriminant(&Token::Colon(Data::default())),
-                    discriminant(&Token::Semicolon(Data::default())),
-                ) {
-                    eprintln!("{:?}", result);
-                    let tok = Token::Illegal(Data::new(0, 0, ""));
-                    let begin = result.first().unwrap_or(&tok).get_data();
-                    let end = result.last().unwrap_or(&tok).get_data();
-                    if let Ok(uri) = Url::from_file_path(file) {
-                        ret.push(Location {
-                            uri,
-                            range: Range {
-                                start: begin.to_position_start(rope),
-                                end: end.to_position_end(rope),
-                            },
-                        });
-                    } else {
-                        eprintln!("Failed to parse URI for {}", file);
-                    }
+            let graph = index.include_graph();
+            let definitions = if projects.config_for(&key).definition.nearest_preceding {
+                let position = params.text_document_position_params.position;
+                let order = graph.load_order(&key, position);
+                index.resolve(
+                    &word,
+                    UseSite {
+                        file: &key,
+                        position,
+                        order: &order,
+                    },
+                )
+            } else {
+                index.definitions(&word)
+            };
+            let definitions = by_proximity(definitions, graph, &key);
+            let mut ret: Vec<Location> = vec![];
+            for (file, definition) in definitions {
+                if let Some(uri) = file_uri(file) {
+                    ret.push(Location {
+                        uri,
+                        range: encoding.encode_file_range(files, file, definition.range),
+                    });
+                } else {
+                    eprintln!("Failed to parse URI for {}", file);
                 }
             }
+            if ret.is_empty() {
+                // builtins have no source, jump to their documentation instead
+                ret.extend(builtin_location(words.for_file(&key), &word));
+            }
             let result = Some(GotoDefinitionResponse::Array(ret));
             let result = serde_json::to_value(result)
                 .expect("Must be able to serialize the GotoDefinitionResponse");
@@ -91,9 +108,69 @@ pub fn handle_goto_definition(
                 .map_err(|err| Error::SendError(err.to_string()))?;
             Ok(())
         }
-        Err(Error::ExtractRequestError(req)) => Err(Error::ExtractRequestError(req)),
-        Err(err) => panic!("{err:?}"),
-        // Err(err @ ExtractError::JsonError { .. }) => panic!("{err:?}"),
-        // Err(ExtractError::MethodMismatch(req)) => req,
+        Err(err) => Err(err),
+    }
+}
+
+/// `definitions` ordered by the file they are in, the current file first, then files
+/// fewer includes away, then files unrelated to it
+fn by_proximity<'a>(
+    mut definitions: Vec<(&'a String, &'a Definition)>,
+    graph: &IncludeGraph,
+    key: &str,
+) -> Vec<(&'a String, &'a Definition)> {
+    let distances = graph.distances(key);
+    definitions.sort_by_key(|(file, definition)| {
+        (
+            *file != key,
+            distances.get(*file).copied().unwrap_or(usize::MAX),
+            file.to_string(),
+            definition.range.start.line,
+            definition.range.start.character,
+        )
+    });
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_files_first() {
+        let files = HashMap::from([
+            (
+                "file:///p/main.forth".to_string(),
+                Rope::from_str("include lib.forth\n: sq dup * ;"),
+            ),
+            (
+                "file:///p/lib.forth".to_string(),
+                Rope::from_str(": sq dup * ;"),
+            ),
+            (
+                "file:///p/a.forth".to_string(),
+                Rope::from_str(": sq dup * ;"),
+            ),
+        ]);
+        let mut index = DefinitionIndex::default();
+        for (file, rope) in files.iter() {
+            index.update_file(file, rope);
+        }
+        let order: Vec<&str> = by_proximity(
+            index.definitions("sq"),
+            index.include_graph(),
+            "file:///p/lib.forth",
+        )
+        .into_iter()
+        .map(|(file, _)| file.as_str())
+        .collect();
+        assert_eq!(
+            vec![
+                "file:///p/lib.forth",
+                "file:///p/main.forth",
+                "file:///p/a.forth"
+            ],
+            order
+        );
     }
 }
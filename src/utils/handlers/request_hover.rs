@@ -1,8 +1,20 @@
 #[allow(unused_imports)]
 use crate::prelude::*;
 use crate::{
+    ast::Program,
+    definition_index::{DefKind, Definition, DefinitionIndex, UseSite},
+    environment::{environment_query_at, environment_query_documentation},
+    exceptions::{parse_number, throw_code_meaning, thrown_at},
+    markers::markers,
+    project::{ProjectWords, Projects},
     utils::{
-        ropey::{get_ix::GetIx, word_on_or_before::WordOnOrBefore},
+        blocks::{is_block_file, screen_line},
+        data_to_position::ToPosition,
+        include_graph::load_included_files,
+        plain_text::{to_plain_text, PLAIN_TEXT_WIDTH},
+        position_encoding::PositionEncoding,
+        ropey::{bounds::RopeBoundsCheck, get_ix::GetIx},
+        token_at::WordTokenAt,
         HashMapGetForLSPParams,
     },
     words::{Word, Words},
@@ -10,21 +22,28 @@ use crate::{
 
 use std::collections::HashMap;
 
+use forth_lexer::{parser::Lexer, token::Token};
 use lsp_server::{Connection, Message, Request, Response};
-use lsp_types::{request::HoverRequest, Hover};
+use lsp_types::{
+    request::HoverRequest, ClientCapabilities, Hover, HoverContents, MarkupContent, MarkupKind,
+};
 use ropey::Rope;
 
-use super::cast;
+use super::{cast, request_reference_count::reference_count};
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_hover(
     req: &Request,
     connection: &Connection,
-    data: &Words,
+    projects: &Projects,
+    words: &ProjectWords,
     files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+    client: &ClientCapabilities,
+    encoding: PositionEncoding,
 ) -> Result<()> {
     match cast::<HoverRequest>(req.clone()) {
-        Ok((id, params)) => {
-            eprintln!("#{id}: {params:?}");
+        Ok((id, mut params)) => {
             let rope = if let Some(rope) =
                 files.for_position_param(&params.text_document_position_params)
             {
@@ -38,28 +57,61 @@ pub fn handle_hover(
                         .to_string(),
                 ));
             };
-            let ix = rope.get_ix(&params);
-            if ix >= rope.len_chars() {
-                return Err(Error::OutOfBounds(ix));
+            let position = &mut params.text_document_position_params.position;
+            *position = encoding.decode(rope, *position);
+            let ix = rope.get_ix(&params)?;
+            let key = params
+                .text_document_position_params
+                .text_document
+                .uri
+                .to_string();
+            let data = words.for_file(&key);
+            let word = index
+                .words(&key)
+                .word_at(ix)
+                .map(|span| span.text(rope))
+                .unwrap_or_default();
+            let progn = rope.to_string();
+            let mut lexer = Lexer::new(progn.as_str());
+            let tokens = lexer.parse();
+            if !word.is_empty() && !index.is_defined(&word) {
+                // it may be defined in an included file outside the workspace
+                load_included_files(&key, files, index);
             }
-            let word = rope.word_on_or_before(ix);
-            let result = if !word.len_chars() > 0 {
-                let default_info = &Word::default();
-                let info = data
-                    .words
-                    .iter()
-                    .find(|x| x.token.to_lowercase() == (word.to_string().to_lowercase().as_str()))
-                    .unwrap_or(&default_info);
-                Some(Hover {
-                    contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
-                        kind: lsp_types::MarkupKind::Markdown,
-                        value: info.documentation(),
-                    }),
-                    range: None,
-                })
+            let position = params.text_document_position_params.position;
+            let nearest_preceding = projects.config_for(&key).definition.nearest_preceding;
+            let order = if nearest_preceding {
+                index.include_graph().load_order(&key, position)
             } else {
-                None
+                vec![]
             };
+            let at = nearest_preceding.then_some(UseSite {
+                file: &key,
+                position,
+                order: &order,
+            });
+            let value = match definition_summary(&tokens, ix, index).or_else(|| {
+                environment_query_at(&tokens, ix)
+                    .and_then(|query| environment_query_documentation(&query.text))
+            }) {
+                Some(summary) => Some(summary),
+                None if word.is_empty() => None,
+                None => Some(match thrown_at(&tokens, ix) {
+                    Some(thrown) => throw_documentation(thrown.value, data, files, index, at),
+                    None => documentation(&word, data, files, index, at, &mut vec![]),
+                }),
+            };
+            let kind = hover_format(client);
+            let result = value.map(|value| Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    value: match kind {
+                        MarkupKind::PlainText => to_plain_text(&value, PLAIN_TEXT_WIDTH),
+                        MarkupKind::Markdown => value,
+                    },
+                    kind,
+                }),
+                range: None,
+            });
             let result = serde_json::to_value(result).expect("Must be able to serialize the Hover");
             let resp = Response {
                 id,
@@ -72,9 +124,397 @@ pub fn handle_hover(
                 .map_err(|err| Error::SendError(err.to_string()))?;
             Ok(())
         }
-        Err(Error::ExtractRequestError(req)) => Err(Error::ExtractRequestError(req)),
-        Err(err) => panic!("{err:?}"),
-        // Err(err @ ExtractError::JsonError { .. }) => panic!("{err:?}"),
-        // Err(ExtractError::MethodMismatch(req)) => req,
+        Err(err) => Err(err),
+    }
+}
+
+/// The markup the client prefers hovers in, markdown unless it asks for plain text first
+fn hover_format(client: &ClientCapabilities) -> MarkupKind {
+    client
+        .text_document
+        .as_ref()
+        .and_then(|text_document| text_document.hover.as_ref())
+        .and_then(|hover| hover.content_format.as_ref())
+        .and_then(|formats| formats.first().cloned())
+        .unwrap_or(MarkupKind::Markdown)
+}
+
+/// Definitions of `word` as seen from `at`, every definition without a use site
+fn definitions_at<'a>(
+    word: &str,
+    index: &'a DefinitionIndex,
+    at: Option<UseSite>,
+) -> Vec<(&'a String, &'a Definition)> {
+    match at {
+        Some(site) => index.resolve(word, site),
+        None => index.definitions(word),
+    }
+}
+
+/// Documentation of a builtin or user word, synonyms show the documentation of their target.
+/// A user word redefining a builtin earlier in the file shows the user definition, followed
+/// by the builtin collapsed
+fn documentation<'a>(
+    word: &'a str,
+    data: &Words,
+    files: &HashMap<String, Rope>,
+    index: &'a DefinitionIndex,
+    at: Option<UseSite>,
+    seen: &mut Vec<&'a str>,
+) -> String {
+    seen.push(word);
+    if let Some(target) = index.alias_target(word) {
+        if !seen.iter().any(|seen| seen.eq_ignore_ascii_case(target)) {
+            return format!(
+                "`{}` is a synonym of `{}`\n\n{}",
+                word,
+                target,
+                documentation(target, data, files, index, at, seen)
+            );
+        }
+    }
+    let default_info = &Word::default();
+    let info = data
+        .words
+        .iter()
+        .find(|x| x.token.to_lowercase() == word.to_lowercase().as_str())
+        .unwrap_or(default_info);
+    let builtin = !info.token.is_empty();
+    let shadowed = match at {
+        Some(site) => index.in_effect(word, site).is_some(),
+        None => builtin && index.is_defined(word),
+    };
+    if (!builtin && index.is_defined(word)) || shadowed {
+        let mut ret = user_documentation(word, files, definitions_at(word, index, at));
+        if builtin {
+            ret.push_str(&shadowed_builtin(info));
+        }
+        ret
+    } else if data.is_override(word) {
+        format!(
+            "{}\n\n*Project-specific documentation of a standard word*",
+            info.documentation()
+        )
+    } else if data.is_extension(word) {
+        format!(
+            "{}\n\n*Common extension, not part of Forth 2012*",
+            info.documentation()
+        )
+    } else {
+        info.documentation()
+    }
+}
+
+/// Summary of the definition whose `:` or `;` is at `ix`: its name and stack comment, how
+/// many words its body has and how often it is used
+fn definition_summary(tokens: &[Token], ix: usize, index: &DefinitionIndex) -> Option<String> {
+    let program = Program::parse(tokens);
+    let definition = program.definitions().find(|definition| {
+        definition.colon.start == ix
+            || definition
+                .semicolon
+                .is_some_and(|semicolon| semicolon.start == ix)
+    })?;
+    let name = if definition.is_noname() {
+        None
+    } else {
+        definition.name
+    };
+    let mut ret = match name {
+        Some(name) => format!("# `{}`", name.value),
+        None => "# `:NONAME`".to_string(),
+    };
+    if let Some(stack) = definition.stack_comment() {
+        ret.push_str(&format!("   `{}`", stack.value));
+    }
+    let size = definition
+        .levels()
+        .iter()
+        .filter(|(token, _)| matches!(token, Token::Word(_) | Token::Number(_)))
+        .filter(|(token, _)| Some(token.get_data().start) != definition.name.map(|name| name.start))
+        .count();
+    ret.push_str(&format!("\n\nDefinition of {}", plural(size, "word")));
+    if let Some(name) = name {
+        let uses = reference_count(index, name.value).total;
+        ret.push_str(&format!(", used {}", plural(uses, "time")));
+    }
+    Some(ret)
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+/// Note below a rule that a user definition shadows the standard `info`, with its stack effect
+fn shadowed_builtin(info: &Word) -> String {
+    let mut ret = format!("\n\n---\n\nAlso a standard word: `{}`", info.token);
+    if !info.stack.is_empty() {
+        ret.push_str(&format!("   `{}`", info.stack));
+    }
+    ret
+}
+
+/// A literal or constant passed to `THROW`, with the meaning of standard codes
+fn throw_documentation(
+    thrown: &str,
+    data: &Words,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    at: Option<UseSite>,
+) -> String {
+    let (mut ret, code) = match parse_number(thrown) {
+        Some(code) => (format!("# THROW `{}`", thrown), Some(code)),
+        None => (
+            documentation(thrown, data, files, index, at, &mut vec![]),
+            definitions_at(thrown, index, at)
+                .iter()
+                .find_map(|(_, definition)| definition.constant.as_deref())
+                .and_then(parse_number),
+        ),
+    };
+    if let Some(code) = code {
+        ret.push_str(&format!("\n\nTHROW code `{}`", code));
+        if let Some(meaning) = throw_code_meaning(code) {
+            ret.push_str(&format!(": {}", meaning));
+        }
+    }
+    ret
+}
+
+/// What running the marker `definition` forgets of the file it is defined in
+fn marker_forgets(rope: Option<&Rope>, definition: &Definition) -> String {
+    let Some(rope) = rope else {
+        return String::new();
+    };
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    let Some(marker) = markers(&tokens)
+        .into_iter()
+        .find(|marker| marker.name.to_position_start(rope) == definition.selection_range.start)
+    else {
+        return String::new();
+    };
+    if marker.forgets.is_empty() {
+        return "\n\nRunning it forgets only itself".to_string();
+    }
+    let names: Vec<String> = marker
+        .forgets
+        .iter()
+        .map(|name| format!("`{}`", name.value))
+        .collect();
+    format!("\n\nRunning it forgets itself and {}", names.join(", "))
+}
+
+/// Where a user word is defined, block files also name the screen and line
+fn user_documentation(
+    word: &str,
+    files: &HashMap<String, Rope>,
+    definitions: Vec<(&String, &Definition)>,
+) -> String {
+    let mut ret = format!("# `{}`", word);
+    for (file, definition) in definitions {
+        let start = definition.range.start;
+        if let Some(doc) = &definition.doc {
+            ret.push_str(&format!("\n\n{}", doc));
+        }
+        if let Some(constant) = &definition.constant {
+            ret.push_str(&format!("\n\nConstant `{}`", constant));
+        }
+        if let Some(field_of) = &definition.field_of {
+            ret.push_str(&format!(
+                "\n\nField of `{}` at offset `{}`",
+                field_of.structure, field_of.offset
+            ));
+        }
+        if definition.kind == DefKind::Marker {
+            ret.push_str(&marker_forgets(files.get(file), definition));
+        }
+        ret.push_str(&format!("\n\nDefined in {}:{}", file, start.line + 1));
+        let block_ix = files
+            .get(file)
+            .filter(|_| is_block_file(file))
+            .and_then(|rope| rope.char_at(start));
+        if let Some(ix) = block_ix {
+            let (screen, line) = screen_line(ix);
+            ret.push_str(&format!(", Screen {}, line {}", screen, line));
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lsp_types::Position;
+
+    #[test]
+    fn synonyms_show_their_target() {
+        let rope = Rope::from_str("synonym twin dup\nsynonym a b\nsynonym b a");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let files = HashMap::from([("/src/a.forth".to_string(), rope)]);
+        let data = Words::default();
+        let hover = documentation("twin", &data, &files, &index, None, &mut vec![]);
+        assert!(hover.starts_with("`twin` is a synonym of `dup`\n\n# `DUP`"));
+        let hover = documentation("a", &data, &files, &index, None, &mut vec![]);
+        assert!(hover.starts_with("`a` is a synonym of `b`\n\n# `b`"));
+    }
+
+    #[test]
+    fn project_documentation_overrides_standard_words() {
+        let custom = [Word {
+            token: "DUP",
+            stack: "( x -- x x )",
+            help: "Prefer OVER for clarity",
+            ..Default::default()
+        }];
+        let data = Words::with_custom(&custom, true);
+        let index = DefinitionIndex::default();
+        let files = HashMap::new();
+        assert_eq!(
+            1,
+            data.words
+                .iter()
+                .filter(|word| word.token.eq_ignore_ascii_case("dup"))
+                .count()
+        );
+        assert_eq!(
+            "# `DUP`   `( x -- x x )`\n\nPrefer OVER for clarity\n\n*Project-specific documentation of a standard word*",
+            documentation("dup", &data, &files, &index, None, &mut vec![])
+        );
+        assert!(
+            !documentation("drop", &data, &files, &index, None, &mut vec![])
+                .contains("Project-specific")
+        );
+    }
+
+    #[test]
+    fn common_extensions_are_marked_as_such() {
+        let data = Words::with_custom(&[], true);
+        let index = DefinitionIndex::default();
+        let files = HashMap::new();
+        assert!(
+            documentation("-rot", &data, &files, &index, None, &mut vec![])
+                .ends_with("*Common extension, not part of Forth 2012*")
+        );
+        assert!(
+            !documentation("rot", &data, &files, &index, None, &mut vec![])
+                .contains("Common extension")
+        );
+    }
+
+    #[test]
+    fn fields_show_structure_and_offset() {
+        let rope = Rope::from_str("begin-structure point field: p.x field: p.y end-structure");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let files = HashMap::from([("/src/a.forth".to_string(), rope)]);
+        assert_eq!(
+            "# `p.y`\n\nField of `point` at offset `1 CELLS`\n\nDefined in /src/a.forth:1",
+            user_documentation("p.y", &files, index.definitions("p.y"))
+        );
+    }
+
+    #[test]
+    fn thrown_codes_show_their_meaning() {
+        let rope = Rope::from_str("-4 constant underflow");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let files = HashMap::from([("/src/a.forth".to_string(), rope)]);
+        let data = Words::default();
+        assert_eq!(
+            "# THROW `-10`\n\nTHROW code `-10`: division by zero",
+            throw_documentation("-10", &data, &files, &index, None)
+        );
+        assert_eq!(
+            "# `underflow`\n\nConstant `-4`\n\nDefined in /src/a.forth:1\n\nTHROW code `-4`: stack underflow",
+            throw_documentation("underflow", &data, &files, &index, None)
+        );
+        assert_eq!(
+            "# THROW `-100`\n\nTHROW code `-100`",
+            throw_documentation("-100", &data, &files, &index, None)
+        );
+    }
+
+    #[test]
+    fn block_definitions_show_screen() {
+        let mut source = " ".repeat(42 * 1024 + 7 * 64);
+        source.push_str(": blink 1 ;");
+        let rope = Rope::from_str(&source);
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/app.blk", &rope);
+        let files = HashMap::from([("/src/app.blk".to_string(), rope)]);
+        assert_eq!(
+            "# `blink`\n\nDefined in /src/app.blk:1, Screen 42, line 7",
+            user_documentation("blink", &files, index.definitions("blink"))
+        );
+    }
+
+    #[test]
+    fn redefinitions_show_the_definition_in_effect() {
+        let rope = Rope::from_str(": sq dup * ;\n: sq sq sq ;\nsq\n: dup 1 ;\ndup");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let files = HashMap::from([("/src/a.forth".to_string(), rope)]);
+        let data = Words::default();
+        let graph = index.include_graph();
+        let hover = |word: &'static str, line, character| {
+            let position = Position::new(line, character);
+            let order = graph.load_order("/src/a.forth", position);
+            let site = UseSite {
+                file: "/src/a.forth",
+                position,
+                order: &order,
+            };
+            documentation(word, &data, &files, &index, Some(site), &mut vec![])
+        };
+        assert_eq!("# `sq`\n\nDefined in /src/a.forth:1", hover("sq", 1, 6));
+        assert_eq!("# `sq`\n\nDefined in /src/a.forth:2", hover("sq", 2, 1));
+        assert!(hover("dup", 2, 1).starts_with("# `DUP`"));
+        assert_eq!(
+            "# `dup`\n\nDefined in /src/a.forth:4\n\n---\n\nAlso a standard word: `DUP`   `( x -- x x )`",
+            hover("dup", 4, 1)
+        );
+        let hover = documentation("dup", &data, &files, &index, None, &mut vec![]);
+        assert!(hover.starts_with("# `dup`\n\nDefined in /src/a.forth:4"));
+        assert!(hover.ends_with("Also a standard word: `DUP`   `( x -- x x )`"));
+    }
+
+    #[test]
+    fn colon_and_semicolon_summarize_their_definition() {
+        let rope = Rope::from_str(": sq ( n -- n ) dup * ;\n: cube dup sq * ;\n5 sq\n:noname 1 ;");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let progn = rope.to_string();
+        let tokens = Lexer::new(progn.as_str()).parse();
+        let summary = "# `sq`   `( n -- n )`\n\nDefinition of 2 words, used 2 times";
+        assert_eq!(
+            Some(summary),
+            definition_summary(&tokens, 0, &index).as_deref()
+        );
+        assert_eq!(
+            Some(summary),
+            definition_summary(&tokens, 22, &index).as_deref()
+        );
+        assert_eq!(None, definition_summary(&tokens, 2, &index));
+        assert_eq!(
+            Some("# `:NONAME`\n\nDefinition of 1 word"),
+            definition_summary(&tokens, 47, &index).as_deref()
+        );
+    }
+
+    #[test]
+    fn markers_show_what_they_forget() {
+        let rope = Rope::from_str("marker -app\n: sq dup * ;\nvariable count\n");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let files = HashMap::from([("/src/a.forth".to_string(), rope)]);
+        let doc = documentation("-app", &Words::default(), &files, &index, None, &mut vec![]);
+        assert!(doc.contains("Running it forgets itself and `sq`, `count`"));
     }
 }
@@ -0,0 +1,104 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    loops::loop_index_uses,
+    utils::{data_to_position::ToPosition, position_encoding::PositionEncoding},
+};
+
+use std::collections::HashMap;
+
+use forth_lexer::parser::Lexer;
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::InlayHintRequest, InlayHint, InlayHintLabel, InlayHintTooltip, Range};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_inlay_hint(
+    req: &Request,
+    connection: &Connection,
+    files: &HashMap<String, Rope>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<InlayHintRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let range = encoding.decode_range(rope, params.range);
+            let mut result = loop_index_hints(rope, range);
+            for hint in result.iter_mut() {
+                hint.position = encoding.encode(rope, hint.position);
+            }
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Which loop of its definition each `I` and `J` in `range` reads the index of, numbered
+/// by their `DO` in source order
+fn loop_index_hints(rope: &Rope, range: Range) -> Vec<InlayHint> {
+    let progn = rope.to_string();
+    let mut lexer = Lexer::new(progn.as_str());
+    let tokens = lexer.parse();
+    loop_index_uses(&tokens)
+        .into_iter()
+        .filter_map(|found| {
+            let position = found.word.to_position_end(rope);
+            if position < range.start || range.end < position {
+                return None;
+            }
+            let opener = found.opener?;
+            Some(InlayHint {
+                position,
+                label: InlayHintLabel::String(format!("loop {}", found.number?)),
+                kind: None,
+                text_edits: None,
+                tooltip: Some(InlayHintTooltip::String(format!(
+                    "Index of the `{}` on line {}",
+                    opener.value,
+                    opener.to_position_start(rope).line + 1
+                ))),
+                padding_left: Some(true),
+                padding_right: None,
+                data: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn hints_name_the_loop_of_each_index() {
+        let rope = Rope::from_str(": grid\n  3 0 do\n    3 0 do i j * . loop\n  loop ;\n: x i ;");
+        let whole = Range::new(Position::new(0, 0), Position::new(5, 0));
+        let hints: Vec<(Position, String)> = loop_index_hints(&rope, whole)
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position, label),
+                InlayHintLabel::LabelParts(_) => panic!("Hints are plain labels"),
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (Position::new(2, 12), "loop 2".to_string()),
+                (Position::new(2, 14), "loop 1".to_string()),
+            ],
+            hints
+        );
+        let first_line = Range::new(Position::new(0, 0), Position::new(1, 0));
+        assert!(loop_index_hints(&rope, first_line).is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::utils::{
+    position_encoding::PositionEncoding,
+    ropey::get_ix::GetIx,
+    token_at::{word_spans, WordTokenAt},
+    HashMapGetForLSPParams,
+};
+
+use std::collections::HashMap;
+
+use forth_lexer::{
+    context::ContextTokens,
+    parser::Lexer,
+    token::{Data, Token},
+};
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::LinkedEditingRange, LinkedEditingRanges};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_linked_editing_range(
+    req: &Request,
+    connection: &Connection,
+    files: &mut HashMap<String, Rope>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<LinkedEditingRange>(req.clone()) {
+        Ok((id, mut params)) => {
+            let rope = if let Some(rope) =
+                files.for_position_param(&params.text_document_position_params)
+            {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(
+                    params
+                        .text_document_position_params
+                        .text_document
+                        .uri
+                        .to_string(),
+                ));
+            };
+            let position = &mut params.text_document_position_params.position;
+            *position = encoding.decode(rope, *position);
+            let ix = rope.get_ix(&params)?;
+            let progn = rope.to_string();
+            let mut lexer = Lexer::new(progn.as_str());
+            let tokens = lexer.parse();
+            // a missing result tells the client to fall back to a regular edit
+            let result = linked_occurrences(&tokens, ix).map(|occurrences| LinkedEditingRanges {
+                ranges: occurrences
+                    .iter()
+                    .map(|data| encoding.data_range(data, rope))
+                    .collect(),
+                word_pattern: Some("[^\\s]+".to_string()),
+            });
+            let result = serde_json::to_value(result)
+                .expect("Must be able to serialize the LinkedEditingRanges");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// All occurrences of the word under `ix` outside strings, provided that word is defined with
+/// `:` in `tokens`
+fn linked_occurrences<'a>(tokens: &Vec<Token<'a>>, ix: usize) -> Option<Vec<Data<'a>>> {
+    let start = word_spans(tokens).word_token_at(ix)?.start;
+    let word = tokens.iter().find_map(|token| match token {
+        Token::Word(data) if data.start == start => Some(data.value),
+        _ => None,
+    })?;
+    let is_defined = tokens
+        .windows(2)
+        .any(|pair| matches!(pair, [Token::Colon(_), Token::Word(name)] if name.value == word));
+    if !is_defined {
+        return None;
+    }
+    Some(
+        ContextTokens::new(tokens.iter().cloned())
+            .filter(|context| !context.in_string && !context.parsed)
+            .filter_map(|context| match context.token {
+                Token::Word(data) if data.value == word => Some(data),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_definition_and_references() {
+        let mut lexer = Lexer::new(": add1 1 + ;\n2 add1 add1");
+        let tokens = lexer.parse();
+        let occurrences = linked_occurrences(&tokens, 3).unwrap();
+        let starts: Vec<usize> = occurrences.iter().map(|data| data.start).collect();
+        assert_eq!(vec![2, 15, 20], starts);
+    }
+
+    #[test]
+    fn skips_string_literals() {
+        let mut lexer = Lexer::new(": add1 1 + ;\n.\" add1\" s\" add1\" 2 add1");
+        let tokens = lexer.parse();
+        let occurrences = linked_occurrences(&tokens, 3).unwrap();
+        let starts: Vec<usize> = occurrences.iter().map(|data| data.start).collect();
+        assert_eq!(vec![2, 33], starts);
+    }
+
+    #[test]
+    fn ignores_words_without_definition() {
+        let mut lexer = Lexer::new(": add1 1 + ;\n2 dup");
+        let tokens = lexer.parse();
+        assert!(linked_occurrences(&tokens, 15).is_none());
+    }
+}
@@ -0,0 +1,214 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::{DefKind, Definition, DefinitionIndex, UseSite},
+    project::{ProjectWords, Projects},
+    utils::{
+        display_path, file_path, position_encoding::PositionEncoding, ropey::get_ix::GetIx,
+        token_at::WordTokenAt, HashMapGetForLSPParams,
+    },
+    words::Words,
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::MonikerRequest, Moniker, MonikerKind, UniquenessLevel};
+use ropey::Rope;
+
+use super::cast;
+
+/// Scheme of the monikers of words defined in a project, identified by
+/// `path:kind:NAME` with the path relative to the project root
+pub const MONIKER_SCHEME: &str = "forth-lsp";
+
+/// Scheme of the monikers of standard words, identified by their upper cased name
+pub const STANDARD_MONIKER_SCHEME: &str = "forth-standard";
+
+pub fn handle_moniker(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &mut HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<MonikerRequest>(req.clone()) {
+        Ok((id, mut params)) => {
+            let params = &mut params.text_document_position_params;
+            let rope = if let Some(rope) = files.for_position_param(params) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(params.text_document.uri.to_string()));
+            };
+            params.position = encoding.decode(rope, params.position);
+            let ix = rope.get_ix(&*params)?;
+            let key = params.text_document.uri.to_string();
+            let word = index
+                .words(&key)
+                .word_token_at(ix)
+                .map(|span| span.text(rope))
+                .unwrap_or_default();
+            let result = if word.is_empty() {
+                None
+            } else {
+                let order = index.include_graph().load_order(&key, params.position);
+                let site = UseSite {
+                    file: &key,
+                    position: params.position,
+                    order: &order,
+                };
+                Some(monikers(projects, words.for_file(&key), index, &word, site))
+            };
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Stable identifiers of what `word` at `site` refers to, for indexers linking uses across
+/// repositories: exported where it is defined, imported from another file, local otherwise
+fn monikers(
+    projects: &Projects,
+    data: &Words,
+    index: &DefinitionIndex,
+    word: &str,
+    site: UseSite,
+) -> Vec<Moniker> {
+    let definitions = index.resolve(word, site);
+    if definitions.is_empty() {
+        let standard = data
+            .words
+            .iter()
+            .any(|builtin| builtin.token.eq_ignore_ascii_case(word));
+        if !standard {
+            return vec![];
+        }
+        return vec![Moniker {
+            scheme: STANDARD_MONIKER_SCHEME.to_string(),
+            identifier: word.to_uppercase(),
+            unique: UniquenessLevel::Scheme,
+            kind: Some(MonikerKind::Import),
+        }];
+    }
+    definitions
+        .into_iter()
+        .map(|(file, definition)| {
+            let kind = if *file != site.file {
+                MonikerKind::Import
+            } else if definition.selection_range.start <= site.position
+                && site.position <= definition.selection_range.end
+            {
+                MonikerKind::Export
+            } else {
+                MonikerKind::Local
+            };
+            Moniker {
+                scheme: MONIKER_SCHEME.to_string(),
+                identifier: moniker_identifier(projects, file, definition),
+                unique: UniquenessLevel::Project,
+                kind: Some(kind),
+            }
+        })
+        .collect()
+}
+
+/// `path:kind:NAME` of `definition` in `file`, the same for its scanned path and its URI
+pub fn moniker_identifier(projects: &Projects, file: &str, definition: &Definition) -> String {
+    let root = file_path(file)
+        .and_then(|path| projects.project_for_path(&path))
+        .map(|project| project.root.as_path());
+    format!(
+        "{}:{}:{}",
+        display_path(file, root),
+        kind_name(definition.kind),
+        definition.name.to_uppercase()
+    )
+}
+
+fn kind_name(kind: DefKind) -> &'static str {
+    match kind {
+        DefKind::Colon => "colon",
+        DefKind::Noname => "noname",
+        DefKind::Synonym => "synonym",
+        DefKind::Variable => "variable",
+        DefKind::Constant => "constant",
+        DefKind::Value => "value",
+        DefKind::Defer => "defer",
+        DefKind::Create => "create",
+        DefKind::Buffer => "buffer",
+        DefKind::Marker => "marker",
+        DefKind::Structure => "structure",
+        DefKind::Field => "field",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lsp_types::Position;
+
+    #[test]
+    fn monikers_of_definitions_uses_and_builtins() {
+        let mut projects = Projects::default();
+        projects.insert("/p".into(), Default::default());
+        let mut index = DefinitionIndex::default();
+        index.update_file("/p/lib/math.fs", &Rope::from_str("variable total"));
+        index.update_file(
+            "file:///p/main.fs",
+            &Rope::from_str(": sq dup * ;\n2 sq total"),
+        );
+        let data = Words::default();
+        let at = |word: &str, line: u32, character: u32| {
+            let site = UseSite {
+                file: "file:///p/main.fs",
+                position: Position::new(line, character),
+                order: &[],
+            };
+            monikers(&projects, &data, &index, word, site)
+                .into_iter()
+                .map(|moniker| (moniker.scheme, moniker.identifier, moniker.kind))
+                .collect::<Vec<_>>()
+        };
+        let forth = MONIKER_SCHEME.to_string();
+        assert_eq!(
+            vec![(
+                forth.clone(),
+                "main.fs:colon:SQ".to_string(),
+                Some(MonikerKind::Export)
+            )],
+            at("sq", 0, 3)
+        );
+        assert_eq!(
+            vec![(
+                forth.clone(),
+                "main.fs:colon:SQ".to_string(),
+                Some(MonikerKind::Local)
+            )],
+            at("sq", 1, 3)
+        );
+        assert_eq!(
+            vec![(
+                forth,
+                "lib/math.fs:variable:TOTAL".to_string(),
+                Some(MonikerKind::Import)
+            )],
+            at("total", 1, 6)
+        );
+        assert_eq!(
+            vec![(
+                STANDARD_MONIKER_SCHEME.to_string(),
+                "DUP".to_string(),
+                Some(MonikerKind::Import)
+            )],
+            at("dup", 0, 6)
+        );
+        assert!(at("frob", 1, 0).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    utils::{
+        position_encoding::PositionEncoding, ropey::get_ix::GetIx, token_at::WordTokenAt,
+        HashMapGetForLSPParams,
+    },
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, ErrorCode, Message, Request, Response};
+use lsp_types::{request::PrepareRenameRequest, PrepareRenameResponse};
+use ropey::Rope;
+
+use super::{cast, request_rename::builtin_rename_message};
+
+pub fn handle_prepare_rename(
+    req: &Request,
+    connection: &Connection,
+    files: &mut HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<PrepareRenameRequest>(req.clone()) {
+        Ok((id, mut params)) => {
+            let rope = if let Some(rope) = files.for_position_param(&params) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(params.text_document.uri.to_string()));
+            };
+            params.position = encoding.decode(rope, params.position);
+            let ix = rope.get_ix(&params)?;
+            let key = params.text_document.uri.to_string();
+            let word = index
+                .words(&key)
+                .word_token_at(ix)
+                .map(|span| (span.text(rope), span.range(rope)));
+            let resp = match word {
+                None => Response::new_ok(id, None::<PrepareRenameResponse>),
+                Some((word, _)) if !index.is_defined(&word) => Response::new_err(
+                    id,
+                    ErrorCode::RequestFailed as i32,
+                    builtin_rename_message(&word),
+                ),
+                Some((word, range)) => Response::new_ok(
+                    id,
+                    PrepareRenameResponse::RangeWithPlaceholder {
+                        range: encoding.encode_range(rope, range),
+                        placeholder: word,
+                    },
+                ),
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
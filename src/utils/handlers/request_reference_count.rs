@@ -0,0 +1,103 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{definition_index::DefinitionIndex, utils::file_uri};
+
+use std::collections::BTreeMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::Url;
+use serde::{Deserialize, Serialize};
+
+use super::cast;
+
+/// Custom request counting the uses of a word per file, for editors to show without asking
+/// for every location
+pub enum ReferenceCount {}
+
+impl lsp_types::request::Request for ReferenceCount {
+    type Params = ReferenceCountParams;
+    type Result = ReferenceCountResult;
+    const METHOD: &'static str = "forth-lsp/referenceCount";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReferenceCountParams {
+    pub word: String,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceCountResult {
+    /// Uses in every file, the names of definitions not counted
+    pub total: usize,
+    /// Uses per file, files without any left out
+    pub files: BTreeMap<Url, usize>,
+}
+
+pub fn handle_reference_count(
+    req: &Request,
+    connection: &Connection,
+    index: &DefinitionIndex,
+) -> Result<()> {
+    match cast::<ReferenceCount>(req.clone()) {
+        Ok((id, params)) => {
+            let result = reference_count(index, &params.word);
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub fn reference_count(index: &DefinitionIndex, word: &str) -> ReferenceCountResult {
+    let mut files: BTreeMap<Url, usize> = BTreeMap::new();
+    for (file, _, is_declaration) in index.references(word) {
+        if is_declaration {
+            continue;
+        }
+        if let Some(uri) = file_uri(file) {
+            *files.entry(uri).or_default() += 1;
+        }
+    }
+    ReferenceCountResult {
+        total: files.values().sum(),
+        files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ropey::Rope;
+
+    #[test]
+    fn counts_uses_per_file() {
+        let mut index = DefinitionIndex::default();
+        index.update_file(
+            "/p/lib.forth",
+            &Rope::from_str(": sq dup * ;\n: cube dup sq * ;"),
+        );
+        index.update_file("/p/main.forth", &Rope::from_str("2 sq 3 SQ cube"));
+        let result = reference_count(&index, "sq");
+        assert_eq!(3, result.total);
+        let files: Vec<(String, usize)> = result
+            .files
+            .into_iter()
+            .map(|(uri, count)| (uri.to_string(), count))
+            .collect();
+        assert_eq!(
+            vec![
+                ("file:///p/lib.forth".to_string(), 1),
+                ("file:///p/main.forth".to_string(), 2)
+            ],
+            files
+        );
+        assert_eq!(
+            ReferenceCountResult::default(),
+            reference_count(&index, "frob")
+        );
+    }
+}
@@ -0,0 +1,88 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    project::Projects,
+    utils::{
+        file_uri, include_graph::files_in_scope, position_encoding::PositionEncoding,
+        ropey::get_ix::GetIx, token_at::WordTokenAt, HashMapGetForLSPParams,
+    },
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::References, Location};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_references(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    files: &mut HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<References>(req.clone()) {
+        Ok((id, mut params)) => {
+            let rope = if let Some(rope) = files.for_position_param(&params.text_document_position)
+            {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(
+                    params.text_document_position.text_document.uri.to_string(),
+                ));
+            };
+            let position = &mut params.text_document_position.position;
+            *position = encoding.decode(rope, *position);
+            let ix = rope.get_ix(&params)?;
+            let key = params.text_document_position.text_document.uri.to_string();
+            let word = index
+                .words(&key)
+                .word_token_at(ix)
+                .map(|span| span.text(rope));
+            let mut ret: Vec<Location> = vec![];
+            if let Some(word) = word {
+                let scope = files_in_scope(
+                    projects
+                        .config_for(params.text_document_position.text_document.uri.as_ref())
+                        .references
+                        .scope,
+                    index,
+                    params.text_document_position.text_document.uri.as_ref(),
+                );
+                for (file, reference, is_declaration) in index.references(&word) {
+                    if scope.as_ref().is_some_and(|scope| !scope.contains(file)) {
+                        continue;
+                    }
+                    if is_declaration && !params.context.include_declaration {
+                        continue;
+                    }
+                    if let Some(uri) = file_uri(file) {
+                        ret.push(Location {
+                            uri,
+                            range: encoding.encode_file_range(files, file, reference.range),
+                        });
+                    } else {
+                        eprintln!("Failed to parse URI for {}", file);
+                    }
+                }
+            }
+            let result =
+                serde_json::to_value(ret).expect("Must be able to serialize the Location list");
+            let resp = Response {
+                id,
+                result: Some(result),
+                error: None,
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,159 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    project::Projects,
+    utils::{
+        file_uri, include_graph::files_in_scope, position_encoding::PositionEncoding,
+        ropey::get_ix::GetIx, token_at::WordTokenAt, HashMapGetForLSPParams,
+    },
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, ErrorCode, Message, Request, Response};
+use lsp_types::{request::Rename, TextEdit, WorkspaceEdit};
+use ropey::Rope;
+
+use super::cast;
+
+pub fn handle_rename(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    files: &mut HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<Rename>(req.clone()) {
+        Ok((id, mut params)) => {
+            let rope = if let Some(rope) = files.for_position_param(&params.text_document_position)
+            {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(
+                    params.text_document_position.text_document.uri.to_string(),
+                ));
+            };
+            let position = &mut params.text_document_position.position;
+            *position = encoding.decode(rope, *position);
+            let ix = rope.get_ix(&params)?;
+            let key = params.text_document_position.text_document.uri.to_string();
+            let word = index
+                .words(&key)
+                .word_token_at(ix)
+                .map(|span| span.text(rope));
+            let resp = match word {
+                None => Response::new_ok(id, None::<WorkspaceEdit>),
+                Some(word) if !index.is_defined(&word) => Response::new_err(
+                    id,
+                    ErrorCode::RequestFailed as i32,
+                    builtin_rename_message(&word),
+                ),
+                Some(_) if !is_valid_name(&params.new_name) => Response::new_err(
+                    id,
+                    ErrorCode::InvalidParams as i32,
+                    format!("`{}` is not a valid word name", params.new_name),
+                ),
+                Some(word) => {
+                    let scope = files_in_scope(
+                        projects
+                            .config_for(params.text_document_position.text_document.uri.as_ref())
+                            .references
+                            .scope,
+                        index,
+                        params.text_document_position.text_document.uri.as_ref(),
+                    );
+                    let mut changes = HashMap::new();
+                    for (file, mut edits) in rename_edits(index, &word, &params.new_name) {
+                        if scope.as_ref().is_some_and(|scope| !scope.contains(file)) {
+                            continue;
+                        }
+                        if let Some(rope) = files.get(file) {
+                            encoding.encode_edits(rope, &mut edits);
+                        }
+                        if let Some(uri) = file_uri(file) {
+                            changes.insert(uri, edits);
+                        } else {
+                            eprintln!("Failed to parse URI for {}", file);
+                        }
+                    }
+                    Response::new_ok(
+                        id,
+                        WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        },
+                    )
+                }
+            };
+            connection
+                .sender
+                .send(Message::Response(resp))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Edits renaming every indexed use of `word`, per file
+fn rename_edits<'a>(
+    index: &'a DefinitionIndex,
+    word: &str,
+    new_name: &str,
+) -> HashMap<&'a String, Vec<TextEdit>> {
+    let mut ret: HashMap<&String, Vec<TextEdit>> = HashMap::new();
+    for (file, reference, _) in index.references(word) {
+        ret.entry(file).or_default().push(TextEdit {
+            range: reference.range,
+            new_text: new_name.to_owned(),
+        });
+    }
+    ret
+}
+
+pub fn builtin_rename_message(word: &str) -> String {
+    format!(
+        "`{}` has no definition in the workspace, builtin words can not be renamed",
+        word
+    )
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    #[test]
+    fn strings_are_not_renamed() {
+        let mut index = DefinitionIndex::default();
+        index.update_file(
+            "a.forth",
+            &Rope::from_str(": one 1 ;\n: greet .\" one\" s\" one\" type ONE ;"),
+        );
+        let edit = |line, start, end| TextEdit {
+            range: Range {
+                start: Position::new(line, start),
+                end: Position::new(line, end),
+            },
+            new_text: "uno".to_string(),
+        };
+        let edits = rename_edits(&index, "one", "uno");
+        assert_eq!(
+            Some(&vec![edit(0, 2, 5), edit(1, 29, 32)]),
+            edits.get(&"a.forth".to_string())
+        );
+    }
+
+    #[test]
+    fn valid_names() {
+        assert!(is_valid_name("2dup+"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("two words"));
+    }
+}
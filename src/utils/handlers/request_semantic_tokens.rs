@@ -0,0 +1,171 @@
+use crate::definition_index::{DefKind, DefinitionIndex};
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::utils::position_encoding::PositionEncoding;
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{
+    request::SemanticTokensFullRequest, SemanticToken, SemanticTokenModifier, SemanticTokenType,
+    SemanticTokens, SemanticTokensLegend, SemanticTokensResult,
+};
+use ropey::Rope;
+
+use super::cast;
+
+/// Token types of the legend, a token refers to them by index
+pub const TOKEN_TYPES: [SemanticTokenType; 2] =
+    [SemanticTokenType::FUNCTION, SemanticTokenType::VARIABLE];
+/// Token modifiers of the legend, a token sets bit `1 << index` for each of them
+pub const TOKEN_MODIFIERS: [SemanticTokenModifier; 2] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+];
+
+const FUNCTION: u32 = 0;
+const VARIABLE: u32 = 1;
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+pub fn handle_semantic_tokens_full(
+    req: &Request,
+    connection: &Connection,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<SemanticTokensFullRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let Some(rope) = files.get(&uri.to_string()) else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let result = SemanticTokensResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: get_semantic_tokens(index, uri.as_str(), rope, encoding),
+            });
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The type and modifiers of the name of a definition of `kind`, none for fields and
+/// structures, and for `:NONAME` definitions named after a use of the word they are bound to
+fn token_kind(kind: DefKind) -> Option<(u32, u32)> {
+    match kind {
+        DefKind::Colon | DefKind::Synonym | DefKind::Defer | DefKind::Marker => Some((FUNCTION, 0)),
+        DefKind::Variable | DefKind::Create | DefKind::Buffer => Some((VARIABLE, 0)),
+        DefKind::Constant | DefKind::Value => Some((VARIABLE, READONLY)),
+        DefKind::Noname | DefKind::Structure | DefKind::Field => None,
+    }
+}
+
+/// Names of the definitions indexed for `file`, functions for `:` and `DEFER`, variables for
+/// data, read only for constants and values, relative to the token before them as the
+/// protocol encodes them, counting as `encoding` does
+pub fn get_semantic_tokens(
+    index: &DefinitionIndex,
+    file: &str,
+    rope: &Rope,
+    encoding: PositionEncoding,
+) -> Vec<SemanticToken> {
+    let mut ret = vec![];
+    let (mut line, mut character) = (0, 0);
+    for (_, range, kind) in index.definitions_in_file(file) {
+        let Some((token_type, modifiers)) = token_kind(kind) else {
+            continue;
+        };
+        let range = encoding.encode_range(rope, range);
+        let start = range.start;
+        ret.push(SemanticToken {
+            delta_line: start.line - line,
+            delta_start: if start.line == line {
+                start.character - character
+            } else {
+                start.character
+            },
+            length: range.end.character - start.character,
+            token_type,
+            token_modifiers_bitset: modifiers | DECLARATION,
+        });
+        (line, character) = (start.line, start.character);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definition_names_by_defining_word() {
+        let rope = Rope::from_str(
+            ": sq dup * ;\nvariable count 5 constant five\ndefer greet .\" create x\"",
+        );
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let tokens: Vec<(u32, u32, u32, u32, u32)> =
+            get_semantic_tokens(&index, "/src/a.forth", &rope, PositionEncoding::Utf32)
+                .into_iter()
+                .map(|token| {
+                    (
+                        token.delta_line,
+                        token.delta_start,
+                        token.length,
+                        token.token_type,
+                        token.token_modifiers_bitset,
+                    )
+                })
+                .collect();
+        assert_eq!(
+            vec![
+                (0, 2, 2, FUNCTION, DECLARATION),
+                (1, 9, 5, VARIABLE, DECLARATION),
+                (0, 17, 4, VARIABLE, DECLARATION | READONLY),
+                (1, 6, 5, FUNCTION, DECLARATION),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    fn comments_ending_the_file_are_not_names() {
+        let rope = Rope::from_str("5 constant five \\ five\r\nvariable \\");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let tokens: Vec<(u32, u32, u32)> =
+            get_semantic_tokens(&index, "/src/a.forth", &rope, PositionEncoding::Utf32)
+                .into_iter()
+                .map(|token| (token.delta_line, token.delta_start, token.length))
+                .collect();
+        assert_eq!(vec![(0, 11, 4)], tokens);
+    }
+
+    #[test]
+    fn columns_and_lengths_follow_the_encoding() {
+        let rope = Rope::from_str(": é-sq dup * ;\n: 😀 ;");
+        let mut index = DefinitionIndex::default();
+        index.update_file("/src/a.forth", &rope);
+        let tokens = |encoding| -> Vec<(u32, u32, u32)> {
+            get_semantic_tokens(&index, "/src/a.forth", &rope, encoding)
+                .into_iter()
+                .map(|token| (token.delta_line, token.delta_start, token.length))
+                .collect()
+        };
+        assert_eq!(vec![(0, 2, 4), (1, 2, 2)], tokens(PositionEncoding::Utf16));
+        assert_eq!(vec![(0, 2, 5), (1, 2, 4)], tokens(PositionEncoding::Utf8));
+    }
+}
@@ -0,0 +1,133 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    project::ProjectWords,
+    stack_effect::{effect, known_effects, trace, Step},
+};
+
+use std::collections::HashMap;
+
+use forth_lexer::parser::Lexer;
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::Url;
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+
+use super::cast;
+
+/// Custom request for what a phrase of code does to the stack, for editor plugins and REPLs
+pub enum StackEffect {}
+
+impl lsp_types::request::Request for StackEffect {
+    type Params = StackEffectParams;
+    type Result = StackEffectResult;
+    const METHOD: &'static str = "forth-lsp/stackEffect";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StackEffectParams {
+    pub snippet: String,
+    /// The document the snippet is from, whose project's `[builtin]` words it knows
+    #[serde(default)]
+    pub uri: Option<Url>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackEffectResult {
+    /// Like `( x1 -- x2 x3 )`, None when the trace stopped early
+    pub effect: Option<String>,
+    pub steps: Vec<StackStep>,
+    /// The word without a known stack effect, or branching, that ended the trace
+    pub stopped_at: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct StackStep {
+    pub word: String,
+    pub taken: usize,
+    pub left: usize,
+    /// Cells on the stack after the word, relative to before the snippet
+    pub depth: isize,
+}
+
+pub fn handle_stack_effect(
+    req: &Request,
+    connection: &Connection,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+) -> Result<()> {
+    match cast::<StackEffect>(req.clone()) {
+        Ok((id, params)) => {
+            // without a document only the words outside of any project are known
+            let file = params.uri.as_ref().map(Url::as_str).unwrap_or_default();
+            let known = known_effects(words.for_file(file), files);
+            let result = stack_effect(&params.snippet, &known);
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn stack_effect(snippet: &str, known: &HashMap<String, String>) -> StackEffectResult {
+    let mut lexer = Lexer::new(snippet);
+    let tokens = lexer.parse();
+    let (steps, stop) = trace(&tokens, known);
+    let effect = stop.is_none().then(|| {
+        let (taken, left) = effect(&steps);
+        let cells = |range: std::ops::Range<usize>| -> String {
+            range.map(|n| format!(" x{}", n)).collect()
+        };
+        format!(
+            "({} --{} )",
+            cells(1..taken + 1),
+            cells(taken + 1..taken + left + 1)
+        )
+    });
+    StackEffectResult {
+        effect,
+        steps: steps
+            .into_iter()
+            .map(
+                |Step {
+                     word,
+                     taken,
+                     left,
+                     depth,
+                 }| StackStep {
+                    word,
+                    taken,
+                    left,
+                    depth,
+                },
+            )
+            .collect(),
+        stopped_at: stop.map(|token| token.get_data().value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effect_of_a_snippet() {
+        let known = HashMap::from([
+            ("DUP".to_string(), "( x -- x x )".to_string()),
+            ("*".to_string(), "( n1 n2 -- n3 )".to_string()),
+        ]);
+        let result = stack_effect("dup * 2", &known);
+        assert_eq!(Some("( x1 -- x2 x3 )".to_string()), result.effect);
+        assert_eq!(3, result.steps.len());
+        assert_eq!(None, result.stopped_at);
+        let result = stack_effect("2 frob dup", &known);
+        assert_eq!(None, result.effect);
+        assert_eq!(1, result.steps.len());
+        assert_eq!(Some("frob".to_string()), result.stopped_at);
+        assert_eq!(Some("( -- )".to_string()), stack_effect("", &known).effect);
+    }
+}
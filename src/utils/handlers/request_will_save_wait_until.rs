@@ -0,0 +1,57 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    config::BeforeSave,
+    diagnostics::trailing_whitespace,
+    project::{ProjectWords, Projects},
+    utils::position_encoding::PositionEncoding,
+};
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::WillSaveWaitUntil, TextEdit};
+use ropey::Rope;
+
+use super::{cast, request_formatting::formatting_edits};
+
+/// Edits the client applies before saving, as `format.before_save` asks
+pub fn handle_will_save_wait_until(
+    req: &Request,
+    connection: &Connection,
+    projects: &Projects,
+    words: &ProjectWords,
+    files: &HashMap<String, Rope>,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<WillSaveWaitUntil>(req.clone()) {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let rope = if let Some(rope) = files.get(&uri.to_string()) {
+                rope
+            } else {
+                return Err(Error::NoSuchFile(uri.to_string()));
+            };
+            let config = &projects.config_for(uri.as_str()).format;
+            let mut ret = match config.before_save {
+                BeforeSave::Off => vec![],
+                BeforeSave::Trim => trailing_whitespace(rope)
+                    .into_iter()
+                    .map(|range| TextEdit {
+                        range,
+                        new_text: String::new(),
+                    })
+                    .collect(),
+                BeforeSave::Format => formatting_edits(rope, config, words.for_file(uri.as_str())),
+            };
+            encoding.encode_edits(rope, &mut ret);
+            let result = serde_json::to_value(ret).expect("Must be able to serialize edits");
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,129 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+use crate::{
+    definition_index::DefinitionIndex,
+    utils::{file_uri, position_encoding::PositionEncoding},
+};
+
+use std::collections::{HashMap, HashSet};
+
+use lsp_server::{Connection, Message, Request, Response};
+use lsp_types::{request::WorkspaceSymbolRequest, Location, SymbolInformation, Url};
+use ropey::Rope;
+
+use super::{cast, request_document_symbol::symbol_kind};
+
+pub fn handle_workspace_symbol(
+    req: &Request,
+    connection: &Connection,
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    encoding: PositionEncoding,
+) -> Result<()> {
+    match cast::<WorkspaceSymbolRequest>(req.clone()) {
+        Ok((id, params)) => {
+            let result = workspace_symbols(files, index, &params.query, encoding);
+            connection
+                .sender
+                .send(Message::Response(Response::new_ok(id, result)))
+                .map_err(|err| Error::SendError(err.to_string()))?;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Definitions in every indexed file whose name contains `query`, ignoring case, sorted by
+/// name. An open document is indexed by its URI and by its path once scanned, the URI wins.
+/// Ranges of loaded files count as `encoding` does
+fn workspace_symbols(
+    files: &HashMap<String, Rope>,
+    index: &DefinitionIndex,
+    query: &str,
+    encoding: PositionEncoding,
+) -> Vec<SymbolInformation> {
+    let query = query.to_lowercase();
+    let open: HashSet<Url> = index
+        .files()
+        .filter(|(file, _)| Url::parse(file).is_ok())
+        .filter_map(|(file, _)| file_uri(file))
+        .collect();
+    let mut ret = vec![];
+    for (file, _) in index.files() {
+        let Some(uri) = file_uri(file) else {
+            continue;
+        };
+        if Url::parse(file).is_err() && open.contains(&uri) {
+            continue;
+        }
+        for definition in index.symbols(file) {
+            if !definition.name.to_lowercase().contains(&query) {
+                continue;
+            }
+            #[allow(deprecated)]
+            ret.push(SymbolInformation {
+                name: definition.name.clone(),
+                kind: symbol_kind(definition.kind),
+                tags: None,
+                deprecated: None,
+                location: Location {
+                    uri: uri.clone(),
+                    range: encoding.encode_file_range(files, file, definition.selection_range),
+                },
+                container_name: definition
+                    .field_of
+                    .as_ref()
+                    .map(|field_of| field_of.structure.clone()),
+            });
+        }
+    }
+    ret.sort_by(|a, b| {
+        (a.name.to_lowercase(), a.location.uri.as_str())
+            .cmp(&(b.name.to_lowercase(), b.location.uri.as_str()))
+    });
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use lsp_types::SymbolKind;
+
+    #[test]
+    fn symbols_matching_the_query() {
+        let mut index = DefinitionIndex::default();
+        index.update_file(
+            "/p/lib.forth",
+            &Rope::from_str(": square dup * ;\nvariable sq-count"),
+        );
+        index.update_file("/p/main.forth", &Rope::from_str(": cube dup square * ;"));
+        // the open document wins over the scanned file
+        index.update_file("file:///p/main.forth", &Rope::from_str(": sq 2 ;"));
+        let symbols: Vec<(String, SymbolKind, String)> =
+            workspace_symbols(&HashMap::new(), &index, "SQ", PositionEncoding::Utf16)
+                .into_iter()
+                .map(|symbol| (symbol.name, symbol.kind, symbol.location.uri.to_string()))
+                .collect();
+        assert_eq!(
+            vec![
+                (
+                    "sq".to_string(),
+                    SymbolKind::FUNCTION,
+                    "file:///p/main.forth".to_string()
+                ),
+                (
+                    "sq-count".to_string(),
+                    SymbolKind::VARIABLE,
+                    "file:///p/lib.forth".to_string()
+                ),
+                (
+                    "square".to_string(),
+                    SymbolKind::FUNCTION,
+                    "file:///p/lib.forth".to_string()
+                ),
+            ],
+            symbols
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use lsp_types::Color;
+
+/// Parse a `$RRGGBB` literal into an LSP `Color`
+pub fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('$')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |ix: usize| u8::from_str_radix(&hex[ix..ix + 2], 16).ok();
+    Some(Color {
+        red: channel(0)? as f32 / 255.0,
+        green: channel(2)? as f32 / 255.0,
+        blue: channel(4)? as f32 / 255.0,
+        alpha: 1.0,
+    })
+}
+
+/// Render an LSP `Color` as a `$RRGGBB` literal, alpha is ignored
+pub fn to_hex_color(color: &Color) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "${:02X}{:02X}{:02X}",
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rrggbb() {
+        let color = parse_hex_color("$FF0080").unwrap();
+        assert_eq!(1.0, color.red);
+        assert_eq!(0.0, color.green);
+        assert_eq!(128.0 / 255.0, color.blue);
+        assert_eq!(1.0, color.alpha);
+    }
+
+    #[test]
+    fn parse_rejects_other_literals() {
+        assert!(parse_hex_color("$FF").is_none());
+        assert!(parse_hex_color("$FF008080").is_none());
+        assert!(parse_hex_color("0xFF0080").is_none());
+        assert!(parse_hex_color("$FF00G0").is_none());
+    }
+
+    #[test]
+    fn round_trip() {
+        let color = parse_hex_color("$12aBcD").unwrap();
+        assert_eq!("$12ABCD", to_hex_color(&color));
+    }
+}
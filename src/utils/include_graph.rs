@@ -0,0 +1,492 @@
+use crate::{
+    config::ReferenceScope,
+    definition_index::DefinitionIndex,
+    utils::{data_to_position::ToPosition, file_path},
+};
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Component, Path, PathBuf},
+    time::Instant,
+};
+
+use forth_lexer::{
+    parser::Lexer,
+    token::{Data, Token},
+};
+use lsp_types::Position;
+use ropey::Rope;
+
+/// The end of a file, whatever its length
+const END_OF_FILE: Position = Position {
+    line: u32::MAX,
+    character: u32::MAX,
+};
+
+/// Which files include which, keyed the same way as the files map. Kept up to date as files
+/// are indexed, an include of a file that is not known yet leads to it once it is
+#[derive(Debug, Default)]
+pub struct IncludeGraph {
+    /// Paths of the included files with where they are included, in source order
+    includes: HashMap<String, Vec<(Position, PathBuf)>>,
+    /// The key of every file with a path
+    keys: HashMap<PathBuf, String>,
+}
+
+/// An include loading a file that is still being loaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludeCycle {
+    /// The file with the include closing the cycle
+    pub file: String,
+    /// The end of the included name
+    pub at: Position,
+    /// The files of the cycle, starting and ending with the file included again
+    pub chain: Vec<String>,
+}
+
+/// Part of a file compiled without an include in between
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadSegment {
+    pub file: String,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl IncludeGraph {
+    /// The graph of `files`, lexing every one of them
+    pub fn from_files(files: &HashMap<String, Rope>) -> IncludeGraph {
+        let mut ret = IncludeGraph::default();
+        for (key, rope) in files.iter() {
+            let progn = rope.to_string();
+            let mut lexer = Lexer::new(progn.as_str());
+            ret.insert(key, &included_names(&lexer.parse(), rope));
+        }
+        ret
+    }
+
+    /// Record that `key` includes `names`, as written in it with the end of each
+    pub fn insert(&mut self, key: &str, names: &[(Position, String)]) {
+        let Some(path) = file_path(key) else {
+            return;
+        };
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        let included = names
+            .iter()
+            .map(|(at, name)| (*at, normalize(&dir.join(name))))
+            .collect();
+        self.includes.insert(key.to_owned(), included);
+        self.keys.insert(path, key.to_owned());
+    }
+
+    /// Forget what `key` includes, includes of it lead nowhere until it is known again
+    pub fn remove(&mut self, key: &str) {
+        self.includes.remove(key);
+        if let Some(path) = file_path(key) {
+            if self.keys.get(&path).is_some_and(|known| known == key) {
+                self.keys.remove(&path);
+            }
+        }
+    }
+
+    /// Key what `from` includes by `to` instead
+    pub fn rename(&mut self, from: &str, to: &str) {
+        let included = self.includes.remove(from);
+        self.remove(from);
+        if let (Some(included), Some(path)) = (included, file_path(to)) {
+            self.includes.insert(to.to_owned(), included);
+            self.keys.insert(path, to.to_owned());
+        }
+    }
+
+    /// Known files `key` includes, with where, in source order
+    fn edges(&self, key: &str) -> impl Iterator<Item = (Position, &String)> {
+        self.includes
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|(at, path)| self.keys.get(path).map(|included| (*at, included)))
+    }
+
+    pub fn includes(&self, key: &str) -> impl Iterator<Item = &String> {
+        self.edges(key).map(|(_, included)| included)
+    }
+
+    /// Files including `key`, with where they include it first
+    fn included_by(&self, key: &str) -> Vec<(&String, Position)> {
+        let mut ret: Vec<(&String, Position)> = self
+            .includes
+            .keys()
+            .filter_map(|file| {
+                self.edges(file)
+                    .find(|(_, included)| *included == key)
+                    .map(|(at, _)| (file, at))
+            })
+            .collect();
+        ret.sort();
+        ret
+    }
+
+    /// What was compiled before `position` in `key`, oldest first. The file including `key`
+    /// comes first, up to where it does, when that is the only one. Included files are
+    /// loaded where they are included, once
+    pub fn load_order(&self, key: &str, position: Position) -> Vec<LoadSegment> {
+        let mut loaded = HashSet::from([key.to_owned()]);
+        let mut ret = vec![];
+        let mut current = key.to_owned();
+        let mut chain = vec![];
+        while let [(file, at)] = self.included_by(&current)[..] {
+            if !loaded.insert(file.to_owned()) {
+                break;
+            }
+            chain.push((file.to_owned(), at));
+            current = file.to_owned();
+        }
+        for (file, at) in chain.iter().rev() {
+            self.load(file, *at, &mut loaded, &mut ret);
+        }
+        self.load(key, position, &mut loaded, &mut ret);
+        ret
+    }
+
+    /// Append `key` up to `position` to `order`, with the files it includes before that
+    fn load(
+        &self,
+        key: &str,
+        position: Position,
+        loaded: &mut HashSet<String>,
+        order: &mut Vec<LoadSegment>,
+    ) {
+        let mut start = Position::default();
+        for (at, included) in self.edges(key) {
+            if (at.line, at.character) >= (position.line, position.character) {
+                break;
+            }
+            order.push(LoadSegment {
+                file: key.to_owned(),
+                start,
+                end: at,
+            });
+            start = at;
+            if loaded.insert(included.to_owned()) {
+                self.load(included, END_OF_FILE, loaded, order);
+            }
+        }
+        order.push(LoadSegment {
+            file: key.to_owned(),
+            start,
+            end: position,
+        });
+    }
+
+    /// Includes closing a cycle. Loading starts at the files nobody includes, in order of
+    /// their keys, so each cycle is reported once, where loading would come back around
+    pub fn cycles(&self) -> Vec<IncludeCycle> {
+        let mut keys: Vec<&String> = self.includes.keys().collect();
+        keys.sort_by_key(|key| (!self.included_by(key).is_empty(), *key));
+        let mut done = HashSet::new();
+        let mut ret = vec![];
+        for key in keys {
+            if !done.contains(key) {
+                self.find_cycles(key, &mut vec![], &mut done, &mut ret);
+            }
+        }
+        ret
+    }
+
+    fn find_cycles(
+        &self,
+        key: &str,
+        loading: &mut Vec<String>,
+        done: &mut HashSet<String>,
+        cycles: &mut Vec<IncludeCycle>,
+    ) {
+        loading.push(key.to_owned());
+        for (at, included) in self.edges(key) {
+            if let Some(ix) = loading.iter().position(|file| file == included) {
+                let mut chain = loading[ix..].to_vec();
+                chain.push(included.to_owned());
+                cycles.push(IncludeCycle {
+                    file: key.to_owned(),
+                    at,
+                    chain,
+                });
+            } else if !done.contains(included) {
+                self.find_cycles(included, loading, done, cycles);
+            }
+        }
+        loading.pop();
+        done.insert(key.to_owned());
+    }
+
+    /// Every file connected to `key` through includes, in either direction
+    pub fn reachable(&self, key: &str) -> HashSet<String> {
+        self.distances(key).into_keys().collect()
+    }
+
+    /// Include steps, in either direction, from `key` to every file connected to it
+    pub fn distances(&self, key: &str) -> HashMap<String, usize> {
+        let mut seen = HashMap::from([(key.to_owned(), 0)]);
+        let mut queue = VecDeque::from([key.to_owned()]);
+        while let Some(current) = queue.pop_front() {
+            let distance = seen[&current] + 1;
+            let included_by = self.included_by(&current).into_iter().map(|(file, _)| file);
+            for next in self.includes(&current).chain(included_by) {
+                if !seen.contains_key(next) {
+                    seen.insert(next.to_owned(), distance);
+                    queue.push_back(next.to_owned());
+                }
+            }
+        }
+        seen
+    }
+}
+
+/// Files a search starting in `key` should look at, `None` meaning every file
+pub fn files_in_scope(
+    scope: ReferenceScope,
+    index: &DefinitionIndex,
+    key: &str,
+) -> Option<HashSet<String>> {
+    match scope {
+        ReferenceScope::Workspace => None,
+        ReferenceScope::IncludeGraph => Some(index.include_graph().reachable(key)),
+    }
+}
+
+/// Load the files `key` includes, directly or through other included files, that exist on
+/// disk but are not loaded yet, like libraries outside the workspace. They are keyed by path
+/// as if they were scanned, the client only owns them once it opens them. Returns the keys
+/// of the files loaded
+pub fn load_included_files(
+    key: &str,
+    files: &mut HashMap<String, Rope>,
+    index: &mut DefinitionIndex,
+) -> Vec<String> {
+    let started = Instant::now();
+    let mut known: HashSet<PathBuf> = files.keys().filter_map(|key| file_path(key)).collect();
+    let mut queue = VecDeque::from([key.to_owned()]);
+    let mut ret = vec![];
+    while let Some(current) = queue.pop_front() {
+        let (Some(rope), Some(dir)) = (
+            files.get(&current),
+            file_path(&current).and_then(|path| path.parent().map(Path::to_path_buf)),
+        ) else {
+            continue;
+        };
+        let progn = rope.to_string();
+        let mut lexer = Lexer::new(progn.as_str());
+        let tokens = lexer.parse();
+        let missing: Vec<PathBuf> = include_targets(&tokens)
+            .iter()
+            .map(|target| normalize(&dir.join(target.value)))
+            .filter(|path| !known.contains(path) && path.is_file())
+            .collect();
+        for path in missing {
+            let raw_content = match fs::read(&path) {
+                Ok(raw_content) => raw_content,
+                Err(err) => {
+                    eprintln!("Failed to load {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+            let entry = path.to_string_lossy().to_string();
+            eprintln!("FORTH load {}", entry);
+            let rope = Rope::from_str(&String::from_utf8_lossy(&raw_content));
+            index.update_file(&entry, &rope);
+            files.insert(entry.clone(), rope);
+            known.insert(path);
+            queue.push_back(entry.clone());
+            ret.push(entry);
+        }
+    }
+    if !ret.is_empty() {
+        index.record_index(started.elapsed());
+    }
+    ret
+}
+
+/// Names of the files `tokens` of `rope` include, with the end of each
+pub fn included_names(tokens: &[Token], rope: &Rope) -> Vec<(Position, String)> {
+    include_targets(tokens)
+        .iter()
+        .map(|target| (target.to_position_end(rope), target.value.to_owned()))
+        .collect()
+}
+
+/// File name tokens of `INCLUDE name`, `REQUIRE name` and `S" name" INCLUDED`
+pub fn include_targets<'a>(tokens: &[Token<'a>]) -> Vec<Data<'a>> {
+    let mut ret = vec![];
+    for (ix, token) in tokens.iter().enumerate() {
+        if let Token::Word(word) = token {
+            let word = word.value.to_uppercase();
+            if word == "INCLUDE" || word == "REQUIRE" {
+                if let Some(Token::Word(name)) = tokens.get(ix + 1) {
+                    ret.push(*name);
+                }
+            } else if word == "INCLUDED" && ix >= 2 {
+                if let (Token::Word(open), Token::Word(name)) = (&tokens[ix - 2], &tokens[ix - 1]) {
+                    if open.value.eq_ignore_ascii_case("S\"") && name.value.ends_with('"') {
+                        let mut name = *name;
+                        name.value = &name.value[..name.value.len() - 1];
+                        name.end -= 1;
+                        ret.push(name);
+                    }
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Resolve `.` and `..` without touching the file system
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut ret = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                ret.pop();
+            }
+            _ => ret.push(component),
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(sources: &[(&str, &str)]) -> HashMap<String, Rope> {
+        sources
+            .iter()
+            .map(|(key, source)| (key.to_string(), Rope::from_str(source)))
+            .collect()
+    }
+
+    #[test]
+    fn include_targets_all_forms() {
+        let mut lexer = Lexer::new("include a.fs REQUIRE ../b.fs S\" c.fs\" included");
+        let tokens = lexer.parse();
+        let targets: Vec<&str> = include_targets(&tokens).iter().map(|t| t.value).collect();
+        assert_eq!(vec!["a.fs", "../b.fs", "c.fs"], targets);
+    }
+
+    #[test]
+    fn reachable_follows_both_directions() {
+        let files = files(&[
+            ("/p/main.forth", "include lib/a.forth"),
+            ("/p/lib/a.forth", "include ./b.forth"),
+            ("/p/lib/b.forth", ": b ;"),
+            ("/other/main.forth", ": b ;"),
+        ]);
+        let graph = IncludeGraph::from_files(&files);
+        assert_eq!(
+            vec!["/p/lib/a.forth"],
+            graph.includes("/p/main.forth").collect::<Vec<_>>()
+        );
+        let reachable = graph.reachable("/p/lib/b.forth");
+        assert_eq!(3, reachable.len());
+        assert!(reachable.contains("/p/main.forth"));
+        assert!(!reachable.contains("/other/main.forth"));
+        let distances = graph.distances("/p/lib/b.forth");
+        assert_eq!(Some(&2), distances.get("/p/main.forth"));
+    }
+
+    #[test]
+    fn load_order_expands_includes_in_place() {
+        let files = files(&[
+            (
+                "/p/main.forth",
+                ": x ;\ninclude a.forth\n: y ;\ninclude b.forth",
+            ),
+            ("/p/a.forth", "include c.forth\n: x ;"),
+            ("/p/b.forth", ": z ;"),
+            ("/p/c.forth", ": c ;"),
+        ]);
+        let graph = IncludeGraph::from_files(&files);
+        let order = graph.load_order("/p/main.forth", Position::new(2, 5));
+        let order: Vec<(&str, u32, u32)> = order
+            .iter()
+            .map(|segment| (segment.file.as_str(), segment.start.line, segment.end.line))
+            .collect();
+        assert_eq!(
+            vec![
+                ("/p/main.forth", 0, 1),
+                ("/p/a.forth", 0, 0),
+                ("/p/c.forth", 0, u32::MAX),
+                ("/p/a.forth", 0, u32::MAX),
+                ("/p/main.forth", 1, 2),
+            ],
+            order
+        );
+        // an included file sees what its includer compiled before including it
+        let order = graph.load_order("/p/b.forth", Position::new(0, 0));
+        assert_eq!(6, order.len());
+        assert_eq!("/p/main.forth", order[4].file);
+        assert_eq!(Position::new(3, 15), order[4].end);
+    }
+
+    #[test]
+    fn cycles_close_where_loading_comes_back() {
+        let files = files(&[
+            ("/p/main.forth", "include a.forth\ninclude b.forth"),
+            ("/p/a.forth", "include b.forth"),
+            ("/p/b.forth", ": b ;\ninclude a.forth"),
+            ("/p/self.forth", "include self.forth"),
+        ]);
+        let cycles = IncludeGraph::from_files(&files).cycles();
+        assert_eq!(
+            vec![
+                IncludeCycle {
+                    file: "/p/b.forth".to_string(),
+                    at: Position::new(1, 15),
+                    chain: vec![
+                        "/p/a.forth".to_string(),
+                        "/p/b.forth".to_string(),
+                        "/p/a.forth".to_string()
+                    ],
+                },
+                IncludeCycle {
+                    file: "/p/self.forth".to_string(),
+                    at: Position::new(0, 18),
+                    chain: vec!["/p/self.forth".to_string(), "/p/self.forth".to_string()],
+                },
+            ],
+            cycles
+        );
+    }
+
+    #[test]
+    fn loads_included_files_from_disk() {
+        let dir = crate::utils::test_dir("load-included-files");
+        let lib = dir.join("lib");
+        fs::create_dir_all(&lib).unwrap();
+        fs::write(lib.join("a.forth"), "include b.forth\n: a ;").unwrap();
+        fs::write(lib.join("b.forth"), ": b ;").unwrap();
+        let main = dir.join("app").join("main.forth");
+        let key = main.to_string_lossy().to_string();
+        let mut files = files(&[(&key, "include ../lib/a.forth\ninclude missing.forth\nb")]);
+        let mut index = DefinitionIndex::default();
+        let loaded = load_included_files(&key, &mut files, &mut index);
+        let a = lib.join("a.forth").to_string_lossy().to_string();
+        let b = lib.join("b.forth").to_string_lossy().to_string();
+        assert_eq!(vec![a.clone(), b.clone()], loaded);
+        assert!(files.contains_key(&a) && files.contains_key(&b));
+        assert_eq!(&b, index.definitions("b")[0].0);
+        // what is loaded stays loaded
+        assert!(load_included_files(&key, &mut files, &mut index).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn normalize_parent_dirs() {
+        assert_eq!(
+            PathBuf::from("/p/b.fs"),
+            normalize(Path::new("/p/lib/./../b.fs"))
+        );
+    }
+}
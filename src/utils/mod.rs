@@ -1,15 +1,30 @@
 #[allow(unused_imports)]
 use crate::prelude::*;
 
+pub mod blocks;
+pub mod builtin_docs;
 pub mod data_to_position;
 pub mod find_variant_sublists;
-pub mod find_variant_sublists_from_to;
 pub mod handlers;
+pub mod hex_color;
+pub mod include_graph;
+pub mod outgoing;
+pub mod plain_text;
+pub mod position_encoding;
 pub mod ropey;
 pub mod server_capabilities;
+pub mod suggest;
+pub mod token_at;
+pub mod trace;
 
-use lsp_types::TextDocumentPositionParams;
-use std::collections::HashMap;
+use crate::definition_index::DefinitionIndex;
+
+use ::ropey::Rope;
+use lsp_types::{TextDocumentPositionParams, Url};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
 
 pub trait HashMapGetForLSPParams<T> {
     fn for_position_param(&mut self, params: &TextDocumentPositionParams) -> Option<&mut T>;
@@ -20,3 +35,118 @@ impl<T> HashMapGetForLSPParams<T> for HashMap<String, T> {
         self.get_mut(&params.text_document.uri.to_string())
     }
 }
+
+/// Keys in the files map are URIs for opened documents and paths for scanned ones
+pub fn file_uri(file: &str) -> Option<Url> {
+    Url::parse(file)
+        .ok()
+        .filter(|uri| uri.scheme() == "file")
+        .or_else(|| Url::from_file_path(file).ok())
+}
+
+pub fn file_path(file: &str) -> Option<PathBuf> {
+    file_uri(file).and_then(|uri| uri.to_file_path().ok())
+}
+
+/// The key `file` is scanned from disk under, which an opened document shadows
+pub fn scanned_key(file: &str) -> Option<String> {
+    file_path(file).map(|path| path.to_string_lossy().to_string())
+}
+
+/// Make `file` the only key of its file in `files` and `index`, moving a scanned copy under
+/// another key to it
+pub fn claim_key(file: &str, files: &mut HashMap<String, Rope>, index: &mut DefinitionIndex) {
+    let Some(scanned) = scanned_key(file).filter(|scanned| scanned != file) else {
+        return;
+    };
+    if let Some(rope) = files.remove(&scanned) {
+        index.rename_file(&scanned, file);
+        files.insert(file.to_owned(), rope);
+    }
+}
+
+/// Keys naming the same file, like its scanned path and its opened URI, grouped and sorted
+pub fn duplicate_keys<'a>(keys: impl IntoIterator<Item = &'a String>) -> Vec<Vec<String>> {
+    let mut by_file = BTreeMap::<String, Vec<String>>::new();
+    for key in keys {
+        let file = scanned_key(key).unwrap_or_else(|| key.clone());
+        by_file.entry(file).or_default().push(key.clone());
+    }
+    by_file
+        .into_values()
+        .filter(|keys| keys.len() > 1)
+        .map(|mut keys| {
+            keys.sort();
+            keys
+        })
+        .collect()
+}
+
+/// Where `file` is, relative to `root` when it is below it
+pub fn display_path(file: &str, root: Option<&Path>) -> String {
+    match (file_path(file), root) {
+        (Some(path), Some(root)) => match path.strip_prefix(root) {
+            Ok(relative) => relative.display().to_string(),
+            Err(_) => path.display().to_string(),
+        },
+        (Some(path), None) => path.display().to_string(),
+        (None, _) => file.to_string(),
+    }
+}
+
+/// A new directory for a test, unique to the test run so parallel runs do not share it
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "forth-lsp-{}-{}-{}",
+        name,
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).expect("Must be able to create a test directory");
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_uri_from_path_and_uri() {
+        let expected = Url::parse("file:///tmp/test.forth").unwrap();
+        assert_eq!(Some(expected.clone()), file_uri("/tmp/test.forth"));
+        assert_eq!(Some(expected), file_uri("file:///tmp/test.forth"));
+    }
+
+    #[test]
+    fn claimed_keys_are_not_duplicates() {
+        let mut files = HashMap::from([
+            ("/tmp/a.forth".to_string(), Rope::from_str(": a ;")),
+            ("/tmp/b.forth".to_string(), Rope::new()),
+            ("file:///tmp/b.forth".to_string(), Rope::new()),
+        ]);
+        let mut index = DefinitionIndex::default();
+        index.update_file("/tmp/a.forth", &files["/tmp/a.forth"]);
+        assert_eq!(
+            vec![vec![
+                "/tmp/b.forth".to_string(),
+                "file:///tmp/b.forth".to_string()
+            ]],
+            duplicate_keys(files.keys())
+        );
+        claim_key("file:///tmp/a.forth", &mut files, &mut index);
+        assert!(files.contains_key("file:///tmp/a.forth"));
+        assert!(!files.contains_key("/tmp/a.forth"));
+        assert_eq!(1, index.definitions("a").len());
+        assert_eq!(1, duplicate_keys(files.keys()).len());
+    }
+
+    #[test]
+    fn scanned_key_of_uri_and_path() {
+        let key = Some("/tmp/test.forth".to_string());
+        assert_eq!(key, scanned_key("file:///tmp/test.forth"));
+        assert_eq!(key, scanned_key("/tmp/test.forth"));
+    }
+}
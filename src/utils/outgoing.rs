@@ -0,0 +1,160 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use std::collections::HashMap;
+
+use lsp_server::{Connection, Message, Request, RequestId, Response};
+use lsp_types::{
+    request::{ApplyWorkspaceEdit, Request as _},
+    ApplyWorkspaceEditParams, ApplyWorkspaceEditResponse, ClientCapabilities, WorkspaceEdit,
+};
+use serde::Serialize;
+
+/// What a request sent to the client was for, to make sense of its response
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pending {
+    /// A `workspace/applyEdit` with this label
+    ApplyEdit(String),
+    /// A `client/registerCapability`
+    Registration,
+}
+
+/// Requests the server sent to the client that still await a response
+#[derive(Debug, Default)]
+pub struct Outgoing {
+    next_id: i32,
+    pending: HashMap<RequestId, Pending>,
+    apply_edit: bool,
+}
+
+impl Outgoing {
+    pub fn new(client: &ClientCapabilities) -> Outgoing {
+        Outgoing {
+            apply_edit: client
+                .workspace
+                .as_ref()
+                .and_then(|workspace| workspace.apply_edit)
+                .unwrap_or(false),
+            ..Default::default()
+        }
+    }
+
+    /// Send a request to the client and remember it until its response arrives
+    pub fn send(
+        &mut self,
+        connection: &Connection,
+        method: &str,
+        params: impl Serialize,
+        pending: Pending,
+    ) -> Result<RequestId> {
+        self.next_id += 1;
+        let id = RequestId::from(format!("forth-lsp-{}", self.next_id));
+        connection
+            .sender
+            .send(Message::Request(Request::new(
+                id.clone(),
+                method.to_string(),
+                params,
+            )))
+            .map_err(|err| Error::SendError(err.to_string()))?;
+        self.pending.insert(id.clone(), pending);
+        Ok(id)
+    }
+
+    /// Ask the client to apply `edit`, nothing is sent when it cannot apply edits
+    pub fn apply_edit(
+        &mut self,
+        connection: &Connection,
+        label: &str,
+        edit: WorkspaceEdit,
+    ) -> Result<Option<RequestId>> {
+        if !self.apply_edit {
+            eprintln!("Client cannot apply edits, dropping '{}'", label);
+            return Ok(None);
+        }
+        let params = ApplyWorkspaceEditParams {
+            label: Some(label.to_string()),
+            edit,
+        };
+        self.send(
+            connection,
+            ApplyWorkspaceEdit::METHOD,
+            params,
+            Pending::ApplyEdit(label.to_string()),
+        )
+        .map(Some)
+    }
+
+    /// Match a response to the request it answers, reporting failures,
+    /// returns None for responses to requests we never sent
+    pub fn complete(&mut self, response: &Response) -> Option<Pending> {
+        let pending = self.pending.remove(&response.id)?;
+        if let Some(error) = &response.error {
+            eprintln!("{:?} failed: {}", pending, error.message);
+            return Some(pending);
+        }
+        if let Pending::ApplyEdit(label) = &pending {
+            let result = response.result.clone().and_then(|result| {
+                serde_json::from_value::<ApplyWorkspaceEditResponse>(result).ok()
+            });
+            match result {
+                Some(result) if result.applied => {}
+                Some(result) => eprintln!(
+                    "Client did not apply '{}': {}",
+                    label,
+                    result.failure_reason.unwrap_or_default()
+                ),
+                None => eprintln!("Malformed response to '{}'", label),
+            }
+        }
+        Some(pending)
+    }
+
+    pub fn is_pending(&self, id: &RequestId) -> bool {
+        self.pending.contains_key(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn client(apply_edit: bool) -> ClientCapabilities {
+        serde_json::from_value(json!({ "workspace": { "applyEdit": apply_edit } })).unwrap()
+    }
+
+    #[test]
+    fn tracks_apply_edit_until_answered() {
+        let (server, client_side) = Connection::memory();
+        let mut outgoing = Outgoing::new(&client(true));
+        let id = outgoing
+            .apply_edit(&server, "Format", WorkspaceEdit::default())
+            .unwrap()
+            .unwrap();
+        let Ok(Message::Request(request)) = client_side.receiver.try_recv() else {
+            panic!("Expected a request");
+        };
+        assert_eq!(id, request.id);
+        assert_eq!("workspace/applyEdit", request.method);
+        assert!(outgoing.is_pending(&id));
+        let response = Response::new_ok(id.clone(), json!({ "applied": true }));
+        assert_eq!(
+            Some(Pending::ApplyEdit("Format".to_string())),
+            outgoing.complete(&response)
+        );
+        assert!(!outgoing.is_pending(&id));
+        assert_eq!(None, outgoing.complete(&response));
+    }
+
+    #[test]
+    fn skips_edits_the_client_cannot_apply() {
+        let (server, client_side) = Connection::memory();
+        let mut outgoing = Outgoing::new(&client(false));
+        let id = outgoing
+            .apply_edit(&server, "Format", WorkspaceEdit::default())
+            .unwrap();
+        assert_eq!(None, id);
+        assert!(client_side.receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,92 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+/// Width plain text documentation is wrapped at
+pub const PLAIN_TEXT_WIDTH: usize = 80;
+
+/// The markdown of our documentation as plain text for clients that do not render markdown:
+/// headings, inline code, emphasis and collapsed sections lose their markup, and lines
+/// outside code blocks wrap at `width`
+pub fn to_plain_text(markdown: &str, width: usize) -> String {
+    let mut lines = vec![];
+    let mut in_code = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(line.to_string());
+            continue;
+        }
+        if line == "</details>" {
+            continue;
+        }
+        let line = match line.trim_start_matches('#').strip_prefix(' ') {
+            Some(heading) if line.starts_with('#') => heading.trim_start(),
+            _ => line,
+        };
+        let line = match line
+            .strip_prefix("<details><summary>")
+            .and_then(|line| line.strip_suffix("</summary>"))
+        {
+            Some(summary) => summary,
+            None => line,
+        };
+        let line = match line
+            .strip_prefix('*')
+            .and_then(|line| line.strip_suffix('*'))
+        {
+            Some(emphasized) if !emphasized.is_empty() => emphasized,
+            _ => line,
+        };
+        lines.extend(wrap(&line.replace('`', ""), width));
+    }
+    // dropped markup may leave blank lines in a row
+    lines.dedup_by(|line, previous| line.is_empty() && previous.is_empty());
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// `line` broken between words so that each part fits in `width`, when its words do
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut ret = vec![];
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            ret.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    ret.push(current);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup_is_dropped() {
+        let markdown = "# `DUP`   `( x -- x x )`\n\nCopy x.\n\n*Project-specific*\n\n\
+                        <details><summary>Also a standard word</summary>\n\n`DUP`\n\n</details>";
+        assert_eq!(
+            "DUP ( x -- x x )\n\nCopy x.\n\nProject-specific\n\nAlso a standard word\n\nDUP",
+            to_plain_text(markdown, PLAIN_TEXT_WIDTH)
+        );
+    }
+
+    #[test]
+    fn long_lines_wrap_outside_code() {
+        let markdown = "#S one two three four\n```\n: five 5 ; : six 6 ;\n```";
+        assert_eq!(
+            "#S one two\nthree four\n: five 5 ; : six 6 ;",
+            to_plain_text(markdown, 10)
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::utils::data_to_position::ToPosition;
+
+use std::collections::HashMap;
+
+use forth_lexer::token::Data;
+use lsp_types::{
+    ClientCapabilities, Diagnostic, DocumentChanges, DocumentSymbol, OneOf, Position,
+    PositionEncodingKind, Range, TextEdit, Url, WorkspaceEdit,
+};
+use ropey::Rope;
+
+/// How the character of a position counts the text before it on its line, negotiated in
+/// `initialize`. The server counts chars, positions are encoded on their way to the client
+/// and decoded on their way in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    Utf8,
+    /// What every client supports, and what they get unless they offer another
+    #[default]
+    Utf16,
+    /// Chars, like the server counts
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// The encoding for a client: chars when it supports them, then UTF-8, which the rope
+    /// counts too, otherwise UTF-16
+    pub fn negotiate(client: &ClientCapabilities) -> PositionEncoding {
+        let offered = client
+            .general
+            .as_ref()
+            .and_then(|general| general.position_encodings.as_ref());
+        match offered {
+            Some(offered) if offered.contains(&PositionEncodingKind::UTF32) => {
+                PositionEncoding::Utf32
+            }
+            Some(offered) if offered.contains(&PositionEncodingKind::UTF8) => {
+                PositionEncoding::Utf8
+            }
+            _ => PositionEncoding::Utf16,
+        }
+    }
+
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+            PositionEncoding::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    fn len(self, chr: char) -> u32 {
+        match self {
+            PositionEncoding::Utf8 => chr.len_utf8() as u32,
+            PositionEncoding::Utf16 => chr.len_utf16() as u32,
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+
+    /// `position` counting chars, as the client counts
+    pub fn encode(self, rope: &Rope, position: Position) -> Position {
+        let Some(line) = rope.get_line(position.line as usize) else {
+            return position;
+        };
+        let before = (position.character as usize).min(line.len_chars());
+        let character = match self {
+            PositionEncoding::Utf32 => return position,
+            // the rope knows the byte offsets, no need to count the line
+            PositionEncoding::Utf8 => line.char_to_byte(before) as u32,
+            PositionEncoding::Utf16 => line.chars().take(before).map(|chr| self.len(chr)).sum(),
+        };
+        Position::new(position.line, character)
+    }
+
+    /// `position` of the client, counting chars. A position inside a char is at its start,
+    /// one past the end of its line stays past it
+    pub fn decode(self, rope: &Rope, position: Position) -> Position {
+        let Some(line) = rope.get_line(position.line as usize) else {
+            return position;
+        };
+        match self {
+            PositionEncoding::Utf32 => return position,
+            PositionEncoding::Utf8 => {
+                let bytes = position.character as usize;
+                let chars = match bytes.checked_sub(line.len_bytes()) {
+                    Some(past) => line.len_chars() + past,
+                    None => line.byte_to_char(bytes),
+                };
+                return Position::new(position.line, chars as u32);
+            }
+            PositionEncoding::Utf16 => {}
+        }
+        let (mut units, mut chars) = (0, 0);
+        for chr in line.chars() {
+            let next = units + self.len(chr);
+            if next > position.character {
+                return Position::new(position.line, chars);
+            }
+            (units, chars) = (next, chars + 1);
+        }
+        Position::new(position.line, chars + (position.character - units))
+    }
+
+    pub fn encode_range(self, rope: &Rope, range: Range) -> Range {
+        Range::new(self.encode(rope, range.start), self.encode(rope, range.end))
+    }
+
+    pub fn decode_range(self, rope: &Rope, range: Range) -> Range {
+        Range::new(self.decode(rope, range.start), self.decode(rope, range.end))
+    }
+
+    /// `range` of `file`, a key of `files`, left as is when the file is not loaded
+    pub fn encode_file_range(
+        self,
+        files: &HashMap<String, Rope>,
+        file: &str,
+        range: Range,
+    ) -> Range {
+        match files.get(file) {
+            Some(rope) => self.encode_range(rope, range),
+            None => range,
+        }
+    }
+
+    /// Range of `data` in `rope`, as the client counts
+    pub fn data_range(self, data: &Data, rope: &Rope) -> Range {
+        self.encode_range(rope, data_range(data, rope))
+    }
+
+    pub fn encode_edits(self, rope: &Rope, edits: &mut [TextEdit]) {
+        for edit in edits {
+            edit.range = self.encode_range(rope, edit.range);
+        }
+    }
+
+    /// The ranges of `diagnostics` of `rope`, the document at `uri`. Related information
+    /// pointing into other documents is left as is
+    pub fn encode_diagnostics(
+        self,
+        rope: &Rope,
+        uri: Option<&Url>,
+        diagnostics: &mut [Diagnostic],
+    ) {
+        for diagnostic in diagnostics {
+            diagnostic.range = self.encode_range(rope, diagnostic.range);
+            for related in diagnostic.related_information.iter_mut().flatten() {
+                if Some(&related.location.uri) == uri {
+                    related.location.range = self.encode_range(rope, related.location.range);
+                }
+            }
+        }
+    }
+
+    /// The ranges of `symbols` of `rope` and of their children
+    pub fn encode_symbols(self, rope: &Rope, symbols: &mut [DocumentSymbol]) {
+        for symbol in symbols {
+            symbol.range = self.encode_range(rope, symbol.range);
+            symbol.selection_range = self.encode_range(rope, symbol.selection_range);
+            self.encode_symbols(rope, symbol.children.as_deref_mut().unwrap_or_default());
+        }
+    }
+
+    /// The edits of `edit`, those of documents missing from `rope_of` are left as is
+    pub fn encode_workspace_edit<'a>(
+        self,
+        rope_of: impl Fn(&str) -> Option<&'a Rope>,
+        edit: &mut WorkspaceEdit,
+    ) {
+        for (uri, edits) in edit.changes.iter_mut().flatten() {
+            if let Some(rope) = rope_of(uri.as_str()) {
+                self.encode_edits(rope, edits);
+            }
+        }
+        let Some(DocumentChanges::Edits(document_edits)) = &mut edit.document_changes else {
+            return;
+        };
+        for document_edit in document_edits {
+            let Some(rope) = rope_of(document_edit.text_document.uri.as_str()) else {
+                continue;
+            };
+            for edit in document_edit.edits.iter_mut() {
+                let edit = match edit {
+                    OneOf::Left(edit) => edit,
+                    OneOf::Right(annotated) => &mut annotated.text_edit,
+                };
+                edit.range = self.encode_range(rope, edit.range);
+            }
+        }
+    }
+}
+
+/// Range of `data` in `rope`, counting chars
+pub fn data_range(data: &Data, rope: &Rope) -> Range {
+    Range::new(data.to_position_start(rope), data.to_position_end(rope))
+}
+
+/// Edit replacing `data` in `rope` with `new_text`
+pub fn data_edit(data: &Data, rope: &Rope, new_text: impl Into<String>) -> TextEdit {
+    TextEdit {
+        range: data_range(data, rope),
+        new_text: new_text.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_columns_after_multi_byte_characters() {
+        let rope = Rope::from_str("é 😀 dup\r\nx");
+        let dup = Position::new(0, 4);
+        assert_eq!(
+            Position::new(0, 5),
+            PositionEncoding::Utf16.encode(&rope, dup)
+        );
+        assert_eq!(
+            Position::new(0, 8),
+            PositionEncoding::Utf8.encode(&rope, dup)
+        );
+        assert_eq!(dup, PositionEncoding::Utf32.encode(&rope, dup));
+        for encoding in [PositionEncoding::Utf8, PositionEncoding::Utf16] {
+            assert_eq!(dup, encoding.decode(&rope, encoding.encode(&rope, dup)));
+        }
+        // inside the surrogate pair of the emoji
+        assert_eq!(
+            Position::new(0, 2),
+            PositionEncoding::Utf16.decode(&rope, Position::new(0, 3))
+        );
+        // inside the bytes of the emoji, and past the end of the line
+        assert_eq!(
+            Position::new(0, 2),
+            PositionEncoding::Utf8.decode(&rope, Position::new(0, 5))
+        );
+        assert_eq!(
+            Position::new(1, 3),
+            PositionEncoding::Utf8.decode(&rope, Position::new(1, 3))
+        );
+        assert_eq!(
+            Range::new(Position::new(0, 8), Position::new(0, 11)),
+            PositionEncoding::Utf8.data_range(&Data::new(4, 7, "dup"), &rope)
+        );
+    }
+
+    #[test]
+    fn data_ranges_stop_before_crlf() {
+        let rope = Rope::from_str("\\ é\r\nx");
+        let data = Data::new(0, 4, "\\ é\r");
+        assert_eq!(
+            Range::new(Position::new(0, 0), Position::new(0, 3)),
+            data_range(&data, &rope)
+        );
+    }
+
+    #[test]
+    fn negotiates_chars_when_offered() {
+        let mut client = ClientCapabilities::default();
+        assert_eq!(
+            PositionEncoding::Utf16,
+            PositionEncoding::negotiate(&client)
+        );
+        client.general = Some(lsp_types::GeneralClientCapabilities {
+            position_encodings: Some(vec![
+                PositionEncodingKind::UTF8,
+                PositionEncodingKind::UTF32,
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(
+            PositionEncoding::Utf32,
+            PositionEncoding::negotiate(&client)
+        );
+        client.general = Some(lsp_types::GeneralClientCapabilities {
+            position_encodings: Some(vec![
+                PositionEncodingKind::UTF16,
+                PositionEncodingKind::UTF8,
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(PositionEncoding::Utf8, PositionEncoding::negotiate(&client));
+    }
+}
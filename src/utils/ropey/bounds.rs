@@ -0,0 +1,133 @@
+use std::ops::Range as CharRange;
+
+use lsp_types::{Position, Range};
+use ropey::{Rope, RopeSlice};
+
+/// Char indices of LSP positions and slices of the rope that never panic, positions from the
+/// client or from stale edits can point anywhere
+pub trait RopeBoundsCheck {
+    /// Char index where `line` starts, None past the last line
+    fn line_start(&self, line: usize) -> Option<usize>;
+    /// Chars on `line` without its line break, None past the last line
+    fn line_len(&self, line: usize) -> Option<usize>;
+    /// Char index of `position`. A character past the end of its line means the end of the
+    /// line, as the protocol asks, a line past the last one has no index
+    fn char_at(&self, position: Position) -> Option<usize>;
+    /// Char indices of `range`, None when either end has none or the ends are reversed
+    fn char_range(&self, range: Range) -> Option<CharRange<usize>>;
+    /// Position of the char index `ix`, None past the end. The `\n` of a `\r\n` line break is
+    /// at the end of its line, like the `\r`, positions never split a line break
+    fn position_of(&self, ix: usize) -> Option<Position>;
+    /// Chars `start..end`, None unless `start <= end <= len_chars`
+    fn slice_chars(&self, start: usize, end: usize) -> Option<RopeSlice<'_>>;
+    /// Text of `range`
+    fn slice_range(&self, range: Range) -> Option<RopeSlice<'_>>;
+    /// Range from the start to the end of the document
+    fn whole_range(&self) -> Range;
+}
+
+fn is_line_break(chr: char) -> bool {
+    matches!(
+        chr,
+        '\n' | '\r' | '\u{000B}' | '\u{000C}' | '\u{0085}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+impl RopeBoundsCheck for Rope {
+    fn line_start(&self, line: usize) -> Option<usize> {
+        self.try_line_to_char(line).ok()
+    }
+
+    fn line_len(&self, line: usize) -> Option<usize> {
+        let text = self.get_line(line)?;
+        let mut len = text.len_chars();
+        while len > 0 && is_line_break(text.char(len - 1)) {
+            len -= 1;
+        }
+        Some(len)
+    }
+
+    fn char_at(&self, position: Position) -> Option<usize> {
+        let line = position.line as usize;
+        let character = (position.character as usize).min(self.line_len(line)?);
+        Some(self.line_start(line)? + character)
+    }
+
+    fn char_range(&self, range: Range) -> Option<CharRange<usize>> {
+        let (start, end) = (self.char_at(range.start)?, self.char_at(range.end)?);
+        (start <= end).then_some(start..end)
+    }
+
+    fn position_of(&self, ix: usize) -> Option<Position> {
+        let line = self.try_char_to_line(ix).ok()?;
+        let character = (ix - self.line_start(line)?).min(self.line_len(line)?);
+        Some(Position::new(line as u32, character as u32))
+    }
+
+    fn slice_chars(&self, start: usize, end: usize) -> Option<RopeSlice<'_>> {
+        if start > end {
+            return None;
+        }
+        self.get_slice(start..end)
+    }
+
+    fn slice_range(&self, range: Range) -> Option<RopeSlice<'_>> {
+        let range = self.char_range(range)?;
+        self.get_slice(range)
+    }
+
+    fn whole_range(&self) -> Range {
+        let last_line = self.len_lines() - 1;
+        Range {
+            start: Position::new(0, 0),
+            end: Position::new(
+                last_line as u32,
+                self.line_len(last_line).unwrap_or_default() as u32,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_at_document_boundaries() {
+        let rope = Rope::from_str("dup\r\n: sq ;\n");
+        assert_eq!(Some(0), rope.char_at(Position::new(0, 0)));
+        // past the end of a line is its end, not the next line
+        assert_eq!(Some(3), rope.char_at(Position::new(0, 99)));
+        assert_eq!(Some(11), rope.char_at(Position::new(1, 6)));
+        assert_eq!(Some(12), rope.char_at(Position::new(2, 0)));
+        assert_eq!(None, rope.char_at(Position::new(3, 0)));
+        assert_eq!(Some(Position::new(2, 0)), rope.position_of(12));
+        assert_eq!(None, rope.position_of(13));
+        assert_eq!(Some(Position::new(0, 3)), rope.position_of(4));
+        assert_eq!(
+            Range::new(Position::new(0, 0), Position::new(2, 0)),
+            rope.whole_range()
+        );
+    }
+
+    #[test]
+    fn slices_at_document_boundaries() {
+        let rope = Rope::from_str("dup *");
+        assert_eq!(Some("*".into()), rope.slice_chars(4, 5).map(String::from));
+        assert_eq!(
+            Some(String::new()),
+            rope.slice_chars(5, 5).map(String::from)
+        );
+        assert_eq!(None, rope.slice_chars(5, 6));
+        assert_eq!(None, rope.slice_chars(3, 2));
+        let range = |start, end| Range::new(Position::new(0, start), Position::new(0, end));
+        assert_eq!(
+            Some("*".into()),
+            rope.slice_range(range(4, 9)).map(String::from)
+        );
+        assert_eq!(None, rope.slice_range(range(4, 0)));
+        let empty = Rope::new();
+        assert_eq!(Some(0), empty.char_at(Position::new(0, 3)));
+        assert_eq!(Range::default(), empty.whole_range());
+    }
+}
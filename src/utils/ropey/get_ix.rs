@@ -1,35 +1,81 @@
-use lsp_types::{request::GotoTypeDefinitionParams, CompletionParams, HoverParams};
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use lsp_types::{
+    request::GotoTypeDefinitionParams, CompletionParams, HoverParams, LinkedEditingRangeParams,
+    Position, ReferenceParams, RenameParams, TextDocumentPositionParams,
+};
 use ropey::Rope;
 
+use super::bounds::RopeBoundsCheck;
+
 pub trait GetIx<T> {
-    fn get_ix(&self, params: &T) -> usize;
+    /// Char index of the position in `params`, an error for lines past the end
+    fn get_ix(&self, params: &T) -> Result<usize>;
+}
+
+fn at_position(rope: &Rope, position: Position) -> Result<usize> {
+    rope.char_at(position).ok_or(Error::OutOfBounds(position))
 }
 
 impl GetIx<CompletionParams> for Rope {
-    fn get_ix(&self, params: &CompletionParams) -> usize {
-        self.line_to_char(params.text_document_position.position.line as usize)
-            + params.text_document_position.position.character as usize
+    fn get_ix(&self, params: &CompletionParams) -> Result<usize> {
+        at_position(self, params.text_document_position.position)
     }
 }
 
 impl GetIx<HoverParams> for Rope {
-    fn get_ix(&self, params: &HoverParams) -> usize {
-        self.line_to_char(params.text_document_position_params.position.line as usize)
-            + params.text_document_position_params.position.character as usize
+    fn get_ix(&self, params: &HoverParams) -> Result<usize> {
+        at_position(self, params.text_document_position_params.position)
     }
 }
 
 impl GetIx<GotoTypeDefinitionParams> for Rope {
-    fn get_ix(&self, params: &GotoTypeDefinitionParams) -> usize {
-        self.line_to_char(params.text_document_position_params.position.line as usize)
-            + params.text_document_position_params.position.character as usize
+    fn get_ix(&self, params: &GotoTypeDefinitionParams) -> Result<usize> {
+        at_position(self, params.text_document_position_params.position)
+    }
+}
+
+impl GetIx<LinkedEditingRangeParams> for Rope {
+    fn get_ix(&self, params: &LinkedEditingRangeParams) -> Result<usize> {
+        at_position(self, params.text_document_position_params.position)
+    }
+}
+
+impl GetIx<TextDocumentPositionParams> for Rope {
+    fn get_ix(&self, params: &TextDocumentPositionParams) -> Result<usize> {
+        at_position(self, params.position)
+    }
+}
+
+impl GetIx<RenameParams> for Rope {
+    fn get_ix(&self, params: &RenameParams) -> Result<usize> {
+        at_position(self, params.text_document_position.position)
+    }
+}
+
+impl GetIx<ReferenceParams> for Rope {
+    fn get_ix(&self, params: &ReferenceParams) -> Result<usize> {
+        at_position(self, params.text_document_position.position)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use lsp_types::{TextDocumentIdentifier, Url};
+
     #[test]
-    fn no_op() {
-        //TODO: actually test this
+    fn positions_past_the_end() {
+        let rope = Rope::from_str("dup\ndrop");
+        let params = |line, character| TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::parse("file:///a.forth").unwrap(),
+            },
+            position: Position::new(line, character),
+        };
+        assert_eq!(6, rope.get_ix(&params(1, 2)).unwrap());
+        assert_eq!(3, rope.get_ix(&params(0, 9)).unwrap());
+        assert!(rope.get_ix(&params(2, 0)).is_err());
     }
 }
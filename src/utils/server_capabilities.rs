@@ -1,11 +1,37 @@
-use lsp_types::{OneOf, ServerCapabilities, TextDocumentSyncKind};
+use crate::{
+    code_actions::{
+        fix_all::FIX_ALL_KIND, normalize_word_case::NORMALIZE_WORD_CASE_KIND,
+        organize_definitions::ORGANIZE_DEFINITIONS_KIND,
+    },
+    utils::handlers::{
+        request_execute_command::COMMANDS, request_semantic_tokens::semantic_tokens_legend,
+    },
+};
+
+use lsp_types::{
+    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, OneOf, ServerCapabilities,
+    TextDocumentSyncKind,
+};
+
+/// Characters that start string words (`."`, `S"`), bracketed immediate words (`[']`)
+/// and ticks, which are not part of the client's notion of an identifier
+pub const COMPLETION_TRIGGER_CHARACTERS: [&str; 4] = [".", "\"", "[", "'"];
 
 pub fn forth_lsp_capabilities() -> ServerCapabilities {
     ServerCapabilities {
-        text_document_sync: Some(lsp_types::TextDocumentSyncCapability::Kind(
-            TextDocumentSyncKind::INCREMENTAL,
+        text_document_sync: Some(lsp_types::TextDocumentSyncCapability::Options(
+            lsp_types::TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                will_save_wait_until: Some(true),
+                save: Some(lsp_types::TextDocumentSyncSaveOptions::SaveOptions(
+                    lsp_types::SaveOptions {
+                        include_text: Some(true),
+                    },
+                )),
+                ..Default::default()
+            },
         )),
-        // workspace_symbol_provider
         workspace: Some(lsp_types::WorkspaceServerCapabilities {
             workspace_folders: Some(lsp_types::WorkspaceFoldersServerCapabilities {
                 supported: Some(true),
@@ -15,7 +41,57 @@ pub fn forth_lsp_capabilities() -> ServerCapabilities {
         }),
         hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
         definition_provider: Some(OneOf::Left(true)),
-        completion_provider: Some(lsp_types::CompletionOptions::default()),
+        completion_provider: Some(lsp_types::CompletionOptions {
+            trigger_characters: Some(
+                COMPLETION_TRIGGER_CHARACTERS
+                    .iter()
+                    .map(|chr| chr.to_string())
+                    .collect(),
+            ),
+            completion_item: Some(lsp_types::CompletionOptionsCompletionItem {
+                label_details_support: Some(true),
+            }),
+            ..Default::default()
+        }),
+        color_provider: Some(lsp_types::ColorProviderCapability::Simple(true)),
+        references_provider: Some(OneOf::Left(true)),
+        moniker_provider: Some(OneOf::Left(true)),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Right(lsp_types::RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        linked_editing_range_provider: Some(
+            lsp_types::LinkedEditingRangeServerCapabilities::Simple(true),
+        ),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        folding_range_provider: Some(lsp_types::FoldingRangeProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(
+            lsp_types::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                lsp_types::SemanticTokensOptions {
+                    legend: semantic_tokens_legend(),
+                    full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
+                    ..Default::default()
+                },
+            ),
+        ),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::new(FIX_ALL_KIND),
+                CodeActionKind::new(ORGANIZE_DEFINITIONS_KIND),
+                CodeActionKind::new(NORMALIZE_WORD_CASE_KIND),
+                CodeActionKind::REFACTOR_REWRITE,
+            ]),
+            work_done_progress_options: Default::default(),
+            resolve_provider: None,
+        })),
+        execute_command_provider: Some(lsp_types::ExecuteCommandOptions {
+            commands: COMMANDS.iter().map(|command| command.to_string()).collect(),
+            work_done_progress_options: Default::default(),
+        }),
         ..Default::default()
     }
 }
@@ -0,0 +1,62 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+/// Edits, swapping two neighbouring characters included, to turn `a` into `b` ignoring
+/// ASCII case
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().map(|chr| chr.to_ascii_uppercase()).collect();
+    let b: Vec<char> = b.chars().map(|chr| chr.to_ascii_uppercase()).collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// The candidate closest to `word`, when it is close enough to be a likely typo. Ties go
+/// to the candidate that comes first
+pub fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (word.chars().count() / 3).max(1);
+    candidates
+        .filter(|candidate| !candidate.is_empty())
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(0, edit_distance("dup", "DUP"));
+        assert_eq!(1, edit_distance("dpu", "dup"));
+        assert_eq!(2, edit_distance("frob", "drop"));
+        assert_eq!(1, edit_distance("swa", "swap"));
+        assert_eq!(3, edit_distance("", "rot"));
+    }
+
+    #[test]
+    fn closest_within_limit() {
+        let words = ["DUP", "DROP", "SWAP", "counter"];
+        assert_eq!(Some("DROP"), closest("drpo", words.into_iter()));
+        assert_eq!(Some("DUP"), closest("drp", words.into_iter()));
+        assert_eq!(Some("counter"), closest("countr", words.into_iter()));
+        assert_eq!(None, closest("frob", words.into_iter()));
+    }
+}
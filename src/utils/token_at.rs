@@ -0,0 +1,154 @@
+use crate::utils::ropey::bounds::RopeBoundsCheck;
+
+use forth_lexer::{context::ContextTokens, token::Token};
+use lsp_types::Range;
+use ropey::Rope;
+
+/// What kind of token a `WordSpan` is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WordKind {
+    Word,
+    Number,
+    Colon,
+    Semicolon,
+}
+
+/// Where a word of a document is, in chars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WordSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: WordKind,
+}
+
+impl WordSpan {
+    pub fn text(&self, rope: &Rope) -> String {
+        rope.slice_chars(self.start, self.end)
+            .map(String::from)
+            .unwrap_or_default()
+    }
+
+    pub fn range(&self, rope: &Rope) -> Range {
+        let position = |ix: usize| {
+            rope.position_of(ix.min(rope.len_chars()))
+                .unwrap_or_default()
+        };
+        Range::new(position(self.start), position(self.end))
+    }
+}
+
+/// The words of `tokens` in order, the way Forth reads them. Only whitespace separates words
+/// and a leading `:` or `;` is a word of its own. Comments and the text of strings hold none
+pub fn word_spans(tokens: &[Token]) -> Vec<WordSpan> {
+    ContextTokens::new(tokens.iter().cloned())
+        .filter(|context| !context.in_comment && !context.in_string)
+        .filter_map(|context| {
+            let (data, kind) = match context.token {
+                Token::Word(data) => (data, WordKind::Word),
+                Token::Number(data) => (data, WordKind::Number),
+                Token::Colon(data) => (data, WordKind::Colon),
+                Token::Semicolon(data) => (data, WordKind::Semicolon),
+                _ => return None,
+            };
+            Some(WordSpan {
+                start: data.start,
+                // the lexer leaves `:` ending where it starts
+                end: data.end.max(data.start + data.value.chars().count()),
+                kind,
+            })
+        })
+        .collect()
+}
+
+pub trait WordTokenAt {
+    /// The word at `ix`, a cursor right after a word also counts, unless another word starts
+    /// there
+    fn word_at(&self, ix: usize) -> Option<&WordSpan>;
+    /// Like `word_at`, but only words that can name a definition, no numbers, `:` or `;`
+    fn word_token_at(&self, ix: usize) -> Option<&WordSpan>;
+}
+
+impl WordTokenAt for [WordSpan] {
+    fn word_at(&self, ix: usize) -> Option<&WordSpan> {
+        // the first word ending at or after `ix`, then the one after it when that starts at `ix`
+        let first = self.partition_point(|span| span.end < ix);
+        let mut candidates = self[first..].iter().take(2).filter(|span| span.start <= ix);
+        let ret = candidates.next()?;
+        match candidates.next() {
+            Some(next) if ret.end == ix => Some(next),
+            _ => Some(ret),
+        }
+    }
+
+    fn word_token_at(&self, ix: usize) -> Option<&WordSpan> {
+        self.word_at(ix).filter(|span| span.kind == WordKind::Word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forth_lexer::parser::Lexer;
+
+    use super::*;
+
+    fn word_at(source: &str, ix: usize) -> Option<String> {
+        let mut lexer = Lexer::new(source);
+        let words = word_spans(&lexer.parse());
+        words
+            .word_at(ix)
+            .map(|span| span.text(&Rope::from_str(source)))
+    }
+
+    #[test]
+    fn word_token_at_start_and_end() {
+        let mut lexer = Lexer::new("1 dup +");
+        let words = word_spans(&lexer.parse());
+        assert_eq!(2, words.word_token_at(2).unwrap().start);
+        assert_eq!(2, words.word_token_at(5).unwrap().start);
+    }
+
+    #[test]
+    fn word_token_at_skips_numbers() {
+        let mut lexer = Lexer::new("12 dup");
+        let words = word_spans(&lexer.parse());
+        assert!(words.word_token_at(1).is_none());
+        assert_eq!(Some("12".to_string()), word_at("12 dup", 1));
+    }
+
+    #[test]
+    fn words_at_the_end_of_the_file() {
+        assert_eq!(Some("dup".to_string()), word_at(": foo dup", 8));
+        assert_eq!(Some("dup".to_string()), word_at(": foo dup", 9));
+        assert_eq!(None, word_at(": foo dup ", 10));
+        assert_eq!(None, word_at("", 0));
+    }
+
+    #[test]
+    fn words_on_or_right_after_the_cursor() {
+        let source = "Should + find this";
+        assert_eq!(Some("Should".to_string()), word_at(source, 0));
+        assert_eq!(Some("+".to_string()), word_at(source, 7));
+        assert_eq!(Some("find".to_string()), word_at(source, 9));
+        assert_eq!(Some("find".to_string()), word_at(source, 12));
+        assert_eq!(Some("find".to_string()), word_at(source, 13));
+        assert_eq!(Some("this".to_string()), word_at(source, 14));
+    }
+
+    #[test]
+    fn colons_and_semicolons_are_words() {
+        assert_eq!(Some(":".to_string()), word_at(": foo ;", 0));
+        assert_eq!(Some(":".to_string()), word_at(": foo ;", 1));
+        assert_eq!(Some("foo".to_string()), word_at(": foo ;", 2));
+        assert_eq!(Some(";".to_string()), word_at(": foo ;", 7));
+        assert_eq!(Some("foo".to_string()), word_at(":foo", 1));
+    }
+
+    #[test]
+    fn words_with_quotes_but_not_strings() {
+        let source = ".\" hi there\" cr";
+        assert_eq!(Some(".\"".to_string()), word_at(source, 1));
+        assert_eq!(None, word_at(source, 4));
+        assert_eq!(Some("cr".to_string()), word_at(source, 14));
+        assert_eq!(None, word_at("\\ dup in a comment", 3));
+    }
+}
@@ -0,0 +1,36 @@
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use lsp_server::{Connection, Message, Notification};
+use lsp_types::{
+    notification::{LogTrace, Notification as _},
+    LogTraceParams, TraceValue,
+};
+
+/// Send `message` as `$/logTrace` unless tracing is off, `verbose` details are only
+/// computed when the client asked for them and left out when empty
+pub fn log_trace(
+    connection: &Connection,
+    trace: TraceValue,
+    message: impl FnOnce() -> String,
+    verbose: impl FnOnce() -> String,
+) -> Result<()> {
+    let params = match trace {
+        TraceValue::Off => return Ok(()),
+        TraceValue::Messages => LogTraceParams {
+            message: message(),
+            verbose: None,
+        },
+        TraceValue::Verbose => LogTraceParams {
+            message: message(),
+            verbose: Some(verbose()).filter(|verbose| !verbose.is_empty()),
+        },
+    };
+    connection
+        .sender
+        .send(Message::Notification(Notification::new(
+            LogTrace::METHOD.to_string(),
+            params,
+        )))
+        .map_err(|err| Error::SendError(err.to_string()))
+}
@@ -0,0 +1,283 @@
+// the LSP test server of `common` is not used here
+#[allow(dead_code)]
+mod common;
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+use common::test_dir;
+use serde_json::json;
+
+fn run(arg: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .arg(arg)
+        .output()
+        .expect("Runs the server binary")
+}
+
+#[test]
+fn version_names_the_release() {
+    let output = run("--version");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(&format!("forth-lsp {} (", env!("CARGO_PKG_VERSION"))));
+}
+
+#[test]
+fn capabilities_are_json() {
+    let output = run("--capabilities");
+    assert!(output.status.success());
+    let capabilities: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(true, capabilities["hoverProvider"]);
+}
+
+#[test]
+fn config_schema_is_json() {
+    let output = run("--config-schema");
+    assert!(output.status.success());
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(".forth-lsp.toml", schema["title"]);
+    assert!(schema["properties"]["format"].is_object());
+}
+
+#[test]
+fn unknown_arguments_fail() {
+    let output = run("--frobnicate");
+    assert_eq!(Some(2), output.status.code());
+}
+
+#[test]
+fn failures_name_what_failed() {
+    let output = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args(["lint", "/nonexistent/forth"])
+        .output()
+        .expect("Runs the server binary");
+    assert_eq!(Some(1), output.status.code());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("forth-lsp: /nonexistent/forth: "));
+}
+
+/// Run a stdio session sending `messages`, returns the messages received and the exit code
+fn session(messages: &[serde_json::Value]) -> (Vec<serde_json::Value>, Option<i32>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Runs the server binary");
+    let mut stdin = child.stdin.take().unwrap();
+    for message in messages {
+        let content = message.to_string();
+        write!(
+            stdin,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .unwrap();
+    }
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let received = stdout
+        .split("Content-Length: ")
+        .filter_map(|frame| frame.split_once("\r\n\r\n"))
+        .map(|(_, content)| serde_json::from_str(content).unwrap())
+        .collect();
+    (received, output.status.code())
+}
+
+#[test]
+fn lifecycle_errors_and_exit_codes() {
+    let hover = |id: i32| {
+        json!({ "jsonrpc": "2.0", "id": id, "method": "textDocument/hover", "params": {
+            "textDocument": { "uri": "file:///a.forth" },
+            "position": { "line": 0, "character": 0 }
+        }})
+    };
+    let (received, code) = session(&[
+        hover(1),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "initialize", "params": { "capabilities": {} } }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+        hover(4),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]);
+    let response = |id: i32| {
+        received
+            .iter()
+            .find(|message| message["id"] == id)
+            .unwrap_or_else(|| panic!("No response to {id}"))
+    };
+    assert_eq!(-32002, response(1)["error"]["code"]);
+    assert_eq!("forth-lsp", response(2)["result"]["serverInfo"]["name"]);
+    assert!(response(3)["error"].is_null());
+    assert_eq!(-32600, response(4)["error"]["code"]);
+    assert_eq!(Some(0), code);
+
+    let (_, code) = session(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} } }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]);
+    assert_eq!(Some(1), code);
+}
+
+#[test]
+fn glossary_of_a_directory() {
+    let root = test_dir("cli-glossary");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(
+        root.join("math.forth"),
+        "\\ Square it.\n: sq ( n -- n ) dup * ;\n: cube dup sq * ;\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args(["glossary", root.to_str().unwrap()])
+        .output()
+        .expect("Runs the server binary");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(
+        "## `sq`\n\n`( n -- n )`\n\nSquare it.\n\nDefined in [math.forth:2](math.forth#L2)\n\nUsed by [`cube`](#word-cube)\n"
+    ));
+
+    let html = root.join("glossary.html");
+    let output = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args([
+            "glossary",
+            root.to_str().unwrap(),
+            "-o",
+            html.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Runs the server binary");
+    assert!(output.status.success());
+    assert!(std::fs::read_to_string(&html)
+        .unwrap()
+        .contains("<h2 id=\"word-cube\"><code>cube</code></h2>"));
+
+    assert_eq!(Some(2), run("glossary").status.code());
+}
+
+#[test]
+fn graph_of_a_directory() {
+    let root = test_dir("cli-graph");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("lib.forth"), ": sq dup * ;\n").unwrap();
+    std::fs::write(
+        root.join("main.forth"),
+        "include lib.forth\n: main 3 sq . ;\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args(["graph", root.to_str().unwrap(), "--format", "json"])
+        .output()
+        .expect("Runs the server binary");
+    assert!(output.status.success());
+    let graphs: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json!(["sq"]), graphs["words"]["main"]);
+    assert_eq!(json!(["lib.forth"]), graphs["files"]["main.forth"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args(["graph", root.to_str().unwrap()])
+        .output()
+        .expect("Runs the server binary");
+    let dot = String::from_utf8(output.stdout).unwrap();
+    assert!(dot.starts_with("digraph forth {"));
+    assert!(dot.contains("\"words:main\" -> \"words:sq\";"));
+    assert!(dot.contains("\"files:main.forth\" -> \"files:lib.forth\";"));
+}
+
+#[test]
+fn scip_index_of_a_directory() {
+    let root = test_dir("cli-index");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("lib.forth"), ": sq dup * ;\n").unwrap();
+    std::fs::write(root.join("main.forth"), "include lib.forth\n3 sq frob\n").unwrap();
+    std::fs::write(
+        root.join(".forth-lsp.toml"),
+        "[[builtin.words]]\nname = \"frob\"\n",
+    )
+    .unwrap();
+    let output = root.join("index.scip");
+    let status = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args(["index", root.to_str().unwrap(), "-o"])
+        .arg(&output)
+        .status()
+        .expect("Runs the server binary");
+    assert!(status.success());
+    let index = std::fs::read(&output).unwrap();
+    let contains = |needle: &str| {
+        index
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    };
+    let name = root.file_name().unwrap().to_str().unwrap();
+    assert!(contains(&format!(
+        "scip-forth . {} . `lib.forth`/SQ.",
+        name
+    )));
+    assert!(contains("scip-forth . forth-standard . DUP."));
+    assert!(contains("scip-forth . forth-standard . FROB."));
+    assert!(contains("main.forth"));
+
+    let status = Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+        .args(["index", root.to_str().unwrap(), "--format", "lsif"])
+        .status()
+        .expect("Runs the server binary");
+    assert_eq!(Some(2), status.code());
+}
+
+#[test]
+fn lint_reports_what_the_baseline_does_not_record() {
+    let root = test_dir("cli-lint");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("legacy.forth"), ": old frob ;\n").unwrap();
+    let _ = std::fs::remove_file(root.join(".forth-lsp.toml"));
+    let lint = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_forth-lsp"))
+            .arg("lint")
+            .arg(root.to_str().unwrap())
+            .args(args)
+            .output()
+            .expect("Runs the server binary")
+    };
+    let output = lint(&[]);
+    assert_eq!(Some(1), output.status.code());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        "legacy.forth:1:7: warning: Undefined word `frob`, did you mean `FROT`? [undefined-word]\n",
+        stdout
+    );
+
+    let baseline = root.join("baseline.json");
+    assert!(lint(&["--write-baseline", baseline.to_str().unwrap()])
+        .status
+        .success());
+    std::fs::write(
+        root.join(".forth-lsp.toml"),
+        "[diagnostics]\nbaseline = \"baseline.json\"\n",
+    )
+    .unwrap();
+    assert!(lint(&[]).status.success());
+
+    std::fs::write(root.join("legacy.forth"), ": old frob ;\n: new frob ;\n").unwrap();
+    let output = lint(&[]);
+    assert_eq!(Some(1), output.status.code());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("legacy.forth:2:7:"));
+    assert_eq!(1, stdout.lines().count());
+
+    // hints are printed without failing
+    std::fs::write(root.join("legacy.forth"), ": old frob ;  \n").unwrap();
+    let output = lint(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        "legacy.forth:1:13: hint: Trailing whitespace [trailing-whitespace]\n",
+        stdout
+    );
+}
@@ -0,0 +1,144 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use forth_lsp::{
+    server::{initialize, main_loop},
+    utils::server_capabilities::forth_lsp_capabilities,
+};
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use serde_json::{json, Value};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The server running `main_loop` on a thread, talked to over an in-memory connection
+pub struct TestServer {
+    client: Connection,
+    thread: Option<JoinHandle<()>>,
+    next_id: i32,
+    /// Result of the initialize request
+    pub initialize: Value,
+    /// Notifications the server sent so far, like published diagnostics
+    pub notifications: Vec<Notification>,
+    /// Requests the server sent so far, like `workspace/applyEdit`, left unanswered
+    pub requests: Vec<Request>,
+}
+
+impl TestServer {
+    /// Start and initialize a server, `capabilities` are the client capabilities
+    pub fn start(capabilities: Value) -> TestServer {
+        TestServer::initialized(json!({ "capabilities": capabilities }))
+    }
+
+    /// Start and initialize a server with `root` as its workspace
+    pub fn start_in(root: &Path, capabilities: Value) -> TestServer {
+        let root_uri = format!("file://{}", root.display());
+        TestServer::initialized(json!({
+            "capabilities": capabilities,
+            "workspaceFolders": [{ "uri": root_uri, "name": "root" }],
+        }))
+    }
+
+    fn initialized(params: Value) -> TestServer {
+        let (server, client) = Connection::memory();
+        let thread = thread::spawn(move || {
+            let capabilities = serde_json::to_value(forth_lsp_capabilities()).unwrap();
+            if let Some(params) = initialize(&server, capabilities).unwrap() {
+                main_loop(server, params).unwrap();
+            }
+        });
+        let mut ret = TestServer {
+            client,
+            thread: Some(thread),
+            next_id: 0,
+            initialize: Value::Null,
+            notifications: vec![],
+            requests: vec![],
+        };
+        ret.initialize = ret.result("initialize", params);
+        ret.notify("initialized", json!({}));
+        ret
+    }
+
+    pub fn notify(&mut self, method: &str, params: Value) {
+        self.client
+            .sender
+            .send(Message::Notification(Notification::new(
+                method.to_string(),
+                params,
+            )))
+            .unwrap();
+    }
+
+    /// Send a request and wait for its response, collecting notifications on the way
+    pub fn request(&mut self, method: &str, params: Value) -> Response {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.client
+            .sender
+            .send(Message::Request(Request::new(
+                id.clone(),
+                method.to_string(),
+                params,
+            )))
+            .unwrap();
+        loop {
+            match self.client.receiver.recv_timeout(TIMEOUT) {
+                Ok(Message::Response(response)) if response.id == id => return response,
+                Ok(Message::Response(_)) => {}
+                Ok(Message::Request(request)) => self.requests.push(request),
+                Ok(Message::Notification(notification)) => self.notifications.push(notification),
+                Err(err) => panic!("No response to {method}: {err}"),
+            }
+        }
+    }
+
+    /// The result of a request that must succeed
+    pub fn result(&mut self, method: &str, params: Value) -> Value {
+        let response = self.request(method, params);
+        if let Some(error) = response.error {
+            panic!("{method} failed: {error:?}");
+        }
+        response.result.unwrap_or(Value::Null)
+    }
+
+    pub fn open(&mut self, uri: &str, text: &str) {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": uri, "languageId": "forth", "version": 1, "text": text }
+            }),
+        );
+    }
+
+    pub fn shutdown(mut self) {
+        self.result("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+pub fn position(uri: &str, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character }
+    })
+}
+
+/// A new directory for a test, unique to the test run so parallel runs do not share it
+pub fn test_dir(name: &str) -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "forth-lsp-{}-{}-{}",
+        name,
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).expect("Must be able to create a test directory");
+    dir
+}
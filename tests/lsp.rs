@@ -0,0 +1,762 @@
+mod common;
+
+use common::{position, test_dir, TestServer};
+use serde_json::json;
+
+const URI: &str = "file:///virtual/main.forth";
+
+#[test]
+fn initialize_advertises_capabilities() {
+    let server = TestServer::start(json!({}));
+    let capabilities = &server.initialize["capabilities"];
+    assert_eq!(true, capabilities["hoverProvider"]);
+    assert_eq!(true, capabilities["definitionProvider"]);
+    assert!(capabilities["completionProvider"].is_object());
+    server.shutdown();
+}
+
+#[test]
+fn positions_follow_the_negotiated_encoding() {
+    for (offered, encoding, character) in [
+        (json!({}), "utf-16", 7),
+        (
+            json!({ "positionEncodings": ["utf-32", "utf-16"] }),
+            "utf-32",
+            6,
+        ),
+        (
+            json!({ "positionEncodings": ["utf-8", "utf-16"] }),
+            "utf-8",
+            9,
+        ),
+    ] {
+        let mut server = TestServer::start(json!({ "general": offered }));
+        assert_eq!(
+            encoding,
+            server.initialize["capabilities"]["positionEncoding"]
+        );
+        // the emoji takes two UTF-16 code units
+        server.open(URI, ".\" 😀\" frob\n");
+        server.result("textDocument/hover", position(URI, 0, 0));
+        let diagnostics = server
+            .notifications
+            .iter()
+            .find(|notification| notification.method == "textDocument/publishDiagnostics")
+            .expect("Diagnostics published on open");
+        assert_eq!(
+            character,
+            diagnostics.params["diagnostics"][0]["range"]["start"]["character"]
+        );
+        server.shutdown();
+    }
+}
+
+#[test]
+fn unhandled_requests_get_an_error() {
+    let mut server = TestServer::start(json!({}));
+    // not opened, so hover fails
+    let response = server.request("textDocument/hover", position(URI, 0, 0));
+    assert_eq!(-32803, response.error.unwrap().code);
+    let response = server.request("forth/unknown", json!({}));
+    assert_eq!(-32601, response.error.unwrap().code);
+    // bad params are an error for the client, not a crash
+    let response = server.request("textDocument/hover", json!({ "position": 3 }));
+    let error = response.error.unwrap();
+    assert_eq!(-32602, error.code);
+    assert!(error
+        .message
+        .contains("Invalid params for textDocument/hover"));
+    server.shutdown();
+}
+
+#[test]
+fn open_publishes_diagnostics() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": add1 1 + ;\nadd1 frob");
+    // the round trip guarantees the didOpen notification was handled
+    server.result("textDocument/hover", position(URI, 0, 2));
+    let diagnostics = server
+        .notifications
+        .iter()
+        .find(|notification| notification.method == "textDocument/publishDiagnostics")
+        .expect("Diagnostics published on open");
+    assert_eq!(URI, diagnostics.params["uri"]);
+    let diagnostic = &diagnostics.params["diagnostics"][0];
+    assert_eq!(
+        "Undefined word `frob`, did you mean `FROT`?",
+        diagnostic["message"]
+    );
+    assert_eq!("undefined-word", diagnostic["code"]);
+    server.shutdown();
+}
+
+#[test]
+fn hover_shows_builtin_documentation() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "1 dup");
+    let hover = server.result("textDocument/hover", position(URI, 0, 3));
+    assert_eq!("markdown", hover["contents"]["kind"]);
+    assert!(hover["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .starts_with("# `DUP`"));
+    server.shutdown();
+}
+
+#[test]
+fn hover_is_plain_text_when_the_client_prefers_it() {
+    let mut server = TestServer::start(json!({
+        "textDocument": { "hover": { "contentFormat": ["plaintext", "markdown"] } }
+    }));
+    server.open(URI, "1 dup");
+    let hover = server.result("textDocument/hover", position(URI, 0, 3));
+    assert_eq!("plaintext", hover["contents"]["kind"]);
+    let value = hover["contents"]["value"].as_str().unwrap();
+    assert!(value.starts_with("DUP ( x -- x x )"));
+    assert!(!value.contains('`'));
+    server.shutdown();
+}
+
+#[test]
+fn completion_offers_builtins_and_definitions() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": double-it 2 * ;\ndo");
+    let completion = server.result(
+        "textDocument/completion",
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": 1, "character": 2 }
+        }),
+    );
+    let labels: Vec<&str> = completion
+        .as_array()
+        .or_else(|| completion["items"].as_array())
+        .expect("Completion items")
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert!(labels.contains(&"double-it"), "{labels:?}");
+    assert!(labels.contains(&"do"), "{labels:?}");
+    server.shutdown();
+}
+
+#[test]
+fn completion_offers_every_kind_of_definition() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "variable dots\n: double-it 2 * ;\nd");
+    let completion = server.result(
+        "textDocument/completion",
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": 2, "character": 1 }
+        }),
+    );
+    let items = completion
+        .as_array()
+        .or_else(|| completion["items"].as_array())
+        .expect("Completion items");
+    let kind = |label: &str| {
+        items
+            .iter()
+            .find(|item| item["label"] == label)
+            .map(|item| item["kind"].clone())
+    };
+    // variable and function
+    assert_eq!(Some(json!(6)), kind("dots"));
+    assert_eq!(Some(json!(3)), kind("double-it"));
+    server.shutdown();
+}
+
+#[test]
+fn environment_queries_complete_and_hover() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "s\" max-\" environment?\n");
+    let completion = server.result("textDocument/completion", position(URI, 0, 7));
+    let labels: Vec<&str> = completion
+        .as_array()
+        .expect("Completion items")
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        vec!["MAX-CHAR", "MAX-D", "MAX-N", "MAX-U", "MAX-UD", "MAX-FLOAT"],
+        labels
+    );
+    assert_eq!(
+        json!({ "line": 0, "character": 3 }),
+        completion[0]["textEdit"]["range"]["start"]
+    );
+    server.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": "s\" /hold\" environment?\n" }]
+        }),
+    );
+    let hover = server.result("textDocument/hover", position(URI, 0, 5));
+    assert!(hover["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .starts_with("# `/HOLD`   `( -- n true )`"));
+    server.shutdown();
+}
+
+#[test]
+fn goto_definition_after_change() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "");
+    server.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{
+                "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+                "text": ": sq dup * ;\n3 sq"
+            }]
+        }),
+    );
+    let locations = server.result("textDocument/definition", position(URI, 1, 3));
+    assert_eq!(URI, locations[0]["uri"]);
+    assert_eq!(0, locations[0]["range"]["start"]["line"]);
+    server.shutdown();
+}
+
+#[test]
+fn goto_and_references_count_utf8_bytes() {
+    let mut server = TestServer::start(json!({ "general": { "positionEncodings": ["utf-8"] } }));
+    // é takes two UTF-8 bytes
+    server.open(URI, ": é-sq dup * ;\nxx é-sq");
+    let locations = server.result("textDocument/definition", position(URI, 1, 7));
+    assert_eq!(15, locations[0]["range"]["end"]["character"]);
+    let mut params = position(URI, 1, 7);
+    params["context"] = json!({ "includeDeclaration": true });
+    let locations = server.result("textDocument/references", params);
+    let ranges: Vec<(u64, u64, u64)> = locations
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|location| {
+            let range = &location["range"];
+            (
+                range["start"]["line"].as_u64().unwrap(),
+                range["start"]["character"].as_u64().unwrap(),
+                range["end"]["character"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(vec![(0, 2, 7), (1, 3, 8)], ranges);
+    server.shutdown();
+}
+
+#[test]
+fn stale_changes_are_dropped() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": sq dup * ;");
+    let change = |version: i32, text: &str| {
+        json!({
+            "textDocument": { "uri": URI, "version": version },
+            "contentChanges": [{
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 0, "character": 0 }
+                },
+                "text": text
+            }]
+        })
+    };
+    server.notify("textDocument/didChange", change(3, "3 sq\n"));
+    // version 2 arrives late, or is replayed, and is dropped without freezing the document
+    server.notify("textDocument/didChange", change(2, "x"));
+    server.notify("textDocument/didChange", change(3, "x"));
+    server.notify("textDocument/didChange", change(4, "sq "));
+    let locations = server.result("textDocument/definition", position(URI, 0, 0));
+    assert_eq!(1, locations[0]["range"]["start"]["line"]);
+    assert!(!server
+        .notifications
+        .iter()
+        .any(|notification| notification.method == "forth-lsp/resync"));
+    server.shutdown();
+}
+
+#[test]
+fn changes_outside_the_document_ask_for_the_whole_text() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": sq dup * ;");
+    let change = |version: i32, line: u32, text: &str| {
+        json!({
+            "textDocument": { "uri": URI, "version": version },
+            "contentChanges": [{
+                "range": {
+                    "start": { "line": line, "character": 0 },
+                    "end": { "line": line, "character": 0 }
+                },
+                "text": text
+            }]
+        })
+    };
+    server.notify("textDocument/didChange", change(2, 7, "x"));
+    server.notify("textDocument/didChange", change(3, 0, "y"));
+    // a request makes sure the notifications were handled
+    server.result("textDocument/definition", position(URI, 0, 0));
+    let resync: Vec<_> = server
+        .notifications
+        .iter()
+        .filter(|notification| notification.method == "forth-lsp/resync")
+        .collect();
+    assert_eq!(1, resync.len());
+    assert_eq!(
+        json!({ "uri": URI, "version": 2 }),
+        resync[0].params["textDocument"]
+    );
+    server.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 4 },
+            "contentChanges": [{ "text": "3 sq\n: sq dup * ;" }]
+        }),
+    );
+    let locations = server.result("textDocument/definition", position(URI, 0, 3));
+    assert_eq!(1, locations[0]["range"]["start"]["line"]);
+    server.shutdown();
+}
+
+#[test]
+fn changes_of_unknown_documents_are_reported_not_fatal() {
+    let mut server = TestServer::start(json!({}));
+    server.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": "file:///tmp/unknown.forth", "version": 2 },
+            "contentChanges": [{ "text": "1 2 +" }]
+        }),
+    );
+    server.open(URI, ": sq dup * ;\n2 sq");
+    let locations = server.result("textDocument/definition", position(URI, 1, 3));
+    assert_eq!(0, locations[0]["range"]["start"]["line"]);
+    server.shutdown();
+}
+
+#[test]
+fn save_reindexes_the_saved_text() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "3 sq");
+    server.notify(
+        "textDocument/didSave",
+        json!({
+            "textDocument": { "uri": URI },
+            "text": "3 sq\n: sq dup * ;\n"
+        }),
+    );
+    let locations = server.result("textDocument/definition", position(URI, 0, 2));
+    assert_eq!(1, locations[0]["range"]["start"]["line"]);
+    let diagnostics = server
+        .notifications
+        .iter()
+        .rfind(|notification| notification.method == "textDocument/publishDiagnostics")
+        .expect("Diagnostics published on save");
+    assert_eq!(json!([]), diagnostics.params["diagnostics"]);
+    server.shutdown();
+}
+
+#[test]
+fn goto_and_hover_load_included_files_outside_the_workspace() {
+    let dir = test_dir("lazy-include");
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(
+        dir.join("lib").join("sq.forth"),
+        "\\ Square it.\n: sq dup * ;",
+    )
+    .unwrap();
+    let uri = format!("file://{}", dir.join("app").join("main.forth").display());
+    let mut server = TestServer::start(json!({}));
+    server.open(&uri, "include ../lib/sq.forth\n3 sq");
+    let locations = server.result("textDocument/definition", position(&uri, 1, 3));
+    assert_eq!(
+        json!(format!(
+            "file://{}",
+            dir.join("lib").join("sq.forth").display()
+        )),
+        locations[0]["uri"]
+    );
+    let hover = server.result("textDocument/hover", position(&uri, 1, 3));
+    let hover = hover["contents"]["value"].as_str().unwrap();
+    assert!(hover.starts_with("# `sq`\n\nSquare it."));
+    server.shutdown();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn open_documents_shadow_their_scanned_copy_until_closed() {
+    let dir = test_dir("shadow-scanned");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.forth"), ": sq dup * ;\n").unwrap();
+    let uri = format!("file://{}", dir.join("main.forth").display());
+    let mut server = TestServer::start_in(&dir, json!({}));
+    let symbols = |server: &mut TestServer, query: &str| {
+        let symbols = server.result("workspace/symbol", json!({ "query": query }));
+        symbols.as_array().unwrap().len()
+    };
+    assert_eq!(1, symbols(&mut server, "sq"));
+    server.open(&uri, ": sq dup * ;\n: cube dup sq * ;\n");
+    assert_eq!(1, symbols(&mut server, "sq"));
+    assert_eq!(1, symbols(&mut server, "cube"));
+    let duplicates = server.result(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.duplicateFiles" }),
+    );
+    assert_eq!(json!([]), duplicates);
+    server.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": uri } }),
+    );
+    assert_eq!(1, symbols(&mut server, "sq"));
+    assert_eq!(0, symbols(&mut server, "cube"));
+    server.shutdown();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn nested_projects_lint_with_their_own_config() {
+    let dir = test_dir("nested-config");
+    std::fs::create_dir_all(dir.join("strict")).unwrap();
+    std::fs::write(
+        dir.join("strict/.forth-lsp.toml"),
+        "[builtin]\ncommon_extensions = false\n\n[diagnostics.severity]\nundefined-word = \"error\"\n",
+    )
+    .unwrap();
+    let outer = format!("file://{}", dir.join("main.forth").display());
+    let inner = format!("file://{}", dir.join("strict/main.forth").display());
+    let mut server = TestServer::start_in(&dir, json!({}));
+    server.open(&outer, "1 2 3 -rot frob\n");
+    server.open(&inner, "1 2 3 -rot frob\n");
+    server.result("textDocument/hover", position(&inner, 0, 0));
+    let diagnostics = |uri: &str| -> Vec<(String, u64)> {
+        let published = server
+            .notifications
+            .iter()
+            .rev()
+            .find(|notification| {
+                notification.method == "textDocument/publishDiagnostics"
+                    && notification.params["uri"] == uri
+            })
+            .expect("Diagnostics published on open");
+        published.params["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|diagnostic| {
+                let message = diagnostic["message"].as_str().unwrap();
+                let word = message.split('`').nth(1).unwrap().to_string();
+                (word, diagnostic["severity"].as_u64().unwrap())
+            })
+            .collect()
+    };
+    assert_eq!(vec![("frob".to_string(), 2)], diagnostics(&outer));
+    assert_eq!(
+        vec![("-rot".to_string(), 1), ("frob".to_string(), 1)],
+        diagnostics(&inner)
+    );
+    server.shutdown();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn goto_deferred_word_finds_its_noname_body() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "defer greet\n:noname .\" hi\" ;\nis greet\ngreet");
+    let locations = server.result("textDocument/definition", position(URI, 3, 2));
+    assert_eq!(1, locations[0]["range"]["start"]["line"]);
+    assert_eq!(0, locations[0]["range"]["start"]["character"]);
+    server.shutdown();
+}
+
+#[test]
+fn defining_a_word_updates_diagnostics_of_its_users() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "3 sq\n");
+    let lib = "file:///virtual/lib.forth";
+    server.open(lib, ": sq dup * ;\n");
+    // a request makes sure every notification before its response arrived
+    server.result("textDocument/hover", position(URI, 0, 0));
+    let published: Vec<usize> = server
+        .notifications
+        .iter()
+        .filter(|notification| {
+            notification.method == "textDocument/publishDiagnostics"
+                && notification.params["uri"] == URI
+        })
+        .map(|notification| notification.params["diagnostics"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(vec![1, 0], published);
+    server.shutdown();
+}
+
+#[test]
+fn rename_synonym_or_its_target() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": one 1 ;\nsynonym uno one\nuno one");
+    let mut rename = |line, character| {
+        let mut params = position(URI, line, character);
+        params["newName"] = json!("x");
+        let edit = server.result("textDocument/rename", params);
+        edit["changes"][URI].as_array().unwrap().len()
+    };
+    // the synonym and its one use
+    assert_eq!(2, rename(2, 1));
+    // the definition, the synonym target and the use
+    assert_eq!(3, rename(0, 3));
+    server.shutdown();
+}
+
+#[test]
+fn reference_count_groups_uses_by_file() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": sq dup * ;\n2 sq sq");
+    let count = server.result("forth-lsp/referenceCount", json!({ "word": "SQ" }));
+    assert_eq!(json!({ "total": 2, "files": { URI: 2 } }), count);
+    server.shutdown();
+}
+
+#[test]
+fn debug_tokens_of_an_open_document() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": sq dup * ;\n");
+    let result = server.result(
+        "forth-lsp/debugTokens",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    let tokens: Vec<(&str, &str)> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|token| {
+            (
+                token["kind"].as_str().unwrap(),
+                token["text"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            ("Colon", ":"),
+            ("Word", "sq"),
+            ("Word", "dup"),
+            ("Word", "*"),
+            ("Semicolon", ";"),
+        ],
+        tokens
+    );
+    assert_eq!(
+        json!({ "line": 0, "character": 2 }),
+        result[1]["range"]["start"]
+    );
+    server.shutdown();
+}
+
+#[test]
+fn stack_effect_of_a_snippet_uses_open_definitions() {
+    let mut server = TestServer::start(json!({}));
+    server.open(
+        URI,
+        ": sq ( n1 -- n2 ) dup * ;\n: pair ( x -- x x ) dup ;\n",
+    );
+    let result = server.result("forth-lsp/stackEffect", json!({ "snippet": "pair sq +" }));
+    assert_eq!(json!("( x1 -- x2 )"), result["effect"]);
+    let depths: Vec<i64> = result["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|step| step["depth"].as_i64().unwrap())
+        .collect();
+    assert_eq!(vec![1, 1, 0], depths);
+    server.shutdown();
+}
+
+#[test]
+fn comment_banners_fold() {
+    let mut server = TestServer::start(json!({}));
+    assert_eq!(
+        json!(true),
+        server.initialize["capabilities"]["foldingRangeProvider"]
+    );
+    server.open(URI, "\\ =====\n\\ Math\n\\ =====\n: sq dup * ;\n");
+    let folds = server.result(
+        "textDocument/foldingRange",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!(
+        json!([{ "startLine": 0, "endLine": 2, "kind": "comment", "collapsedText": "Math" }]),
+        folds
+    );
+    server.shutdown();
+}
+
+#[test]
+fn document_symbols_nest_structure_fields() {
+    let mut server = TestServer::start(json!({}));
+    server.open(
+        URI,
+        "begin-structure point\n  field: p.x\n  field: p.y\nend-structure\n: origin 0 0 ;\n",
+    );
+    let symbols = server.result(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!("point", symbols[0]["name"]);
+    assert_eq!("p.y", symbols[0]["children"][1]["name"]);
+    assert_eq!("origin", symbols[1]["name"]);
+    let diagnostics = server
+        .notifications
+        .iter()
+        .find(|notification| notification.method == "textDocument/publishDiagnostics")
+        .expect("Diagnostics published on open");
+    assert_eq!(json!([]), diagnostics.params["diagnostics"]);
+    server.shutdown();
+}
+
+#[test]
+fn stats_command_counts_the_index() {
+    let mut server = TestServer::start(json!({}));
+    let commands = &server.initialize["capabilities"]["executeCommandProvider"]["commands"];
+    assert_eq!(
+        json!([
+            "forth-lsp.stats",
+            "forth-lsp.glossary",
+            "forth-lsp.graph",
+            "forth-lsp.formatWorkspace",
+            "forth-lsp.duplicateFiles"
+        ]),
+        *commands
+    );
+    server.open(URI, ": add1 1 + ;\nadd1");
+    let stats = server.result(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.stats", "arguments": [] }),
+    );
+    assert_eq!(1, stats["files"]);
+    assert_eq!(1, stats["definitions"]);
+    assert_eq!(3, stats["references"]);
+    assert!(stats["indexBytes"].as_u64().unwrap() > 0);
+    assert!(stats["lastIndexMillis"].is_u64());
+    let response = server.request(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.unknown" }),
+    );
+    assert!(response.error.is_some());
+    server.shutdown();
+}
+
+#[test]
+fn glossary_command_lists_user_words() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, ": add1 1 + ;\n: add2 add1 add1 ;");
+    let glossary = server.result(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.glossary", "arguments": ["markdown"] }),
+    );
+    let glossary = glossary.as_str().unwrap();
+    assert!(glossary.starts_with("# Glossary\n\n<a id=\"word-add1\"></a>\n## `add1`"));
+    assert!(glossary.contains("Used by [`add2`](#word-add2)"));
+    let response = server.request(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.glossary", "arguments": ["pdf"] }),
+    );
+    assert!(response.error.is_some());
+    server.shutdown();
+}
+
+#[test]
+fn format_workspace_reports_changed_files() {
+    let mut server = TestServer::start(json!({ "workspace": { "applyEdit": true } }));
+    server.open(URI, ":   sq dup * ;");
+    server.open("file:///virtual/empty.forth", "");
+    let report = server.result(
+        "workspace/executeCommand",
+        json!({
+            "command": "forth-lsp.formatWorkspace",
+            "arguments": ["dry-run"],
+            "workDoneToken": "format",
+        }),
+    );
+    assert_eq!(1, report["changed"]);
+    assert_eq!(
+        json!([
+            { "uri": "file:///virtual/empty.forth", "changed": false },
+            { "uri": URI, "changed": true },
+        ]),
+        report["files"]
+    );
+    let progress: Vec<&str> = server
+        .notifications
+        .iter()
+        .filter(|notification| notification.method == "$/progress")
+        .map(|notification| notification.params["value"]["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(vec!["begin", "report", "report", "end"], progress);
+    server.result(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.formatWorkspace" }),
+    );
+    server.shutdown();
+}
+
+#[test]
+fn format_workspace_encodes_ranges() {
+    let mut server = TestServer::start(json!({ "workspace": { "applyEdit": true } }));
+    server.open(URI, ":   sq dup * ;\n.\" 😀\"");
+    server.result(
+        "workspace/executeCommand",
+        json!({ "command": "forth-lsp.formatWorkspace" }),
+    );
+    let apply = server
+        .requests
+        .iter()
+        .find(|request| request.method == "workspace/applyEdit")
+        .expect("Formatting applies an edit");
+    let ends: Vec<&serde_json::Value> = apply.params["edit"]["changes"][URI]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edit| &edit["range"]["end"])
+        .collect();
+    // the emoji is two UTF-16 code units
+    assert!(ends
+        .iter()
+        .all(|end| end["line"] == 0 || end["character"] == 6));
+    server.shutdown();
+}
+
+#[test]
+fn set_trace_logs_requests() {
+    let mut server = TestServer::start(json!({}));
+    server.open(URI, "1 dup");
+    server.result("textDocument/hover", position(URI, 0, 3));
+    let traced = |server: &TestServer| {
+        server
+            .notifications
+            .iter()
+            .filter(|notification| notification.method == "$/logTrace")
+            .map(|notification| notification.params.clone())
+            .collect::<Vec<_>>()
+    };
+    assert!(traced(&server).is_empty());
+    server.notify("$/setTrace", json!({ "value": "verbose" }));
+    server.result("textDocument/hover", position(URI, 0, 3));
+    // the trace of a response follows the response, the next round trip collects it
+    server.result("textDocument/hover", position(URI, 0, 3));
+    let traces = traced(&server);
+    assert_eq!(3, traces.len());
+    assert!(traces[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Received request 'textDocument/hover"));
+    assert!(traces[0]["verbose"]
+        .as_str()
+        .unwrap()
+        .contains("main.forth"));
+    assert!(traces[1]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Processing request took"));
+    server.shutdown();
+}